@@ -0,0 +1,70 @@
+// End-to-end benchmarks of the CLI file path
+// Run with: cargo bench -p bg-noise-reduction --bench cli
+//
+// Each iteration runs the built binary on a WAV file: process start-up, WAV
+// reading, noise reduction and WAV writing. The inputs are made once with the
+// generate command in a temporary directory. Throughput is reported as
+// samples per second and real-time factor, as in the core benchmarks.
+
+#[path = "../../core/benches/common/mod.rs"]
+mod common;
+
+use common::RealTime;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+const BINARY: &str = env!("CARGO_BIN_EXE_bg-noise-reduction");
+const SAMPLE_RATE: u32 = 16000;
+
+/// Run the binary with `args`, failing the benchmark if it fails
+fn run(args: &[&str]) {
+    let status = Command::new(BINARY).args(args).stdout(Stdio::null()).status().expect("failed to run the CLI");
+    assert!(status.success(), "bg-noise-reduction {} failed", args.join(" "));
+}
+
+/// Generate noisy speech of `seconds` into `dir`, named after its length
+fn input(dir: &Path, seconds: u32) -> PathBuf {
+    let path = dir.join(format!("input-{}s.wav", seconds));
+    let (duration, sample_rate) = (seconds.to_string(), SAMPLE_RATE.to_string());
+    run(&[
+        "generate",
+        "speech",
+        "--duration",
+        &duration,
+        "--sample-rate",
+        &sample_rate,
+        "--noise",
+        "pink",
+        "--snr",
+        "10",
+        path.to_str().unwrap(),
+    ]);
+    path
+}
+
+fn bench_denoise(c: &mut Criterion<RealTime>) {
+    let dir = std::env::temp_dir().join(format!("bg-noise-reduction-bench-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let output = dir.join("output.wav");
+
+    let mut group = c.benchmark_group("denoise");
+    group.sample_size(10);
+    for seconds in [1, 10, 60] {
+        let input = input(&dir, seconds);
+        group.throughput(Throughput::Elements(seconds as u64 * SAMPLE_RATE as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(format!("{}s", seconds)), &input, |b, input| {
+            b.iter(|| run(&["denoise", input.to_str().unwrap(), output.to_str().unwrap()]))
+        });
+    }
+    group.finish();
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().with_measurement(RealTime { sample_rate: SAMPLE_RATE });
+    targets = bench_denoise
+}
+criterion_main!(benches);
@@ -0,0 +1,243 @@
+//! Reading, denoising and writing WAV files
+
+use crate::options::{ProcessingOptions, Reference};
+use bg_noise_reduction_core::{
+    analysis, anc, dereverb, loudness, multichannel, residual, Algorithm, AutoSettings, ChannelMode, FilterSpec, NoiseReductionConfig,
+    Steering, FRAME_SIZE,
+};
+use hound::{WavReader, WavSpec, WavWriter};
+use std::error::Error;
+use std::io::Write;
+use std::path::Path;
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+/// Read 16-bit samples as f32 in -1.0 to 1.0, interleaved
+pub fn read_samples(reader: WavReader<std::io::BufReader<std::fs::File>>) -> Vec<f32> {
+    reader
+        .into_samples::<i16>()
+        .filter_map(|s| s.ok())
+        .map(|s| s as f32 / i16::MAX as f32)
+        .collect()
+}
+
+/// Read a WAV file as one buffer per channel, with its sample rate
+///
+/// Only 16-bit integer PCM is supported; other formats are an error rather than an empty signal.
+pub fn read_channels(path: &Path) -> Result<(Vec<Vec<f32>>, u32)> {
+    let reader = WavReader::open(path)?;
+    let spec = reader.spec();
+    if spec.sample_format != hound::SampleFormat::Int || spec.bits_per_sample != 16 {
+        return Err(format!("{}: {}-bit {:?} samples are not supported, only 16-bit integer PCM",
+            path.display(), spec.bits_per_sample, spec.sample_format).into());
+    }
+    if spec.channels == 0 || spec.sample_rate == 0 {
+        return Err(format!("{}: invalid WAV header ({} channels at {} Hz)", path.display(), spec.channels, spec.sample_rate).into());
+    }
+    Ok((multichannel::deinterleave(&read_samples(reader), spec.channels as usize), spec.sample_rate))
+}
+
+/// Read a WAV file at `sample_rate` and downmix it to mono
+pub fn read_mono(path: &Path, sample_rate: u32) -> Result<Vec<f32>> {
+    let (channels, file_rate) = read_channels(path)?;
+    if file_rate != sample_rate {
+        return Err(format!("{} is {} Hz but the input is {} Hz", path.display(), file_rate, sample_rate).into());
+    }
+    Ok(downmix(&channels))
+}
+
+/// Average of all channels
+pub fn downmix(channels: &[Vec<f32>]) -> Vec<f32> {
+    (0..channels[0].len())
+        .map(|i| channels.iter().map(|channel| channel[i]).sum::<f32>() / channels.len() as f32)
+        .collect()
+}
+
+/// Write channels as a 16-bit WAV file, clipping to full scale
+pub fn write_wav(path: &Path, channels: &[Vec<f32>], sample_rate: u32) -> Result<()> {
+    let spec = WavSpec {
+        channels: channels.len() as u16,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+
+    let mut writer = WavWriter::create(path, spec)?;
+    for sample in &multichannel::interleave(channels) {
+        let sample_i16 = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        writer.write_sample(sample_i16)?;
+    }
+    writer.finalize()?;
+    Ok(())
+}
+
+/// Denoise one WAV file with `config`, taking the per-file inputs (reference, far end, auto preset) from `options`
+/// and writing progress to `log`; with `residual_path`, also write what was removed
+pub fn denoise_file(
+    input_path: &Path,
+    output_path: &Path,
+    residual_path: Option<&Path>,
+    config: NoiseReductionConfig,
+    options: &ProcessingOptions,
+    log: &mut dyn Write,
+) -> Result<()> {
+    let (channels, sample_rate) = read_channels(input_path)?;
+    let output_channels = denoise_channels(channels.clone(), sample_rate, config, options, log)?;
+    write_wav(output_path, &output_channels, sample_rate)?;
+    writeln!(log, "Output written to: {}", output_path.display())?;
+
+    if let Some(path) = residual_path {
+        // Beamforming or a reference channel change the channel count: compare the downmixes then
+        let (inputs, outputs) = if channels.len() == output_channels.len() {
+            (channels, output_channels)
+        } else {
+            (vec![downmix(&channels)], vec![downmix(&output_channels)])
+        };
+        let mut removed = Vec::new();
+        for (input, output) in inputs.iter().zip(&outputs) {
+            let alignment = residual::align(input, output);
+            writeln!(log, "Residual: output delay {} samples, gain compensation {:.3}", alignment.delay, alignment.gain)?;
+            removed.push(residual::residual_with(input, output, alignment));
+        }
+        write_wav(path, &removed, sample_rate)?;
+        writeln!(log, "Residual written to: {}", path.display())?;
+    }
+    Ok(())
+}
+
+/// Denoise the channels of a recording as [`denoise_file`] does, returning the processed channels
+pub fn denoise_channels(
+    mut channel_buffers: Vec<Vec<f32>>,
+    sample_rate: u32,
+    mut config: NoiseReductionConfig,
+    options: &ProcessingOptions,
+    log: &mut dyn Write,
+) -> Result<Vec<Vec<f32>>> {
+    let channels = channel_buffers.len();
+    config.sample_rate = sample_rate;
+    if let Some(profile) = &config.noise_profile {
+        if profile.sample_rate != sample_rate {
+            return Err(format!("noise profile is for {} Hz but the input is {} Hz", profile.sample_rate, sample_rate).into());
+        }
+    }
+
+    writeln!(log, "Input: {} Hz, {} channels", sample_rate, channels)?;
+    writeln!(log, "Duration: {:.2} seconds", channel_buffers[0].len() as f32 / sample_rate as f32)?;
+    writeln!(log, "Config: noise_frames={}, spectral_floor={}, over_subtraction={}, makeup_gain={}, remove_dc={}",
+        config.noise_frames, config.spectral_floor, config.over_subtraction, config.makeup_gain, config.remove_dc)?;
+    if let Some(beamforming) = &config.beamforming {
+        match &beamforming.steering {
+            Steering::Geometry { positions, azimuth_deg, elevation_deg } => writeln!(log,
+                "Beamforming: {} over {} microphones, looking at {} deg azimuth, {} deg elevation",
+                beamforming.method, positions.len(), azimuth_deg, elevation_deg
+            )?,
+            Steering::Estimated => writeln!(log, "Beamforming: {}, delays estimated with GCC-PHAT", beamforming.method)?,
+        }
+        if channels < 2 {
+            writeln!(log, "Warning: beamforming needs more than one channel, ignoring it")?;
+        }
+        if let Steering::Geometry { positions, .. } = &beamforming.steering {
+            let microphones = channels - usize::from(matches!(options.reference, Some(Reference::Channel(_))));
+            if positions.len() != microphones {
+                return Err(format!("{} microphone positions given for {} channels", positions.len(), microphones).into());
+            }
+        }
+    } else if channels > 1 {
+        writeln!(log, "Channels: {}", config.channel_mode)?;
+        if config.channel_mode == ChannelMode::MidSide && channels != 2 {
+            writeln!(log, "Warning: mid-side needs 2 channels, processing {} channels independently", channels)?;
+        }
+    }
+    if let Some(tracking) = &config.noise_tracking {
+        writeln!(log, "Noise tracking: {} over {} s window, percentile {}", tracking.method, tracking.window_s, tracking.percentile)?;
+    }
+    if let Algorithm::SpectralGate(gating) = &config.algorithm {
+        writeln!(log, "Algorithm: {}, n_std_thresh {}, prop_decrease {}, mask smoothing {} Hz / {} ms, time constant {} s",
+            config.algorithm, gating.n_std_thresh, gating.prop_decrease,
+            gating.freq_mask_smooth_hz, gating.time_mask_smooth_ms, gating.time_constant_s)?;
+    }
+    if let Some(harmonic) = &config.harmonic_regeneration {
+        writeln!(log, "Harmonic regeneration: mix {}", harmonic.mix)?;
+    }
+    if !config.pre_filters.is_empty() || !config.post_filters.is_empty() {
+        let list = |specs: &[FilterSpec]| specs.iter().map(|s| s.to_string()).collect::<Vec<_>>().join(", ");
+        writeln!(log, "Filters: pre [{}], post [{}]", list(&config.pre_filters), list(&config.post_filters))?;
+    }
+    if let Some(target) = &config.loudness {
+        writeln!(log, "Loudness target: {} LUFS, true peak ceiling {} dBTP (replaces makeup_gain)",
+            target.integrated_lufs, target.true_peak_ceiling)?;
+    }
+    if let Some(gate) = &config.gate {
+        writeln!(log, "Gate: {} keyed on {}, threshold {} dB, ratio {}, range {} dB, attack {} ms, hold {} ms, release {} ms",
+            gate.mode, gate.key, gate.threshold_db, gate.ratio, gate.range_db,
+            gate.attack_ms, gate.hold_ms, gate.release_ms)?;
+    }
+    if let Some(limiter) = &config.limiter {
+        writeln!(log, "Limiter: ceiling {} dBTP, release {} ms, look-ahead {} ms",
+            limiter.ceiling, limiter.release_ms, limiter.lookahead_ms)?;
+    }
+
+    writeln!(log, "Total samples: {}", channels * channel_buffers[0].len())?;
+
+    if let Some(reference) = &options.reference {
+        let anc_config = options.anc_config();
+        let reference_samples = match reference {
+            &Reference::Channel(channel) => {
+                if channel_buffers.len() < 2 || channel > channel_buffers.len() {
+                    return Err(format!("reference channel {} not available in a {}-channel input", channel, channel_buffers.len()).into());
+                }
+                writeln!(log, "Adaptive noise cancellation: {} with {} taps, reference channel {}", anc_config.method, anc_config.filter_length, channel)?;
+                channel_buffers.remove(channel - 1)
+            }
+            Reference::File(path) => {
+                writeln!(log, "Adaptive noise cancellation: {} with {} taps, reference {}", anc_config.method, anc_config.filter_length, path.display())?;
+                read_mono(path, sample_rate)?
+            }
+        };
+        for channel in &mut channel_buffers {
+            *channel = anc::cancel(channel, &reference_samples, &anc_config);
+        }
+    }
+
+    if options.is_auto() {
+        let analysis = analysis::analyze(&downmix(&channel_buffers), sample_rate);
+        writeln!(log, "Analysis: noise floor {:.1} dBFS, signal {:.1} dBFS, SNR {:.1} dB, noise flatness {:.2}, {:.2} s of leading noise",
+            analysis.noise_floor_db, analysis.signal_level_db, analysis.snr_db, analysis.noise_flatness, analysis.leading_noise_s)?;
+        options.apply_auto(&AutoSettings::from_analysis(&analysis, sample_rate), &mut config);
+        writeln!(log, "Auto preset: noise_frames={}, spectral_floor={:.3}, over_subtraction={:.2}, makeup_gain={:.2}",
+            config.noise_frames, config.spectral_floor, config.over_subtraction, config.makeup_gain)?;
+    }
+
+    if let Some(settings) = &mut config.dereverb {
+        if settings.t60.is_none() {
+            settings.t60 = dereverb::estimate_t60(&downmix(&channel_buffers), sample_rate);
+            match settings.t60 {
+                Some(t60) => writeln!(log, "Dereverb: estimated T60 {:.2} s", t60)?,
+                None => writeln!(log, "Dereverb: no free decays found, assuming T60 {:.2} s", dereverb::FALLBACK_T60)?,
+            }
+        } else if let Some(t60) = settings.t60 {
+            writeln!(log, "Dereverb: T60 {:.2} s", t60)?;
+        }
+    }
+
+    // Use core library for processing
+    let mut processor = bg_noise_reduction_core::AudioProcessor::new(FRAME_SIZE);
+    let output_channels = match &options.far_end {
+        Some(path) => {
+            let echo = &config.echo_cancellation;
+            writeln!(log, "Echo cancellation: far end {}, tail {} ms, step {}, double-talk threshold {}, residual suppression {}",
+                path.display(), echo.tail_ms, echo.step_size, echo.double_talk_threshold, echo.residual_suppression)?;
+            let far_end_samples = read_mono(path, sample_rate)?;
+            processor.process_channels_with_far_end(&channel_buffers, &far_end_samples, &config)
+        }
+        None => processor.process_channels(&channel_buffers, &config),
+    };
+
+    writeln!(log, "Processed {} frames", channel_buffers[0].len() / FRAME_SIZE)?;
+
+    let views: Vec<&[f32]> = output_channels.iter().map(Vec::as_slice).collect();
+    let stats = loudness::measure_channels(&views, sample_rate);
+    writeln!(log, "Output loudness: {:.1} LUFS, range {:.1} LU, true peak {:.1} dBTP",
+        stats.integrated_lufs, stats.loudness_range, stats.true_peak_dbtp)?;
+    Ok(output_channels)
+}
@@ -0,0 +1,290 @@
+//! Batch processing: expanding directories and globs into jobs, running them
+//! in parallel and reporting per-file status
+
+use crate::audio::denoise_file;
+use crate::options::ProcessingOptions;
+use bg_noise_reduction_core::NoiseReductionConfig;
+use clap::Args;
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+#[derive(Args)]
+pub struct BatchArgs {
+    #[command(flatten)]
+    options: ProcessingOptions,
+    /// Directory the denoised files are written to, mirroring the input tree
+    #[arg(long, value_name = "DIR")]
+    output_dir: PathBuf,
+    /// Output file name; {stem}, {name} and {ext} stand for the input's
+    #[arg(long, value_name = "TEMPLATE", default_value = "{stem}.wav")]
+    name: NameTemplate,
+    /// Files processed at once (default: one per core)
+    #[arg(short, long, value_name = "N")]
+    jobs: Option<usize>,
+    /// Process files whose output already exists instead of skipping them
+    #[arg(long)]
+    overwrite: bool,
+    /// Write the per-file status to FILE, as CSV if it ends in .csv and JSON otherwise
+    #[arg(long, value_name = "FILE")]
+    report: Option<PathBuf>,
+    /// Print each file's processing log
+    #[arg(short, long)]
+    verbose: bool,
+    /// WAV files, directories (searched recursively for .wav files) or glob patterns such as 'takes/**/*.wav'
+    #[arg(required = true)]
+    inputs: Vec<PathBuf>,
+}
+
+/// Output file name with `{stem}`, `{name}` and `{ext}` placeholders
+#[derive(Debug, Clone)]
+pub struct NameTemplate(String);
+
+impl NameTemplate {
+    fn render(&self, input: &Path) -> String {
+        let part = |s: Option<&std::ffi::OsStr>| s.map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        self.0
+            .replace("{stem}", &part(input.file_stem()))
+            .replace("{name}", &part(input.file_name()))
+            .replace("{ext}", &part(input.extension()))
+    }
+}
+
+impl FromStr for NameTemplate {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let rest = s.replace("{stem}", "").replace("{name}", "").replace("{ext}", "");
+        if rest.contains(['{', '}']) {
+            return Err(format!("unknown placeholder in '{}', expected {{stem}}, {{name}} or {{ext}}", s));
+        }
+        if rest.contains(['/', '\\']) || s.is_empty() {
+            return Err(format!("'{}' is not a file name", s));
+        }
+        Ok(NameTemplate(s.to_string()))
+    }
+}
+
+/// One input file and where its output goes
+struct Job {
+    input: PathBuf,
+    output: PathBuf,
+}
+
+/// What happened to one file
+enum Status {
+    Done,
+    Skipped,
+    Failed(String),
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Status::Done => write!(f, "done"),
+            Status::Skipped => write!(f, "skipped"),
+            Status::Failed(_) => write!(f, "failed"),
+        }
+    }
+}
+
+struct Outcome {
+    status: Status,
+    seconds: f64,
+}
+
+fn has_wildcard(s: &str) -> bool {
+    s.contains(['*', '?', '['])
+}
+
+/// Leading components of a glob pattern without wildcards, which matches are made relative to
+fn glob_base(pattern: &Path) -> PathBuf {
+    pattern
+        .components()
+        .take_while(|component| !matches!(component, Component::Normal(part) if has_wildcard(&part.to_string_lossy())))
+        .collect()
+}
+
+/// Files matched by a glob pattern, with their paths relative to `base`
+fn glob_files(pattern: &str, base: &Path) -> Result<Vec<(PathBuf, PathBuf)>> {
+    let options = glob::MatchOptions { case_sensitive: false, ..glob::MatchOptions::new() };
+    let mut files = Vec::new();
+    for entry in glob::glob_with(pattern, options)? {
+        let path = entry?;
+        if path.is_file() {
+            let relative = path.strip_prefix(base).unwrap_or(&path).to_path_buf();
+            files.push((path, relative));
+        }
+    }
+    Ok(files)
+}
+
+/// Expand the inputs into (file, path relative to the tree being mirrored) pairs
+///
+/// Files given directly keep only their name; files found under a directory or
+/// by a glob keep their path below the directory or the glob's literal prefix.
+fn expand_inputs(inputs: &[PathBuf]) -> Result<Vec<(PathBuf, PathBuf)>> {
+    let mut files = Vec::new();
+    for input in inputs {
+        let text = input.to_string_lossy();
+        let found = if input.is_dir() {
+            let pattern = format!("{}/**/*.wav", glob::Pattern::escape(text.trim_end_matches(['/', '\\'])));
+            glob_files(&pattern, input)?
+        } else if !input.exists() && has_wildcard(&text) {
+            glob_files(&text, &glob_base(input))?
+        } else {
+            // Missing files are kept so they show up as failed
+            vec![(input.clone(), PathBuf::from(input.file_name().unwrap_or(input.as_os_str())))]
+        };
+        if found.is_empty() {
+            eprintln!("Warning: no .wav files found for '{}'", text);
+        }
+        files.extend(found);
+    }
+
+    let mut seen = HashSet::new();
+    files.retain(|(path, _)| seen.insert(path.canonicalize().unwrap_or_else(|_| path.clone())));
+    Ok(files)
+}
+
+fn plan_jobs(args: &BatchArgs) -> Result<Vec<Job>> {
+    let mut jobs = Vec::new();
+    let mut outputs: HashMap<PathBuf, PathBuf> = HashMap::new();
+    for (input, relative) in expand_inputs(&args.inputs)? {
+        let directory = relative.parent().map_or_else(|| args.output_dir.clone(), |parent| args.output_dir.join(parent));
+        let output = directory.join(args.name.render(&relative));
+        if let Some(other) = outputs.insert(output.clone(), input.clone()) {
+            return Err(format!("{} and {} would both be written to {}", other.display(), input.display(), output.display()).into());
+        }
+        if output.canonicalize().ok().is_some_and(|output| input.canonicalize().ok() == Some(output)) {
+            return Err(format!("{} would be overwritten by its own output; choose another --output-dir or --name", input.display()).into());
+        }
+        jobs.push(Job { input, output });
+    }
+    if jobs.is_empty() {
+        return Err("no input files".into());
+    }
+    Ok(jobs)
+}
+
+fn run_job(job: &Job, config: &NoiseReductionConfig, args: &BatchArgs) -> Outcome {
+    let start = Instant::now();
+    if !args.overwrite && job.output.exists() {
+        return Outcome { status: Status::Skipped, seconds: 0.0 };
+    }
+
+    let mut log = Vec::new();
+    let result = if job.input.is_file() {
+        std::fs::create_dir_all(job.output.parent().unwrap_or(Path::new(".")))
+            .map_err(Into::into)
+            .and_then(|_| denoise_file(&job.input, &job.output, None, config.clone(), &args.options, &mut log))
+    } else {
+        Err(format!("Input file '{}' does not exist", job.input.display()).into())
+    };
+    if args.verbose && !log.is_empty() {
+        let _ = std::io::stdout().lock().write_all(&log);
+    }
+
+    let status = match result {
+        Ok(()) => Status::Done,
+        Err(e) => Status::Failed(e.to_string()),
+    };
+    Outcome { status, seconds: start.elapsed().as_secs_f64() }
+}
+
+/// Quote a CSV field when it needs it
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn write_report(path: &Path, jobs: &[Job], outcomes: &[Outcome]) -> Result<()> {
+    let error = |outcome: &Outcome| match &outcome.status {
+        Status::Failed(e) => e.clone(),
+        _ => String::new(),
+    };
+
+    let text = if path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("csv")) {
+        let mut text = String::from("input,output,status,seconds,error\n");
+        for (job, outcome) in jobs.iter().zip(outcomes) {
+            text += &format!(
+                "{},{},{},{:.3},{}\n",
+                csv_field(&job.input.to_string_lossy()),
+                csv_field(&job.output.to_string_lossy()),
+                outcome.status,
+                outcome.seconds,
+                csv_field(&error(outcome))
+            );
+        }
+        text
+    } else {
+        let files: Vec<_> = jobs
+            .iter()
+            .zip(outcomes)
+            .map(|(job, outcome)| {
+                serde_json::json!({
+                    "input": job.input,
+                    "output": job.output,
+                    "status": outcome.status.to_string(),
+                    "seconds": (outcome.seconds * 1000.0).round() / 1000.0,
+                    "error": matches!(outcome.status, Status::Failed(_)).then(|| error(outcome)),
+                })
+            })
+            .collect();
+        serde_json::to_string_pretty(&serde_json::json!({ "files": files }))? + "\n"
+    };
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, text).map_err(|e| format!("{}: {}", path.display(), e).into())
+}
+
+/// Denoise every input into the output directory, in parallel
+pub fn run(args: &BatchArgs) -> Result<()> {
+    let config = args.options.config()?;
+    let jobs = plan_jobs(args)?;
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(args.jobs.unwrap_or(0)).build()?;
+    println!("Processing {} files on {} workers", jobs.len(), pool.current_num_threads());
+
+    let finished = AtomicUsize::new(0);
+    let outcomes: Vec<Outcome> = pool.install(|| {
+        jobs.par_iter()
+            .map(|job| {
+                let outcome = run_job(job, &config, args);
+                let count = finished.fetch_add(1, Ordering::Relaxed) + 1;
+                match &outcome.status {
+                    Status::Failed(e) => eprintln!("[{}/{}] failed {}: {}", count, jobs.len(), job.input.display(), e),
+                    status => println!("[{}/{}] {} {} -> {}", count, jobs.len(), status, job.input.display(), job.output.display()),
+                }
+                outcome
+            })
+            .collect()
+    });
+
+    let count = |f: fn(&Status) -> bool| outcomes.iter().filter(|outcome| f(&outcome.status)).count();
+    let done = count(|status| matches!(status, Status::Done));
+    let skipped = count(|status| matches!(status, Status::Skipped));
+    let failed = count(|status| matches!(status, Status::Failed(_)));
+    let seconds: f64 = outcomes.iter().map(|outcome| outcome.seconds).sum();
+    println!("Processed {} files: {} done, {} skipped, {} failed ({:.1} s of work)", jobs.len(), done, skipped, failed, seconds);
+
+    if let Some(path) = &args.report {
+        write_report(path, &jobs, &outcomes)?;
+        println!("Report written to: {}", path.display());
+    }
+    if failed > 0 {
+        return Err(format!("{} of {} files failed", failed, jobs.len()).into());
+    }
+    Ok(())
+}
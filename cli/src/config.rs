@@ -0,0 +1,78 @@
+//! Config files, user presets and noise profiles on disk
+
+use bg_noise_reduction_core::{NoiseProfile, NoiseReductionConfig, PresetDefinition, PresetRegistry};
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// Directory holding user configuration: `$BG_NOISE_REDUCTION_CONFIG_DIR`, or
+/// `bg-noise-reduction` in the platform's config directory
+pub fn config_dir() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os("BG_NOISE_REDUCTION_CONFIG_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))
+        .map(|dir| dir.join("bg-noise-reduction"))
+}
+
+/// Built-in presets plus the user's `presets/<name>.toml` files
+pub fn load_presets() -> Result<PresetRegistry, String> {
+    let mut registry = PresetRegistry::default();
+    let Some(dir) = config_dir().map(|dir| dir.join("presets")) else {
+        return Ok(registry);
+    };
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Ok(registry);
+    };
+
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "toml"))
+        .collect();
+    paths.sort();
+    for path in paths {
+        let name = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+        let text = std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        registry.add(PresetDefinition::parse(name, &text).map_err(|e| format!("{}: {}", path.display(), e))?);
+    }
+    Ok(registry)
+}
+
+/// Read a configuration written by `dump-config`; options left out keep their defaults
+pub fn load_config(path: &Path) -> Result<NoiseReductionConfig, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    toml::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Render a configuration as TOML for `--config`
+///
+/// Goes through JSON so f32 values print in their shortest form (0.1, not
+/// 0.10000000149011612) and disabled stages, which JSON writes as null, can
+/// be left out.
+pub fn config_to_toml(config: &NoiseReductionConfig) -> Result<String, String> {
+    fn drop_nulls(value: &mut serde_json::Value) {
+        if let serde_json::Value::Object(map) = value {
+            map.retain(|_, field| !field.is_null());
+            map.values_mut().for_each(drop_nulls);
+        }
+    }
+
+    let json = serde_json::to_string(config).map_err(|e| e.to_string())?;
+    let mut value: serde_json::Value = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+    drop_nulls(&mut value);
+    toml::to_string_pretty(&value).map_err(|e| e.to_string())
+}
+
+/// Read a noise profile written by `learn-profile`
+pub fn load_profile(path: &Path) -> Result<NoiseProfile, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    serde_json::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Write a noise profile as JSON
+pub fn save_profile(path: &Path, profile: &NoiseProfile) -> Result<(), String> {
+    let json = serde_json::to_string(profile).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| format!("{}: {}", path.display(), e))
+}
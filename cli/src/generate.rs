@@ -0,0 +1,151 @@
+//! Seeded synthetic test signals, optionally mixed with noise at a target SNR
+
+use crate::audio::write_wav;
+use bg_noise_reduction_core::testsignal::{self, NoiseColor, Sweep};
+use clap::Args;
+use std::error::Error;
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+#[derive(Args)]
+pub struct GenerateArgs {
+    /// Length in seconds
+    #[arg(long, value_name = "SECONDS", default_value_t = 5.0)]
+    duration: f32,
+    /// Sample rate in Hz
+    #[arg(long, value_name = "HZ", default_value_t = 16000)]
+    sample_rate: u32,
+    /// Random seed; the same seed and options always give the same file
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    /// RMS level of the signal in dBFS
+    #[arg(long, value_name = "DB", default_value_t = -20.0, allow_negative_numbers = true)]
+    level: f32,
+    /// Hum fundamental in Hz
+    #[arg(long, value_name = "HZ", default_value_t = 50.0)]
+    hum_freq: f32,
+    /// Hum harmonics, including the fundamental
+    #[arg(long, value_name = "N", default_value_t = 8)]
+    harmonics: usize,
+    /// Chirp start frequency in Hz
+    #[arg(long, value_name = "HZ", default_value_t = 20.0)]
+    chirp_start: f32,
+    /// Chirp end frequency in Hz (default: just below Nyquist)
+    #[arg(long, value_name = "HZ")]
+    chirp_end: Option<f32>,
+    /// Chirp sweep: linear or exponential
+    #[arg(long, value_name = "SWEEP", default_value = "exponential")]
+    sweep: Sweep,
+    /// Add noise of this kind (white, pink, brown or hum) at --snr
+    #[arg(long, value_name = "KIND")]
+    noise: Option<Signal>,
+    /// SNR of the signal over the added noise in dB, over the whole file
+    #[arg(long, value_name = "DB", default_value_t = 10.0, allow_negative_numbers = true, requires = "noise")]
+    snr: f32,
+    /// Seconds of noise alone before the signal, for the noise estimate of the denoiser
+    #[arg(long, value_name = "SECONDS", default_value_t = 0.0, requires = "noise")]
+    lead_in: f32,
+    /// Also write the signal without the noise, the clean reference for compare
+    #[arg(long, value_name = "FILE", requires = "noise")]
+    clean: Option<PathBuf>,
+    /// white, pink, brown, hum, chirp or speech
+    signal: Signal,
+    /// WAV file to write
+    output: PathBuf,
+}
+
+/// Kind of signal to generate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Signal {
+    Noise(NoiseColor),
+    Hum,
+    Chirp,
+    Speech,
+}
+
+impl FromStr for Signal {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "hum" => Ok(Signal::Hum),
+            "chirp" => Ok(Signal::Chirp),
+            "speech" => Ok(Signal::Speech),
+            _ => s
+                .parse()
+                .map(Signal::Noise)
+                .map_err(|_| format!("unknown signal '{}' (expected white, pink, brown, hum, chirp or speech)", s)),
+        }
+    }
+}
+
+impl fmt::Display for Signal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Signal::Noise(color) => write!(f, "{} noise", color),
+            Signal::Hum => write!(f, "hum"),
+            Signal::Chirp => write!(f, "chirp"),
+            Signal::Speech => write!(f, "speech-like signal"),
+        }
+    }
+}
+
+fn synthesize(signal: Signal, len: usize, seed: u64, args: &GenerateArgs) -> Vec<f32> {
+    let nyquist = args.sample_rate as f32 / 2.0;
+    match signal {
+        Signal::Noise(color) => testsignal::noise(color, len, seed),
+        Signal::Hum => testsignal::hum(len, args.sample_rate, args.hum_freq, args.harmonics),
+        Signal::Chirp => {
+            let end = args.chirp_end.unwrap_or(0.95 * nyquist);
+            testsignal::chirp(len, args.sample_rate, args.chirp_start, end, args.sweep)
+        }
+        Signal::Speech => testsignal::speech_like(len, args.sample_rate, seed),
+    }
+}
+
+pub fn run(args: &GenerateArgs) -> Result<()> {
+    let nyquist = args.sample_rate as f32 / 2.0;
+    if args.duration <= 0.0 || args.lead_in < 0.0 {
+        return Err("--duration must be positive and --lead-in not negative".into());
+    }
+    if args.signal == Signal::Chirp && (args.chirp_start.max(args.chirp_end.unwrap_or(0.0)) >= nyquist || args.chirp_start < 0.0) {
+        return Err(format!("chirp frequencies must be between 0 and {} Hz", nyquist).into());
+    }
+
+    let rate = args.sample_rate as f32;
+    let lead_in = (args.lead_in * rate) as usize;
+    let mut body = synthesize(args.signal, (args.duration * rate) as usize, args.seed, args);
+    testsignal::set_level(&mut body, args.level);
+    let mut signal = vec![0.0; lead_in];
+    signal.extend(body);
+
+    let output = match args.noise {
+        Some(kind) => {
+            // A different seed, so noise of the same kind as the signal is not identical to it
+            let noise = synthesize(kind, signal.len(), args.seed.wrapping_add(1), args);
+            testsignal::mix_at_snr(&signal, &noise, args.snr)
+        }
+        None => signal.clone(),
+    };
+
+    let peak = output.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+    if peak > 1.0 {
+        eprintln!("Warning: peak {:.1} dBFS clips; lower --level", 20.0 * peak.log10());
+    }
+    write_wav(&args.output, &[output], args.sample_rate)?;
+    match args.noise {
+        Some(kind) => println!("Generated {} at {:.1} dBFS RMS with {} at {} dB SNR, {:.2} s at {} Hz, seed {}",
+            args.signal, args.level, kind, args.snr, signal.len() as f32 / rate, args.sample_rate, args.seed),
+        None => println!("Generated {} at {:.1} dBFS RMS, {:.2} s at {} Hz, seed {}",
+            args.signal, args.level, signal.len() as f32 / rate, args.sample_rate, args.seed),
+    }
+    println!("Output written to: {}", args.output.display());
+    if let Some(path) = &args.clean {
+        write_wav(path, &[signal], args.sample_rate)?;
+        println!("Clean signal written to: {}", path.display());
+    }
+    Ok(())
+}
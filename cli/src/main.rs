@@ -1,183 +1,352 @@
-use bg_noise_reduction_core::{NoiseReductionConfig, FRAME_SIZE};
-use hound::{WavReader, WavWriter, WavSpec};
+mod audio;
+mod batch;
+mod config;
+mod generate;
+mod options;
+mod spectrogram;
+
+use audio::{denoise_file, downmix, read_channels, read_mono};
+use batch::BatchArgs;
+use bg_noise_reduction_core::metrics::{self, Metrics};
+use bg_noise_reduction_core::{analysis, AudioProcessor, AutoSettings, NoiseReductionConfig, FRAME_SIZE};
+use clap::{Args, CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use generate::GenerateArgs;
+use options::{FilterOptions, ProcessingOptions};
+use spectrogram::SpectrogramArgs;
 use std::env;
-use std::path::Path;
-
-fn print_usage(program_name: &str) {
-    eprintln!("Audio Noise Reduction - Spectral Subtraction");
-    eprintln!();
-    eprintln!("Usage: {} [OPTIONS] <input.wav> <output.wav>", program_name);
-    eprintln!();
-    eprintln!("Options:");
-    eprintln!("  --noise-frames <N>        Number of frames for noise estimation (default: 10)");
-    eprintln!("  --spectral-floor <F>      Spectral floor, 0.0-1.0 (default: 0.1)");
-    eprintln!("                             Higher = more signal preserved, less noise reduction");
-    eprintln!("  --over-subtraction <F>    Over-subtraction factor (default: 2.0)");
-    eprintln!("                             Higher = more noise reduction, more distortion");
-    eprintln!("  --makeup-gain <F>         Output gain multiplier (default: 1.5)");
-    eprintln!("                             Compensates for volume loss from noise reduction");
-    eprintln!();
-    eprintln!("Examples:");
-    eprintln!("  {} input.wav output.wav", program_name);
-    eprintln!("  {} --over-subtraction 3.0 --spectral-floor 0.05 --makeup-gain 2.0 input.wav output.wav", program_name);
-    eprintln!();
-    eprintln!("Presets:");
-    eprintln!("  Light:     --over-subtraction 1.0 --spectral-floor 0.25 --makeup-gain 1.2");
-    eprintln!("  Medium:    --over-subtraction 2.0 --spectral-floor 0.1 --makeup-gain 1.5 (default)");
-    eprintln!("  Heavy:     --over-subtraction 3.0 --spectral-floor 0.05 --makeup-gain 1.8");
-    eprintln!("  Extreme:   --over-subtraction 4.0 --spectral-floor 0.02 --makeup-gain 2.0");
+use std::error::Error;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+const EXAMPLES: &str = "\
+Examples:
+  bg-noise-reduction input.wav output.wav
+  bg-noise-reduction denoise --preset auto input.wav output.wav
+  bg-noise-reduction denoise --over-subtraction 3.0 --spectral-floor 0.05 --makeup-gain 2.0 input.wav output.wav
+  bg-noise-reduction analyze input.wav
+  bg-noise-reduction learn-profile room-tone.wav room.json
+  bg-noise-reduction denoise --noise-profile room.json input.wav output.wav
+  bg-noise-reduction batch --preset heavy --output-dir clean/ takes/*.wav
+  bg-noise-reduction compare --noisy noisy.wav clean.wav output.wav
+  bg-noise-reduction generate speech --noise pink --snr 5 --lead-in 1 --clean clean.wav noisy.wav
+  bg-noise-reduction spectrogram --noise-profile room.json -o before-after.svg input.wav
+  bg-noise-reduction dump-config --preset heavy --gate > podcast.toml
+  bg-noise-reduction completions bash > /etc/bash_completion.d/bg-noise-reduction";
+
+const DENOISE_EXAMPLES: &str = "\
+Examples:
+  bg-noise-reduction denoise input.wav output.wav
+  bg-noise-reduction denoise --preset auto input.wav output.wav
+  bg-noise-reduction denoise --residual removed.wav input.wav output.wav
+  bg-noise-reduction denoise --algorithm spectral-gate --n-std-thresh 2.0 input.wav output.wav
+  bg-noise-reduction denoise --algorithm spectral-gate-nonstationary --time-constant 1.0 input.wav output.wav
+  bg-noise-reduction denoise --noise-tracking --noise-window 3.0 input.wav output.wav
+  bg-noise-reduction denoise --channel-mode mid-side stereo.wav output.wav
+  bg-noise-reduction denoise --beamform mvdr --mic-positions '0,0,0;0.05,0,0;0.1,0,0;0.15,0,0' meeting.wav output.wav
+  bg-noise-reduction denoise --reference fan.wav input.wav output.wav
+  bg-noise-reduction denoise --reference 2 --anc-method rls --anc-taps 32 two-mic.wav output.wav
+  bg-noise-reduction denoise --far-end remote.wav --echo-tail 300 mic.wav output.wav
+  bg-noise-reduction denoise --target-lufs -16 --true-peak -1.5 input.wav output.wav
+  bg-noise-reduction denoise --dereverb --t60 0.8 input.wav output.wav
+  bg-noise-reduction denoise --highpass 80 --eq highshelf:8000:2 input.wav output.wav
+  bg-noise-reduction denoise --gate --gate-key snr --gate-threshold 6 --gate-mode per-band input.wav output.wav
+  bg-noise-reduction denoise --makeup-gain 3.0 --limiter --limiter-ceiling -0.5 input.wav output.wav
+  bg-noise-reduction denoise --config podcast.toml --makeup-gain 2.0 input.wav output.wav";
+
+/// Audio Noise Reduction - Spectral Subtraction
+#[derive(Parser)]
+#[command(name = "bg-noise-reduction", version, after_help = EXAMPLES)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Reduce background noise in a WAV file (the default command)
+    #[command(after_help = DENOISE_EXAMPLES)]
+    Denoise(DenoiseArgs),
+    /// Report levels, noise floor per band, SNR, clipping and hum of a WAV file without processing it
+    Analyze {
+        /// Print the report as JSON instead of a table
+        #[arg(long)]
+        json: bool,
+        /// WAV file to analyze
+        input: PathBuf,
+    },
+    /// Learn a noise profile from a noise-only recording, for denoise --noise-profile
+    LearnProfile(LearnProfileArgs),
+    /// Denoise directories, globs or lists of WAV files into an output directory, in parallel
+    Batch(BatchArgs),
+    /// Score a processed recording against its clean reference: SNR, segmental SNR, log-spectral distance, STOI and ESTOI
+    Compare {
+        /// Also score the unprocessed noisy mixture, to show the improvement
+        #[arg(long, value_name = "FILE")]
+        noisy: Option<PathBuf>,
+        /// Print the scores as JSON instead of a table
+        #[arg(long)]
+        json: bool,
+        /// Clean reference WAV file
+        clean: PathBuf,
+        /// Processed WAV file
+        processed: PathBuf,
+    },
+    /// Write a seeded synthetic test signal (noise, hum, chirp or speech-like), optionally mixed with noise at an SNR
+    Generate(GenerateArgs),
+    /// Draw spectrograms of a recording before and after denoising, and of what was removed, as PNG or SVG
+    Spectrogram(SpectrogramArgs),
+    /// List the built-in and user presets
+    Presets,
+    /// Print the configuration the options produce as TOML, for --config
+    DumpConfig(ProcessingOptions),
+    /// Print a shell completion script
+    Completions {
+        /// bash, zsh, fish, elvish or powershell
+        shell: Shell,
+    },
+    /// Print the man page, or write one page per command to a directory
+    Man {
+        /// Directory to write the pages to
+        #[arg(long, value_name = "DIR")]
+        out_dir: Option<PathBuf>,
+    },
+}
+
+#[derive(Args)]
+struct DenoiseArgs {
+    #[command(flatten)]
+    options: ProcessingOptions,
+    /// Also write what was removed (input minus the aligned, level-matched output) to this WAV file
+    #[arg(long, value_name = "FILE")]
+    residual: Option<PathBuf>,
+    /// Noisy WAV file
+    input: PathBuf,
+    /// Denoised WAV file to write
+    output: PathBuf,
+}
+
+#[derive(Args)]
+struct LearnProfileArgs {
+    #[command(flatten)]
+    filters: FilterOptions,
+    /// Noise-only WAV recording, e.g. room tone
+    input: PathBuf,
+    /// Profile file to write (JSON)
+    output: PathBuf,
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let cli = Cli::parse_from(with_default_command(env::args_os().collect()));
 
-    if args.len() < 3 || args.contains(&"--help".to_string()) || args.contains(&"-h".to_string()) {
-        print_usage(&args[0]);
-        if args.len() < 3 {
-            std::process::exit(1);
+    let result = match cli.command {
+        Command::Denoise(args) => denoise(&args),
+        Command::Analyze { input, json } => analyze(&input, json),
+        Command::LearnProfile(args) => learn_profile(&args),
+        Command::Batch(args) => batch::run(&args),
+        Command::Compare { noisy, json, clean, processed } => compare(&clean, &processed, noisy.as_deref(), json),
+        Command::Generate(args) => generate::run(&args),
+        Command::Spectrogram(args) => spectrogram::run(&args),
+        Command::Presets => presets(),
+        Command::DumpConfig(options) => dump_config(&options),
+        Command::Completions { shell } => {
+            clap_complete::generate(shell, &mut Cli::command(), "bg-noise-reduction", &mut std::io::stdout());
+            Ok(())
         }
-        return;
+        Command::Man { out_dir } => man(out_dir.as_deref()),
+    };
+
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
     }
+}
 
-    let mut config = NoiseReductionConfig::default();
-    let mut input_idx = 1;
-    let mut output_idx = 2;
-
-    // Parse options
-    let mut i = 1;
-    while i < args.len() {
-        match args[i].as_str() {
-            "--noise-frames" => {
-                if i + 1 < args.len() {
-                    config.noise_frames = args[i + 1].parse().unwrap_or_else(|_| {
-                        eprintln!("Error: Invalid value for --noise-frames");
-                        std::process::exit(1);
-                    });
-                    i += 2;
-                    input_idx = i;
-                    output_idx = i + 1;
-                } else {
-                    eprintln!("Error: --noise-frames requires a value");
-                    std::process::exit(1);
-                }
-            }
-            "--spectral-floor" => {
-                if i + 1 < args.len() {
-                    config.spectral_floor = args[i + 1].parse().unwrap_or_else(|_| {
-                        eprintln!("Error: Invalid value for --spectral-floor");
-                        std::process::exit(1);
-                    });
-                    i += 2;
-                    input_idx = i;
-                    output_idx = i + 1;
-                } else {
-                    eprintln!("Error: --spectral-floor requires a value");
-                    std::process::exit(1);
-                }
-            }
-            "--over-subtraction" => {
-                if i + 1 < args.len() {
-                    config.over_subtraction = args[i + 1].parse().unwrap_or_else(|_| {
-                        eprintln!("Error: Invalid value for --over-subtraction");
-                        std::process::exit(1);
-                    });
-                    i += 2;
-                    input_idx = i;
-                    output_idx = i + 1;
-                } else {
-                    eprintln!("Error: --over-subtraction requires a value");
-                    std::process::exit(1);
-                }
-            }
-            "--makeup-gain" => {
-                if i + 1 < args.len() {
-                    config.makeup_gain = args[i + 1].parse().unwrap_or_else(|_| {
-                        eprintln!("Error: Invalid value for --makeup-gain");
-                        std::process::exit(1);
-                    });
-                    i += 2;
-                    input_idx = i;
-                    output_idx = i + 1;
-                } else {
-                    eprintln!("Error: --makeup-gain requires a value");
-                    std::process::exit(1);
-                }
-            }
-            _ => {
-                // Not an option, must be input/output
-                if args[i].starts_with("--") {
-                    eprintln!("Error: Unknown option '{}'", args[i]);
-                    std::process::exit(1);
-                }
-                break;
-            }
-        }
+/// Insert `denoise` when no command is given, so `bg-noise-reduction [OPTIONS] in.wav out.wav` keeps working
+fn with_default_command(mut args: Vec<OsString>) -> Vec<OsString> {
+    let command = Cli::command();
+    let given = args.get(1).and_then(|arg| arg.to_str()).is_some_and(|arg| {
+        matches!(arg, "help" | "-h" | "--help" | "-V" | "--version")
+            || command.get_subcommands().any(|subcommand| subcommand.get_name() == arg)
+    });
+    if args.len() > 1 && !given {
+        args.insert(1, "denoise".into());
     }
+    args
+}
 
-    if args.len() < output_idx + 1 {
-        eprintln!("Error: Missing input or output file");
-        eprintln!();
-        print_usage(&args[0]);
-        std::process::exit(1);
+fn check_input(path: &Path) -> Result<(), Box<dyn Error>> {
+    if !path.exists() {
+        return Err(format!("Input file '{}' does not exist", path.display()).into());
     }
+    Ok(())
+}
+
+fn denoise(args: &DenoiseArgs) -> Result<(), Box<dyn Error>> {
+    check_input(&args.input)?;
+    let config = args.options.config()?;
+    denoise_file(&args.input, &args.output, args.residual.as_deref(), config, &args.options, &mut std::io::stdout())
+}
 
-    let input_path = Path::new(&args[input_idx]);
-    let output_path = Path::new(&args[output_idx]);
+fn analyze(input: &Path, json: bool) -> Result<(), Box<dyn Error>> {
+    check_input(input)?;
+    let (channels, sample_rate) = read_channels(input)?;
+    let report = analysis::report(&channels, sample_rate);
+    let settings = AutoSettings::from_analysis(&report.levels, sample_rate);
 
-    if !input_path.exists() {
-        eprintln!("Error: Input file '{}' does not exist", input_path.display());
-        std::process::exit(1);
+    if json {
+        let mut value = serde_json::json!({ "file": input });
+        if let (Some(object), serde_json::Value::Object(fields)) = (value.as_object_mut(), serde_json::to_value(&report)?) {
+            object.extend(fields);
+            object.insert("suggested".to_string(), serde_json::to_value(settings)?);
+        }
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
     }
 
-    if let Err(e) = process_audio(input_path, output_path, config) {
-        eprintln!("Error processing audio: {}", e);
-        std::process::exit(1);
+    let levels = &report.levels;
+    let dc: Vec<String> = report.dc_offset.iter().map(|offset| format!("{:+.4}", offset)).collect();
+    println!("Input: {} Hz, {} channels, {:.2} seconds", sample_rate, report.channels, report.duration_s);
+    println!("Peak:              {:7.1} dBFS", report.peak_db);
+    println!("RMS:               {:7.1} dBFS", report.rms_db);
+    println!("DC offset:         {}", dc.join(", "));
+    println!("Clipped samples:   {:7}", report.clipped_samples);
+    println!("Noise floor:       {:7.1} dBFS", levels.noise_floor_db);
+    println!("Signal level:      {:7.1} dBFS", levels.signal_level_db);
+    println!("SNR:               {:7.1} dB", levels.snr_db);
+    println!("Spectral flatness: {:7.2}", report.spectral_flatness);
+    println!("Noise flatness:    {:7.2}", levels.noise_flatness);
+    println!("Leading noise:     {:7.2} s", levels.leading_noise_s);
+    match &report.hum {
+        Some(hum) => {
+            let harmonics: Vec<String> = hum.harmonics.iter().map(|h| h.to_string()).collect();
+            println!("Hum:               {} Hz at {:.1} dBFS, harmonics {}", hum.fundamental_hz, hum.level_db, harmonics.join(", "));
+        }
+        None => println!("Hum:               none"),
     }
+    println!();
+    println!("{:<18} {:>10}", "Band (Hz)", "Noise dBFS");
+    for band in &report.bands {
+        println!("{:<18} {:>10.1}", format!("{:.0}-{:.0}", band.low_hz, band.high_hz), band.noise_floor_db);
+    }
+    println!();
+    println!("Suggested (--preset auto): --noise-frames {} --spectral-floor {:.3} --over-subtraction {:.2} --makeup-gain {:.2}",
+        settings.noise_frames, settings.spectral_floor, settings.over_subtraction, settings.makeup_gain);
+    Ok(())
+}
+
+fn learn_profile(args: &LearnProfileArgs) -> Result<(), Box<dyn Error>> {
+    check_input(&args.input)?;
+    let (channels, sample_rate) = read_channels(&args.input)?;
+    let mut config = NoiseReductionConfig { sample_rate, ..NoiseReductionConfig::default() };
+    args.filters.apply(&mut config);
+    config.validate().map_err(|e| format!("invalid configuration: {}", e))?;
+
+    let profile = AudioProcessor::new(FRAME_SIZE).learn_noise_profile(&downmix(&channels), &config)?;
+    config::save_profile(&args.output, &profile)?;
+
+    let db = profile.magnitudes_db();
+    let loudest = db.iter().enumerate().max_by(|a, b| a.1.total_cmp(b.1)).map_or(0, |(bin, _)| bin);
+    println!("Noise profile: {} bins at {} Hz, loudest at {:.0} Hz", db.len(), sample_rate, profile.frequencies()[loudest]);
+    println!("Profile written to: {}", args.output.display());
+    Ok(())
 }
 
-fn process_audio(
-    input_path: &Path,
-    output_path: &Path,
-    config: NoiseReductionConfig,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let reader = WavReader::open(input_path)?;
-    let spec = reader.spec();
-    let channels = spec.channels;
-    let sample_rate = spec.sample_rate;
-
-    println!("Input: {} Hz, {} channels", sample_rate, channels);
-    println!("Duration: {:.2} seconds", reader.duration() as f32 / sample_rate as f32);
-    println!("Config: noise_frames={}, spectral_floor={}, over_subtraction={}, makeup_gain={}",
-        config.noise_frames, config.spectral_floor, config.over_subtraction, config.makeup_gain);
-
-    let samples: Vec<f32> = reader
-        .into_samples::<i16>()
-        .filter_map(|s| s.ok())
-        .map(|s| s as f32 / i16::MAX as f32)
-        .collect();
-
-    println!("Total samples: {}", samples.len());
-
-    // Use core library for processing
-    let mut processor = bg_noise_reduction_core::AudioProcessor::new(FRAME_SIZE);
-    let output_samples = processor.process(&samples, &config);
-
-    println!("Processed {} frames", samples.len() / FRAME_SIZE);
-
-    let output_spec = WavSpec {
-        channels,
-        sample_rate,
-        bits_per_sample: 16,
-        sample_format: hound::SampleFormat::Int,
+/// Metrics of a file against the clean reference, both downmixed
+fn score_file(clean: &[f32], sample_rate: u32, path: &Path) -> Result<Metrics, Box<dyn Error>> {
+    check_input(path)?;
+    Ok(metrics::evaluate(clean, &read_mono(path, sample_rate)?, sample_rate))
+}
+
+fn compare(clean_path: &Path, processed_path: &Path, noisy_path: Option<&Path>, json: bool) -> Result<(), Box<dyn Error>> {
+    check_input(clean_path)?;
+    let (channels, sample_rate) = read_channels(clean_path)?;
+    let clean = downmix(&channels);
+    let processed = score_file(&clean, sample_rate, processed_path)?;
+    let noisy = noisy_path.map(|path| score_file(&clean, sample_rate, path)).transpose()?;
+
+    if json {
+        let value = serde_json::json!({
+            "clean": clean_path,
+            "processed": { "file": processed_path, "metrics": processed },
+            "noisy": noisy_path.zip(noisy).map(|(file, metrics)| serde_json::json!({ "file": file, "metrics": metrics })),
+        });
+        println!("{}", serde_json::to_string_pretty(&value)?);
+        return Ok(());
+    }
+
+    let optional = |value: Option<f32>| value.unwrap_or(f32::NAN);
+    let rows = |m: &Metrics| {
+        [
+            ("SNR (dB)", m.snr_db),
+            ("Segmental SNR (dB)", m.segmental_snr_db),
+            ("Log-spectral dist. (dB)", m.log_spectral_distance_db),
+            ("STOI", optional(m.stoi)),
+            ("ESTOI", optional(m.estoi)),
+        ]
     };
+    match &noisy {
+        Some(noisy) => {
+            println!("{:<24} {:>10} {:>10} {:>10}", "", "noisy", "processed", "change");
+            for ((name, before), (_, after)) in rows(noisy).into_iter().zip(rows(&processed)) {
+                println!("{:<24} {:>10.3} {:>10.3} {:>+10.3}", name, before, after, after - before);
+            }
+        }
+        None => {
+            println!("{:<24} {:>10}", "", "processed");
+            for (name, value) in rows(&processed) {
+                println!("{:<24} {:>10.3}", name, value);
+            }
+        }
+    }
+    println!();
+    println!("Processed aligned with delay {} samples, gain {:.3}", processed.delay, processed.gain);
+    if processed.stoi.is_none() {
+        println!("STOI and ESTOI need at least 384 ms of non-silent audio");
+    }
+    Ok(())
+}
 
-    let mut writer = WavWriter::create(output_path, output_spec)?;
-    for sample in &output_samples {
-        let sample_i16 = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
-        writer.write_sample(sample_i16)?;
+fn presets() -> Result<(), Box<dyn Error>> {
+    println!("{:<10} Choose noise frames, floor, over-subtraction and makeup gain from", "auto");
+    println!("{:<10} the measured SNR and noise", "");
+    for preset in config::load_presets()?.iter() {
+        println!("{:<10} --over-subtraction {:?} --spectral-floor {:?} --makeup-gain {:?} ({})",
+            preset.name, preset.over_subtraction, preset.spectral_floor, preset.makeup_gain, preset.description);
+    }
+    if let Some(dir) = config::config_dir() {
+        println!();
+        println!("User presets are read from {}", dir.join("presets").join("<name>.toml").display());
     }
-    writer.finalize()?;
+    Ok(())
+}
 
-    println!("Output written to: {}", output_path.display());
+fn dump_config(options: &ProcessingOptions) -> Result<(), Box<dyn Error>> {
+    if options.is_auto() {
+        return Err("--preset auto needs an input file; run it on a recording instead".into());
+    }
+    print!("{}", config::config_to_toml(&options.config()?)?);
+    Ok(())
+}
+
+fn man(out_dir: Option<&Path>) -> Result<(), Box<dyn Error>> {
+    let command = Cli::command();
+    let Some(dir) = out_dir else {
+        clap_mangen::Man::new(command).render(&mut std::io::stdout())?;
+        return Ok(());
+    };
+
+    std::fs::create_dir_all(dir)?;
+    let name = command.get_name().to_string();
+    for subcommand in command.get_subcommands() {
+        let page_name = format!("{}-{}", name, subcommand.get_name());
+        let mut page = Vec::new();
+        clap_mangen::Man::new(subcommand.clone().name(page_name.clone())).render(&mut page)?;
+        std::fs::write(dir.join(format!("{}.1", page_name)), page)?;
+    }
+    let mut page = Vec::new();
+    clap_mangen::Man::new(command).render(&mut page)?;
+    std::fs::write(dir.join(format!("{}.1", name)), page)?;
+    println!("Man pages written to: {}", dir.display());
     Ok(())
 }
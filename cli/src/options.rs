@@ -0,0 +1,452 @@
+//! Processing options shared by the denoise, batch and dump-config commands
+//!
+//! Every option is optional so the configuration can be layered: a config
+//! file first, then the preset, then whatever was given on the command line.
+
+use crate::config::{load_config, load_presets, load_profile};
+use bg_noise_reduction_core::{
+    Algorithm, AncConfig, AncMethod, AutoSettings, BeamformConfig, BeamformMethod, ChannelMode, FilterSpec, GateKey,
+    GateMode, NoiseReductionConfig, SpectralGateConfig, Steering, TrackingMethod,
+};
+use clap::Args;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+const NOISE: &str = "Noise reduction";
+const SPECTRAL_GATE: &str = "Spectral gate";
+const MULTICHANNEL: &str = "Multichannel";
+const ANC: &str = "Adaptive noise cancellation";
+const AEC: &str = "Echo cancellation";
+const FILTERS: &str = "Filters";
+const STAGES: &str = "Output stages";
+const GATE: &str = "Gate";
+
+/// Where the noise reference for adaptive noise cancellation comes from
+#[derive(Debug, Clone)]
+pub enum Reference {
+    /// Separate WAV recording of the reference microphone
+    File(PathBuf),
+    /// Channel of the input file, counted from 1
+    Channel(usize),
+}
+
+impl FromStr for Reference {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.parse::<usize>() {
+            Ok(0) => Err("channels are counted from 1".to_string()),
+            Ok(channel) => Ok(Reference::Channel(channel)),
+            Err(_) => Ok(Reference::File(PathBuf::from(s))),
+        }
+    }
+}
+
+/// Microphone positions given as `x,y,z;x,y,z;...` in metres
+#[derive(Debug, Clone)]
+pub struct MicPositions(Vec<[f32; 3]>);
+
+impl FromStr for MicPositions {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(';')
+            .map(|position| {
+                let coordinates: Vec<f32> = position
+                    .split(',')
+                    .map(|v| v.trim().parse().map_err(|_| format!("invalid coordinate '{}'", v)))
+                    .collect::<Result<_, _>>()?;
+                match coordinates[..] {
+                    [x, y, z] => Ok([x, y, z]),
+                    [x, y] => Ok([x, y, 0.0]),
+                    _ => Err(format!("expected x,y or x,y,z, got '{}'", position)),
+                }
+            })
+            .collect::<Result<_, _>>()
+            .map(MicPositions)
+    }
+}
+
+/// Beam direction given as `azimuth[,elevation]` in degrees
+#[derive(Debug, Clone, Copy)]
+pub struct LookDirection {
+    azimuth_deg: f32,
+    elevation_deg: f32,
+}
+
+impl FromStr for LookDirection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let angles: Vec<f32> = s
+            .split(',')
+            .map(|v| v.trim().parse().map_err(|_| format!("invalid angle '{}'", v)))
+            .collect::<Result<_, _>>()?;
+        match angles[..] {
+            [azimuth_deg] => Ok(LookDirection { azimuth_deg, elevation_deg: 0.0 }),
+            [azimuth_deg, elevation_deg] => Ok(LookDirection { azimuth_deg, elevation_deg }),
+            _ => Err(format!("expected azimuth[,elevation], got '{}'", s)),
+        }
+    }
+}
+
+/// DC removal and filters applied before noise estimation
+#[derive(Debug, Clone, Args)]
+pub struct FilterOptions {
+    /// High-pass before processing to remove rumble (e.g. 80)
+    #[arg(long, value_name = "HZ", help_heading = FILTERS)]
+    pub highpass: Option<f32>,
+    /// Low-pass before processing (e.g. 12000)
+    #[arg(long, value_name = "HZ", help_heading = FILTERS)]
+    pub lowpass: Option<f32>,
+    /// Add a filter before processing, <type>:<freq>[:<gain_db>][:<q>] with type hp, lp,
+    /// lowshelf, highshelf or peak, e.g. hp:80, lowshelf:200:-3, peak:3000:2:1.4 (repeatable)
+    #[arg(long, value_name = "SPEC", help_heading = FILTERS)]
+    pub pre_eq: Vec<FilterSpec>,
+    /// Keep the DC offset (removed by default)
+    #[arg(long, help_heading = FILTERS)]
+    pub no_dc_removal: bool,
+}
+
+impl FilterOptions {
+    pub fn apply(&self, config: &mut NoiseReductionConfig) {
+        if let Some(frequency) = self.highpass {
+            config.pre_filters.push(FilterSpec::highpass(frequency));
+        }
+        if let Some(frequency) = self.lowpass {
+            config.pre_filters.push(FilterSpec::lowpass(frequency));
+        }
+        config.pre_filters.extend(&self.pre_eq);
+        if self.no_dc_removal {
+            config.remove_dc = false;
+        }
+    }
+}
+
+/// Options that build the processing configuration
+#[derive(Debug, Clone, Args)]
+pub struct ProcessingOptions {
+    /// Read options from a TOML file (see dump-config); presets and options given here override it
+    #[arg(long, value_name = "FILE", help_heading = NOISE)]
+    pub config: Option<PathBuf>,
+    /// Start from a preset: light, medium, heavy, extreme, a user preset, or auto to choose
+    /// from the measured SNR and noise (see the presets command)
+    #[arg(long, value_name = "NAME", help_heading = NOISE)]
+    pub preset: Option<String>,
+    /// Number of frames for noise estimation (default: 10)
+    #[arg(long, value_name = "N", help_heading = NOISE)]
+    pub noise_frames: Option<usize>,
+    /// Noise profile written by learn-profile, used instead of the first frames
+    #[arg(long, value_name = "FILE", help_heading = NOISE)]
+    pub noise_profile: Option<PathBuf>,
+    /// Spectral floor, 0.0-1.0; higher keeps more signal, removes less noise (default: 0.1)
+    #[arg(long, value_name = "F", help_heading = NOISE)]
+    pub spectral_floor: Option<f32>,
+    /// Over-subtraction factor; higher removes more noise, adds distortion (default: 2.0)
+    #[arg(long, value_name = "F", help_heading = NOISE)]
+    pub over_subtraction: Option<f32>,
+    /// Output gain multiplier compensating for volume loss (default: 1.5)
+    #[arg(long, value_name = "F", help_heading = NOISE)]
+    pub makeup_gain: Option<f32>,
+    /// subtraction, spectral-gate or spectral-gate-nonstationary (default: subtraction)
+    #[arg(long, value_name = "NAME", help_heading = NOISE)]
+    pub algorithm: Option<Algorithm>,
+    /// Re-estimate noise from a trailing window for noise that changes over time (traffic, HVAC, crowds)
+    #[arg(long, help_heading = NOISE)]
+    pub noise_tracking: bool,
+    /// Noise tracking window in seconds (default: 2.0)
+    #[arg(long, value_name = "S", help_heading = NOISE)]
+    pub noise_window: Option<f32>,
+    /// Noise tracking statistic: percentile or minimum (default: percentile)
+    #[arg(long, value_name = "METHOD", help_heading = NOISE)]
+    pub noise_method: Option<TrackingMethod>,
+    /// Window percentile used as the noise level (default: 20)
+    #[arg(long, value_name = "F", help_heading = NOISE)]
+    pub noise_percentile: Option<f32>,
+
+    /// Spectral gate threshold in std devs above the noise mean, stationary only (default: 1.5)
+    #[arg(long, value_name = "F", help_heading = SPECTRAL_GATE)]
+    pub n_std_thresh: Option<f32>,
+    /// Fraction of gated noise removed, 0.0-1.0 (default: 1.0)
+    #[arg(long, value_name = "F", help_heading = SPECTRAL_GATE)]
+    pub prop_decrease: Option<f32>,
+    /// Spectral gate mask smoothing in Hz (default: 500)
+    #[arg(long, value_name = "HZ", help_heading = SPECTRAL_GATE)]
+    pub freq_mask_smooth: Option<f32>,
+    /// Spectral gate mask smoothing in ms (default: 50)
+    #[arg(long, value_name = "MS", help_heading = SPECTRAL_GATE)]
+    pub time_mask_smooth: Option<f32>,
+    /// Noise estimate time constant in seconds, non-stationary only (default: 2.0)
+    #[arg(long, value_name = "S", help_heading = SPECTRAL_GATE)]
+    pub time_constant: Option<f32>,
+
+    /// linked-max, linked-mean, linked-mid, mid-side or independent (default: linked-max)
+    #[arg(long, value_name = "MODE", help_heading = MULTICHANNEL)]
+    pub channel_mode: Option<ChannelMode>,
+    /// Beamform a microphone array to mono before noise reduction: delay-and-sum or mvdr
+    #[arg(long, value_name = "METHOD", help_heading = MULTICHANNEL)]
+    pub beamform: Option<BeamformMethod>,
+    /// Microphone positions in metres, 'x,y,z;x,y,z;...' (default: delays estimated with GCC-PHAT)
+    #[arg(long, value_name = "LIST", help_heading = MULTICHANNEL)]
+    pub mic_positions: Option<MicPositions>,
+    /// Beam azimuth[,elevation] in degrees (default: 0,0)
+    #[arg(long, value_name = "DEG", requires = "mic_positions", help_heading = MULTICHANNEL)]
+    pub look_direction: Option<LookDirection>,
+
+    /// Cancel noise correlated with a reference microphone, given as a WAV file or an input
+    /// channel number (1 = first)
+    #[arg(long, value_name = "FILE|N", help_heading = ANC)]
+    pub reference: Option<Reference>,
+    /// Adaptive filter: nlms, rls or fdaf (default: nlms)
+    #[arg(long, value_name = "METHOD", help_heading = ANC)]
+    pub anc_method: Option<AncMethod>,
+    /// Adaptive filter length in samples (default: 256)
+    #[arg(long, value_name = "N", help_heading = ANC)]
+    pub anc_taps: Option<usize>,
+    /// NLMS / fdaf step size, 0.0-1.0 (default: 0.1)
+    #[arg(long, value_name = "F", help_heading = ANC)]
+    pub anc_step: Option<f32>,
+    /// RLS forgetting factor (default: 0.9995)
+    #[arg(long, value_name = "F", help_heading = ANC)]
+    pub anc_forgetting: Option<f32>,
+
+    /// Cancel acoustic echo of this far-end (playback) WAV, e.g. the remote side of a call
+    #[arg(long, value_name = "FILE", help_heading = AEC)]
+    pub far_end: Option<PathBuf>,
+    /// Longest echo path covered in ms, including playback delay (default: 250)
+    #[arg(long, value_name = "MS", help_heading = AEC)]
+    pub echo_tail: Option<f32>,
+    /// Echo canceller step size, 0.0-1.0 (default: 0.5)
+    #[arg(long, value_name = "F", help_heading = AEC)]
+    pub echo_step: Option<f32>,
+    /// Mic/far-end peak ratio that freezes adaptation (default: 0.5)
+    #[arg(long, value_name = "F", help_heading = AEC)]
+    pub double_talk_threshold: Option<f32>,
+    /// Residual echo suppression weight, 0 = off (default: 0.5)
+    #[arg(long, value_name = "F", help_heading = AEC)]
+    pub echo_suppression: Option<f32>,
+
+    #[command(flatten)]
+    pub filters: FilterOptions,
+    /// Add a filter to the output, same SPEC as --pre-eq (repeatable)
+    #[arg(long, value_name = "SPEC", help_heading = FILTERS)]
+    pub eq: Vec<FilterSpec>,
+
+    /// Rebuild speech harmonics removed by aggressive subtraction (helps Heavy/Extreme settings)
+    #[arg(long, help_heading = STAGES)]
+    pub harmonic_regeneration: bool,
+    /// Enhanced vs regenerated weight, 0.0-1.0; lower restores more harmonics (default: 0.5)
+    #[arg(long, value_name = "F", help_heading = STAGES)]
+    pub harmonic_mix: Option<f32>,
+    /// Suppress late reverberation (room echo)
+    #[arg(long, help_heading = STAGES)]
+    pub dereverb: bool,
+    /// Reverberation time in seconds for --dereverb (default: estimated from the input)
+    #[arg(long, value_name = "S", help_heading = STAGES)]
+    pub t60: Option<f32>,
+    /// Reverb over-suppression factor (default: 1.0)
+    #[arg(long, value_name = "F", help_heading = STAGES)]
+    pub dereverb_strength: Option<f32>,
+    /// Normalize to integrated loudness in LUFS instead of applying makeup gain
+    /// (EBU R128: -23, podcasts: -16)
+    #[arg(long, value_name = "F", allow_negative_numbers = true, help_heading = STAGES)]
+    pub target_lufs: Option<f32>,
+    /// True-peak ceiling in dBTP for loudness normalization (default: -1.0)
+    #[arg(long, value_name = "F", allow_negative_numbers = true, help_heading = STAGES)]
+    pub true_peak: Option<f32>,
+    /// Enable the look-ahead true-peak limiter instead of hard clipping the output
+    #[arg(long, help_heading = STAGES)]
+    pub limiter: bool,
+    /// Limiter ceiling in dBTP (default: -1.0)
+    #[arg(long, value_name = "F", allow_negative_numbers = true, help_heading = STAGES)]
+    pub limiter_ceiling: Option<f32>,
+    /// Limiter release time in ms (default: 50)
+    #[arg(long, value_name = "MS", help_heading = STAGES)]
+    pub limiter_release: Option<f32>,
+    /// Limiter look-ahead in ms (default: 5)
+    #[arg(long, value_name = "MS", help_heading = STAGES)]
+    pub limiter_lookahead: Option<f32>,
+
+    /// Enable the noise gate / downward expander after spectral subtraction
+    #[arg(long, help_heading = GATE)]
+    pub gate: bool,
+    /// Gate threshold: dBFS for the level key, dB SNR for the snr key (default: -50)
+    #[arg(long, value_name = "DB", allow_negative_numbers = true, help_heading = GATE)]
+    pub gate_threshold: Option<f32>,
+    /// Expansion ratio below threshold, 'inf' for a hard gate (default: 4.0)
+    #[arg(long, value_name = "F", help_heading = GATE)]
+    pub gate_ratio: Option<f32>,
+    /// Maximum attenuation in dB (default: -30)
+    #[arg(long, value_name = "DB", allow_negative_numbers = true, help_heading = GATE)]
+    pub gate_range: Option<f32>,
+    /// Gate attack time in ms (default: 5)
+    #[arg(long, value_name = "MS", help_heading = GATE)]
+    pub gate_attack: Option<f32>,
+    /// Gate hold time in ms (default: 50)
+    #[arg(long, value_name = "MS", help_heading = GATE)]
+    pub gate_hold: Option<f32>,
+    /// Gate release time in ms (default: 150)
+    #[arg(long, value_name = "MS", help_heading = GATE)]
+    pub gate_release: Option<f32>,
+    /// broadband or per-band (default: broadband)
+    #[arg(long, value_name = "MODE", help_heading = GATE)]
+    pub gate_mode: Option<GateMode>,
+    /// level, snr or vad (default: level)
+    #[arg(long, value_name = "KEY", help_heading = GATE)]
+    pub gate_key: Option<GateKey>,
+}
+
+impl ProcessingOptions {
+    /// Configuration from the config file, the preset and the options given, in that order
+    ///
+    /// The auto preset needs the input, so it is applied later with
+    /// [`apply_auto`](Self::apply_auto).
+    pub fn config(&self) -> Result<NoiseReductionConfig, String> {
+        let mut config = match &self.config {
+            Some(path) => load_config(path)?,
+            None => NoiseReductionConfig::default(),
+        };
+        if let Some(name) = self.preset.as_deref().filter(|name| *name != "auto") {
+            load_presets()?.get(name)?.apply(&mut config);
+        }
+        self.apply(&mut config)?;
+        config.validate().map_err(|e| format!("invalid configuration: {}", e))?;
+        Ok(config)
+    }
+
+    /// Whether the settings are chosen per input by `--preset auto`
+    pub fn is_auto(&self) -> bool {
+        self.preset.as_deref() == Some("auto")
+    }
+
+    /// Apply automatically chosen settings, keeping the ones given on the command line
+    pub fn apply_auto(&self, settings: &AutoSettings, config: &mut NoiseReductionConfig) {
+        settings.apply(config);
+        self.apply_tuning(config);
+    }
+
+    /// Adaptive noise cancellation settings for `--reference`
+    pub fn anc_config(&self) -> AncConfig {
+        let defaults = AncConfig::default();
+        AncConfig {
+            method: self.anc_method.unwrap_or(defaults.method),
+            filter_length: self.anc_taps.unwrap_or(defaults.filter_length),
+            step_size: self.anc_step.unwrap_or(defaults.step_size),
+            forgetting_factor: self.anc_forgetting.unwrap_or(defaults.forgetting_factor),
+        }
+    }
+
+    /// The four values a preset sets
+    fn apply_tuning(&self, config: &mut NoiseReductionConfig) {
+        if let Some(value) = self.noise_frames {
+            config.noise_frames = value;
+        }
+        if let Some(value) = self.spectral_floor {
+            config.spectral_floor = value;
+        }
+        if let Some(value) = self.over_subtraction {
+            config.over_subtraction = value;
+        }
+        if let Some(value) = self.makeup_gain {
+            config.makeup_gain = value;
+        }
+    }
+
+    fn apply(&self, config: &mut NoiseReductionConfig) -> Result<(), String> {
+        self.apply_tuning(config);
+        if let Some(path) = &self.noise_profile {
+            config.noise_profile = Some(load_profile(path)?);
+        }
+
+        if let Some(algorithm) = self.algorithm {
+            config.algorithm = algorithm;
+        }
+        let gate_options = [self.n_std_thresh, self.prop_decrease, self.freq_mask_smooth, self.time_mask_smooth, self.time_constant];
+        if gate_options.iter().any(Option::is_some) {
+            let mut gating = match config.algorithm {
+                Algorithm::SpectralGate(gating) => gating,
+                Algorithm::SpectralSubtraction => SpectralGateConfig::default(),
+            };
+            set(&mut gating.n_std_thresh, self.n_std_thresh);
+            set(&mut gating.prop_decrease, self.prop_decrease);
+            set(&mut gating.freq_mask_smooth_hz, self.freq_mask_smooth);
+            set(&mut gating.time_mask_smooth_ms, self.time_mask_smooth);
+            set(&mut gating.time_constant_s, self.time_constant);
+            config.algorithm = Algorithm::SpectralGate(gating);
+        }
+
+        if self.noise_tracking || self.noise_window.is_some() || self.noise_method.is_some() || self.noise_percentile.is_some() {
+            let tracking = config.noise_tracking.get_or_insert_with(Default::default);
+            set(&mut tracking.window_s, self.noise_window);
+            set(&mut tracking.method, self.noise_method);
+            set(&mut tracking.percentile, self.noise_percentile);
+        }
+
+        set(&mut config.channel_mode, self.channel_mode);
+        if self.beamform.is_some() || self.mic_positions.is_some() {
+            let beamforming = config.beamforming.get_or_insert_with(BeamformConfig::default);
+            set(&mut beamforming.method, self.beamform);
+            if let Some(MicPositions(positions)) = &self.mic_positions {
+                let direction = self.look_direction.unwrap_or(LookDirection { azimuth_deg: 0.0, elevation_deg: 0.0 });
+                beamforming.steering = Steering::Geometry {
+                    positions: positions.clone(),
+                    azimuth_deg: direction.azimuth_deg,
+                    elevation_deg: direction.elevation_deg,
+                };
+            }
+        }
+
+        let echo = &mut config.echo_cancellation;
+        set(&mut echo.tail_ms, self.echo_tail);
+        set(&mut echo.step_size, self.echo_step);
+        set(&mut echo.double_talk_threshold, self.double_talk_threshold);
+        set(&mut echo.residual_suppression, self.echo_suppression);
+
+        self.filters.apply(config);
+        config.post_filters.extend(&self.eq);
+
+        if self.harmonic_regeneration || self.harmonic_mix.is_some() {
+            let harmonic = config.harmonic_regeneration.get_or_insert_with(Default::default);
+            set(&mut harmonic.mix, self.harmonic_mix);
+        }
+        if self.dereverb || self.t60.is_some() || self.dereverb_strength.is_some() {
+            let dereverb = config.dereverb.get_or_insert_with(Default::default);
+            if self.t60.is_some() {
+                dereverb.t60 = self.t60;
+            }
+            set(&mut dereverb.strength, self.dereverb_strength);
+        }
+        if self.target_lufs.is_some() || self.true_peak.is_some() {
+            let target = config.loudness.get_or_insert_with(Default::default);
+            set(&mut target.integrated_lufs, self.target_lufs);
+            set(&mut target.true_peak_ceiling, self.true_peak);
+        }
+        if self.limiter || self.limiter_ceiling.is_some() || self.limiter_release.is_some() || self.limiter_lookahead.is_some() {
+            let limiter = config.limiter.get_or_insert_with(Default::default);
+            set(&mut limiter.ceiling, self.limiter_ceiling);
+            set(&mut limiter.release_ms, self.limiter_release);
+            set(&mut limiter.lookahead_ms, self.limiter_lookahead);
+        }
+
+        let gate_levels = [self.gate_threshold, self.gate_ratio, self.gate_range, self.gate_attack, self.gate_hold, self.gate_release];
+        if self.gate || gate_levels.iter().any(Option::is_some) || self.gate_mode.is_some() || self.gate_key.is_some() {
+            let gate = config.gate.get_or_insert_with(Default::default);
+            set(&mut gate.threshold_db, self.gate_threshold);
+            set(&mut gate.ratio, self.gate_ratio);
+            set(&mut gate.range_db, self.gate_range);
+            set(&mut gate.attack_ms, self.gate_attack);
+            set(&mut gate.hold_ms, self.gate_hold);
+            set(&mut gate.release_ms, self.gate_release);
+            set(&mut gate.mode, self.gate_mode);
+            set(&mut gate.key, self.gate_key);
+        }
+        Ok(())
+    }
+}
+
+/// Overwrite `field` with an option that was given
+fn set<T>(field: &mut T, value: Option<T>) {
+    if let Some(value) = value {
+        *field = value;
+    }
+}
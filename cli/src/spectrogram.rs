@@ -0,0 +1,592 @@
+//! Spectrogram images of a recording before and after denoising
+//!
+//! Draws the input, the output and the attenuation between them (what was
+//! removed, in dB) from the core STFT, plus the noise profile curve when one is
+//! given. PNG files hold the bare panels; SVG adds titles, axes and a colour
+//! bar around the same pixels and draws the profile curve as a vector line.
+
+use crate::audio::{denoise_channels, downmix, read_channels};
+use crate::options::ProcessingOptions;
+use bg_noise_reduction_core::{residual, AudioProcessor, NoiseProfile, FRAME_SIZE};
+use clap::Args;
+use std::error::Error;
+use std::fmt;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+/// Lowest frequency of the log scale, in Hz
+const LOG_MIN_HZ: f32 = 20.0;
+/// Space between panels in pixels
+const GAP: usize = 8;
+/// Background around the panels and behind the profile curve
+const BACKGROUND: [u8; 3] = [24, 24, 24];
+/// SVG margins: left for frequency labels, right for the colour bar, above each panel for its title, below for its axis
+const MARGIN_LEFT: usize = 64;
+const MARGIN_RIGHT: usize = 40;
+const TITLE_HEIGHT: usize = 24;
+const AXIS_HEIGHT: usize = 24;
+
+#[derive(Args)]
+pub struct SpectrogramArgs {
+    #[command(flatten)]
+    options: ProcessingOptions,
+    /// Image to write: SVG if it ends in .svg, PNG otherwise
+    #[arg(short, long, value_name = "FILE")]
+    output: PathBuf,
+    /// Processed version of the input to compare against, instead of denoising the input with the options
+    #[arg(long, value_name = "FILE")]
+    processed: Option<PathBuf>,
+    /// Draw only the input, without denoising it
+    #[arg(long, conflicts_with = "processed")]
+    input_only: bool,
+    /// magma, inferno, viridis or gray
+    #[arg(long, value_name = "NAME", default_value = "magma")]
+    colormap: Colormap,
+    /// Frequency axis: linear, log or mel
+    #[arg(long, value_name = "SCALE", default_value = "log")]
+    scale: FrequencyScale,
+    /// Level at the top of the colormap in dBFS
+    #[arg(long, value_name = "DB", default_value_t = 0.0, allow_negative_numbers = true)]
+    max_db: f32,
+    /// Levels shown below --max-db, in dB
+    #[arg(long, value_name = "DB", default_value_t = 90.0)]
+    db_range: f32,
+    /// Attenuation at the top of the colormap in the removed panel, in dB
+    #[arg(long, value_name = "DB", default_value_t = 30.0)]
+    removed_range: f32,
+    /// Width of the panels in pixels
+    #[arg(long, value_name = "PX", default_value_t = 1000)]
+    width: usize,
+    /// Height of each panel in pixels
+    #[arg(long, value_name = "PX", default_value_t = 300)]
+    height: usize,
+    /// WAV file to draw
+    input: PathBuf,
+}
+
+/// Colour gradient the levels are drawn with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Colormap {
+    Magma,
+    Inferno,
+    Viridis,
+    Gray,
+}
+
+impl Colormap {
+    /// Evenly spaced colours of the gradient, from low to high
+    fn stops(self) -> &'static [[u8; 3]] {
+        match self {
+            Colormap::Magma => &[
+                [0, 0, 4], [28, 16, 68], [79, 18, 123], [129, 37, 129], [181, 54, 122],
+                [229, 80, 100], [251, 135, 97], [254, 194, 135], [252, 253, 191],
+            ],
+            Colormap::Inferno => &[
+                [0, 0, 4], [31, 12, 72], [85, 15, 109], [136, 34, 106], [186, 54, 85],
+                [227, 89, 51], [249, 140, 10], [249, 201, 50], [252, 255, 164],
+            ],
+            Colormap::Viridis => &[
+                [68, 1, 84], [72, 40, 120], [62, 73, 137], [49, 104, 142], [38, 130, 142],
+                [31, 158, 137], [53, 183, 121], [110, 206, 88], [181, 222, 43], [253, 231, 37],
+            ],
+            Colormap::Gray => &[[0, 0, 0], [255, 255, 255]],
+        }
+    }
+
+    /// Colour of `value`, 0.0 (lowest) to 1.0 (highest), clamped
+    pub fn color(self, value: f32) -> [u8; 3] {
+        let stops = self.stops();
+        let position = value.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+        let index = (position as usize).min(stops.len() - 2);
+        let fraction = position - index as f32;
+        let (a, b) = (stops[index], stops[index + 1]);
+        [0, 1, 2].map(|c| (a[c] as f32 + (b[c] as f32 - a[c] as f32) * fraction).round() as u8)
+    }
+}
+
+impl FromStr for Colormap {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "magma" => Ok(Colormap::Magma),
+            "inferno" => Ok(Colormap::Inferno),
+            "viridis" => Ok(Colormap::Viridis),
+            "gray" | "grey" => Ok(Colormap::Gray),
+            _ => Err(format!("unknown colormap '{}', expected magma, inferno, viridis or gray", s)),
+        }
+    }
+}
+
+impl fmt::Display for Colormap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Colormap::Magma => write!(f, "magma"),
+            Colormap::Inferno => write!(f, "inferno"),
+            Colormap::Viridis => write!(f, "viridis"),
+            Colormap::Gray => write!(f, "gray"),
+        }
+    }
+}
+
+/// How frequencies are spaced along the frequency axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrequencyScale {
+    Linear,
+    /// Logarithmic from 20 Hz
+    Log,
+    /// Mel scale, close to how pitch is heard
+    Mel,
+}
+
+impl FrequencyScale {
+    fn warp(self, hz: f32) -> f32 {
+        match self {
+            FrequencyScale::Linear => hz,
+            FrequencyScale::Log => hz.max(LOG_MIN_HZ).ln(),
+            FrequencyScale::Mel => 2595.0 * (1.0 + hz / 700.0).log10(),
+        }
+    }
+
+    fn unwarp(self, value: f32) -> f32 {
+        match self {
+            FrequencyScale::Linear => value,
+            FrequencyScale::Log => value.exp(),
+            FrequencyScale::Mel => 700.0 * (10f32.powf(value / 2595.0) - 1.0),
+        }
+    }
+
+    fn lowest(self) -> f32 {
+        match self {
+            FrequencyScale::Log => LOG_MIN_HZ,
+            FrequencyScale::Linear | FrequencyScale::Mel => 0.0,
+        }
+    }
+
+    /// Frequency at `fraction` of the axis, 0.0 being the lowest frequency and 1.0 Nyquist
+    pub fn frequency(self, fraction: f32, nyquist: f32) -> f32 {
+        let (low, high) = (self.warp(self.lowest()), self.warp(nyquist));
+        self.unwarp(low + (high - low) * fraction)
+    }
+
+    /// Fraction of the axis at `hz`, the inverse of [`FrequencyScale::frequency`]
+    pub fn position(self, hz: f32, nyquist: f32) -> f32 {
+        let (low, high) = (self.warp(self.lowest()), self.warp(nyquist));
+        (self.warp(hz) - low) / (high - low)
+    }
+
+    /// Frequencies worth a tick mark below `nyquist`
+    fn ticks(self, nyquist: f32) -> Vec<f32> {
+        match self {
+            FrequencyScale::Linear => {
+                let step = [500.0, 1000.0, 2000.0, 5000.0, 10000.0]
+                    .into_iter()
+                    .find(|step| nyquist / step <= 8.0)
+                    .unwrap_or(20000.0);
+                (0..).map(|i| i as f32 * step).take_while(|&hz| hz < nyquist).collect()
+            }
+            FrequencyScale::Log => [50.0, 100.0, 200.0, 500.0, 1000.0, 2000.0, 5000.0, 10000.0, 20000.0]
+                .into_iter()
+                .filter(|&hz| hz < nyquist)
+                .collect(),
+            // Low frequencies are squeezed together on the mel scale
+            FrequencyScale::Mel => [100.0, 500.0, 1000.0, 2000.0, 4000.0, 8000.0, 16000.0]
+                .into_iter()
+                .filter(|&hz| hz < nyquist)
+                .collect(),
+        }
+    }
+}
+
+impl FromStr for FrequencyScale {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "linear" => Ok(FrequencyScale::Linear),
+            "log" => Ok(FrequencyScale::Log),
+            "mel" => Ok(FrequencyScale::Mel),
+            _ => Err(format!("unknown frequency scale '{}', expected linear, log or mel", s)),
+        }
+    }
+}
+
+impl fmt::Display for FrequencyScale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrequencyScale::Linear => write!(f, "linear"),
+            FrequencyScale::Log => write!(f, "log"),
+            FrequencyScale::Mel => write!(f, "mel"),
+        }
+    }
+}
+
+/// RGB pixels, row by row from the top
+struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<[u8; 3]>,
+}
+
+impl Image {
+    fn new(width: usize, height: usize, color: [u8; 3]) -> Self {
+        Image { width, height, pixels: vec![color; width * height] }
+    }
+
+    fn set(&mut self, x: i64, y: i64, color: [u8; 3]) {
+        if (0..self.width as i64).contains(&x) && (0..self.height as i64).contains(&y) {
+            self.pixels[y as usize * self.width + x as usize] = color;
+        }
+    }
+
+    /// Straight line from (x0, y0) to (x1, y1), Bresenham style
+    fn line(&mut self, (x0, y0): (i64, i64), (x1, y1): (i64, i64), color: [u8; 3]) {
+        let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+        let (sx, sy) = ((x1 - x0).signum(), (y1 - y0).signum());
+        let (mut x, mut y, mut error) = (x0, y0, dx + dy);
+        loop {
+            self.set(x, y, color);
+            if x == x1 && y == y1 {
+                break;
+            }
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                x += sx;
+            }
+            if doubled <= dx {
+                error += dx;
+                y += sy;
+            }
+        }
+    }
+
+    /// Images one above the other, `GAP` pixels apart
+    fn stack(images: &[Image]) -> Image {
+        let width = images.iter().map(|image| image.width).max().unwrap_or(0);
+        let height = images.iter().map(|image| image.height).sum::<usize>() + GAP * images.len().saturating_sub(1);
+        let mut stacked = Image::new(width, height, BACKGROUND);
+        let mut top = 0;
+        for image in images {
+            for y in 0..image.height {
+                let row = &image.pixels[y * image.width..(y + 1) * image.width];
+                stacked.pixels[(top + y) * width..(top + y) * width + image.width].copy_from_slice(row);
+            }
+            top += image.height + GAP;
+        }
+        stacked
+    }
+
+    fn to_png(&self) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels.concat())?;
+        writer.finish()?;
+        Ok(bytes)
+    }
+}
+
+/// Panel drawing settings shared by every panel
+struct Style {
+    colormap: Colormap,
+    scale: FrequencyScale,
+    width: usize,
+    height: usize,
+    nyquist: f32,
+}
+
+impl Style {
+    /// Fractional STFT bins covered by pixel row `y`, counted from the top
+    fn row_bins(&self, y: usize) -> (f32, f32) {
+        let bin_hz = 2.0 * self.nyquist / FRAME_SIZE as f32;
+        let top = self.scale.frequency(1.0 - y as f32 / self.height as f32, self.nyquist);
+        let bottom = self.scale.frequency(1.0 - (y + 1) as f32 / self.height as f32, self.nyquist);
+        (bottom / bin_hz, top / bin_hz)
+    }
+
+    /// Draw per-frame values (0.0-1.0 per bin) as a spectrogram, taking the
+    /// maximum where several frames or bins fall on one pixel
+    fn spectrogram(&self, frames: &[Vec<f32>]) -> Image {
+        let mut image = Image::new(self.width, self.height, self.colormap.color(0.0));
+        if frames.is_empty() {
+            return image;
+        }
+        let rows: Vec<(f32, f32)> = (0..self.height).map(|y| self.row_bins(y)).collect();
+        for x in 0..self.width {
+            let start = x * frames.len() / self.width;
+            let end = ((x + 1) * frames.len() / self.width).max(start + 1);
+            let column: Vec<f32> = (0..frames[0].len())
+                .map(|bin| frames[start..end].iter().map(|frame| frame[bin]).fold(f32::MIN, f32::max))
+                .collect();
+            for (y, &(low, high)) in rows.iter().enumerate() {
+                let value = if high - low < 1.0 {
+                    // Less than a bin per row: interpolate at the row centre
+                    let center = ((low + high) / 2.0).min((column.len() - 1) as f32);
+                    let index = (center as usize).min(column.len() - 2);
+                    let fraction = center - index as f32;
+                    column[index] * (1.0 - fraction) + column[index + 1] * fraction
+                } else {
+                    let bins = low.ceil() as usize..=(high.floor() as usize).min(column.len() - 1);
+                    column[bins].iter().copied().fold(f32::MIN, f32::max)
+                };
+                image.set(x as i64, y as i64, self.colormap.color(value));
+            }
+        }
+        image
+    }
+
+    /// Draw the profile as a curve: frequency across, level up
+    fn profile(&self, profile: &NoiseProfile, levels: &Levels) -> Image {
+        let mut image = Image::new(self.width, self.height, BACKGROUND);
+        let color = self.colormap.color(0.85);
+        let points = profile_points(profile, self, levels);
+        for pair in points.windows(2) {
+            image.line(pair[0], pair[1], color);
+        }
+        image
+    }
+}
+
+/// dB range the levels are mapped to
+struct Levels {
+    max_db: f32,
+    range: f32,
+}
+
+impl Levels {
+    fn normalize(&self, db: f32) -> f32 {
+        (db - (self.max_db - self.range)) / self.range
+    }
+}
+
+/// Profile curve as pixel coordinates within a panel
+fn profile_points(profile: &NoiseProfile, style: &Style, levels: &Levels) -> Vec<(i64, i64)> {
+    profile
+        .frequencies()
+        .into_iter()
+        .zip(profile.magnitudes_db())
+        .filter(|&(hz, _)| hz >= style.scale.lowest() && hz > 0.0)
+        .map(|(hz, db)| {
+            let x = style.scale.position(hz, style.nyquist) * (style.width - 1) as f32;
+            let y = (1.0 - levels.normalize(db).clamp(0.0, 1.0)) * (style.height - 1) as f32;
+            (x.round() as i64, y.round() as i64)
+        })
+        .collect()
+}
+
+/// STFT levels of `samples` in dB relative to a full-scale sine
+fn stft_db(samples: &[f32]) -> Vec<Vec<f32>> {
+    let full_scale = FRAME_SIZE as f32 / 4.0;
+    AudioProcessor::new(FRAME_SIZE)
+        .stft_magnitudes(samples)
+        .into_iter()
+        .map(|frame| frame.iter().map(|m| 20.0 * (m / full_scale).max(1e-9).log10()).collect())
+        .collect()
+}
+
+/// One drawn panel with the title and value range shown in SVG output
+struct Panel {
+    title: String,
+    image: Image,
+    kind: PanelKind,
+}
+
+enum PanelKind {
+    /// Time across, frequency up
+    Spectrogram,
+    /// Frequency across, level up, with the curve points for the vector line
+    Profile(Vec<(i64, i64)>),
+}
+
+pub fn run(args: &SpectrogramArgs) -> Result<()> {
+    if args.width < 2 || args.height < 2 {
+        return Err("--width and --height must be at least 2 pixels".into());
+    }
+    if args.db_range <= 0.0 || args.removed_range <= 0.0 {
+        return Err("--db-range and --removed-range must be positive".into());
+    }
+    if !args.input.exists() {
+        return Err(format!("Input file '{}' does not exist", args.input.display()).into());
+    }
+    let config = args.options.config()?;
+    let (channels, sample_rate) = read_channels(&args.input)?;
+    if channels[0].len() < FRAME_SIZE {
+        return Err(format!("{} is shorter than one frame ({} samples)", args.input.display(), FRAME_SIZE).into());
+    }
+    let profile = config.noise_profile.clone();
+    let processed = if args.input_only {
+        None
+    } else if let Some(path) = &args.processed {
+        let (processed, processed_rate) = read_channels(path)?;
+        if processed_rate != sample_rate {
+            return Err(format!("{} is {} Hz but the input is {} Hz", path.display(), processed_rate, sample_rate).into());
+        }
+        Some(downmix(&processed))
+    } else {
+        Some(downmix(&denoise_channels(channels.clone(), sample_rate, config, &args.options, &mut std::io::sink())?))
+    };
+
+    let style = Style {
+        colormap: args.colormap,
+        scale: args.scale,
+        width: args.width,
+        height: args.height,
+        nyquist: sample_rate as f32 / 2.0,
+    };
+    let levels = Levels { max_db: args.max_db, range: args.db_range };
+    let normalize = |frames: &[Vec<f32>]| -> Vec<Vec<f32>> {
+        frames.iter().map(|frame| frame.iter().map(|&db| levels.normalize(db)).collect()).collect()
+    };
+    let range = format!("{} to {} dBFS", args.max_db - args.db_range, args.max_db);
+
+    let input = downmix(&channels);
+    let before = stft_db(&input);
+    let mut panels = vec![Panel {
+        title: format!("Input ({})", range),
+        image: style.spectrogram(&normalize(&before)),
+        kind: PanelKind::Spectrogram,
+    }];
+    if let Some(processed) = &processed {
+        let after = stft_db(processed);
+        // Attenuation per bin once the output is brought back to the input level, so
+        // makeup or loudness gain doesn't hide what was removed
+        let gain = residual::align(&input, processed).gain;
+        let compensation_db = if gain > 0.0 { 20.0 * gain.log10() } else { 0.0 };
+        let removed: Vec<Vec<f32>> = before
+            .iter()
+            .zip(&after)
+            .map(|(b, a)| b.iter().zip(a).map(|(b, a)| (b - a - compensation_db) / args.removed_range).collect())
+            .collect();
+        panels.push(Panel {
+            title: format!("Output ({})", range),
+            image: style.spectrogram(&normalize(&after)),
+            kind: PanelKind::Spectrogram,
+        });
+        panels.push(Panel {
+            title: format!("Removed (0 to {} dB attenuation)", args.removed_range),
+            image: style.spectrogram(&removed),
+            kind: PanelKind::Spectrogram,
+        });
+    }
+    if let Some(profile) = &profile {
+        panels.push(Panel {
+            title: format!("Noise profile ({})", range),
+            image: style.profile(profile, &levels),
+            kind: PanelKind::Profile(profile_points(profile, &style, &levels)),
+        });
+    }
+
+    let is_svg = args.output.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("svg"));
+    let bytes = if is_svg {
+        render_svg(&panels, &style, channels[0].len() as f32 / sample_rate as f32)?.into_bytes()
+    } else {
+        let images: Vec<Image> = panels.into_iter().map(|panel| panel.image).collect();
+        Image::stack(&images).to_png()?
+    };
+    write_file(&args.output, &bytes)?;
+    println!("Spectrogram written to: {}", args.output.display());
+    Ok(())
+}
+
+fn write_file(path: &Path, bytes: &[u8]) -> Result<()> {
+    std::fs::write(path, bytes).map_err(|e| format!("{}: {}", path.display(), e).into())
+}
+
+/// Panels with titles, frequency and time axes and a colour bar, the pixels embedded as PNG
+fn render_svg(panels: &[Panel], style: &Style, duration_s: f32) -> Result<String> {
+    let panel_height = TITLE_HEIGHT + style.height + AXIS_HEIGHT;
+    let width = MARGIN_LEFT + style.width + MARGIN_RIGHT;
+    let height = panels.len() * panel_height;
+    let mut svg = String::new();
+    writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="sans-serif" font-size="11">"#)?;
+    writeln!(svg, r#"<rect width="100%" height="100%" fill="{}"/>"#, hex(BACKGROUND))?;
+
+    // Colour bar for the level panels
+    writeln!(svg, r#"<defs><linearGradient id="colormap" x1="0" y1="1" x2="0" y2="0">"#)?;
+    for i in 0..=10 {
+        writeln!(svg, r#"<stop offset="{}" stop-color="{}"/>"#, i as f32 / 10.0, hex(style.colormap.color(i as f32 / 10.0)))?;
+    }
+    writeln!(svg, "</linearGradient></defs>")?;
+
+    let right = MARGIN_LEFT + style.width;
+    let step = [0.1, 0.2, 0.5, 1.0, 2.0, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0, 600.0]
+        .into_iter()
+        .find(|step| duration_s / step <= 10.0)
+        .unwrap_or(1800.0);
+    let times: Vec<f32> = (0..).map(|i| i as f32 * step).take_while(|&t| t <= duration_s).collect();
+    for (index, panel) in panels.iter().enumerate() {
+        let top = index * panel_height + TITLE_HEIGHT;
+        let bottom = top + style.height;
+        writeln!(svg, r##"<text x="{}" y="{}" fill="#ddd" font-size="13">{}</text>"##, MARGIN_LEFT, top - 8, panel.title)?;
+        match &panel.kind {
+            PanelKind::Spectrogram => {
+                writeln!(
+                    svg,
+                    r#"<image x="{}" y="{}" width="{}" height="{}" preserveAspectRatio="none" href="data:image/png;base64,{}"/>"#,
+                    MARGIN_LEFT, top, style.width, style.height, base64(&panel.image.to_png()?)
+                )?;
+                writeln!(svg, r#"<rect x="{}" y="{}" width="12" height="{}" fill="url(#colormap)"/>"#, right + 12, top, style.height)?;
+                for hz in style.scale.ticks(style.nyquist) {
+                    let y = bottom as f32 - style.scale.position(hz, style.nyquist) * style.height as f32;
+                    writeln!(svg, r##"<line x1="{}" y1="{y:.1}" x2="{}" y2="{y:.1}" stroke="#aaa"/>"##, MARGIN_LEFT - 4, MARGIN_LEFT)?;
+                    writeln!(svg, r##"<text x="{}" y="{:.1}" fill="#aaa" text-anchor="end">{}</text>"##, MARGIN_LEFT - 6, y + 4.0, hz_label(hz))?;
+                }
+                for &time in &times {
+                    let x = MARGIN_LEFT as f32 + time / duration_s * style.width as f32;
+                    let label = if step < 1.0 { format!("{:.1}s", time) } else { format!("{}s", time) };
+                    writeln!(svg, r##"<text x="{x:.1}" y="{}" fill="#aaa" text-anchor="middle">{}</text>"##, bottom + 14, label)?;
+                }
+            }
+            PanelKind::Profile(points) => {
+                let line: Vec<String> = points
+                    .iter()
+                    .map(|&(x, y)| format!("{},{}", MARGIN_LEFT as i64 + x, top as i64 + y))
+                    .collect();
+                writeln!(svg, r##"<rect x="{}" y="{}" width="{}" height="{}" fill="none" stroke="#555"/>"##, MARGIN_LEFT, top, style.width, style.height)?;
+                writeln!(svg, r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="1.5"/>"#, line.join(" "), hex(style.colormap.color(0.85)))?;
+                for hz in style.scale.ticks(style.nyquist) {
+                    let x = MARGIN_LEFT as f32 + style.scale.position(hz, style.nyquist) * style.width as f32;
+                    writeln!(svg, r##"<text x="{x:.1}" y="{}" fill="#aaa" text-anchor="middle">{}</text>"##, bottom + 14, hz_label(hz))?;
+                }
+            }
+        }
+    }
+
+    writeln!(svg, "</svg>")?;
+    Ok(svg)
+}
+
+fn hz_label(hz: f32) -> String {
+    if hz >= 1000.0 {
+        format!("{}k", hz / 1000.0)
+    } else {
+        format!("{}", hz)
+    }
+}
+
+fn hex([r, g, b]: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// Standard base64 with padding, for the data URIs
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = (chunk[0] as u32) << 16 | (*chunk.get(1).unwrap_or(&0) as u32) << 8 | *chunk.get(2).unwrap_or(&0) as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                text.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
@@ -0,0 +1,47 @@
+// Command-line tests, running the built binary
+// Run with: cargo test -p bg-noise-reduction --test cli
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+const BINARY: &str = env!("CARGO_BIN_EXE_bg-noise-reduction");
+
+fn run(args: &[&str]) -> Output {
+    Command::new(BINARY).args(args).output().expect("failed to run the CLI")
+}
+
+/// Scratch directory for one test, removed and recreated
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("bg-noise-reduction-test-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Two seconds of pink noise at 16 kHz
+fn room_tone(dir: &Path) -> PathBuf {
+    let path = dir.join("noise.wav");
+    let output = run(&["generate", "pink", "--duration", "2", "--sample-rate", "16000", path.to_str().unwrap()]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    path
+}
+
+#[test]
+fn test_learn_profile_rejects_invalid_filters() {
+    let dir = scratch("learn-profile");
+    let noise = room_tone(&dir);
+    let profile = dir.join("profile.json");
+    let (noise, profile_arg) = (noise.to_str().unwrap(), profile.to_str().unwrap());
+
+    let output = run(&["learn-profile", "--highpass", "NaN", noise, profile_arg]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("invalid configuration"), "{}", stderr);
+    assert!(!profile.exists(), "profile written despite the invalid filter");
+
+    let output = run(&["learn-profile", "--highpass", "80", noise, profile_arg]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(profile.exists());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
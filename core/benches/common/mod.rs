@@ -0,0 +1,97 @@
+//! Wall-clock measurement that reports throughput as samples per second and real-time factor
+//!
+//! Benchmarks set `Throughput::Elements` to the number of audio samples one
+//! iteration handles, and the throughput line then reads, for example,
+//! `1.52 Msamples/s (RTF 0.0105)`: the real-time factor is processing time
+//! divided by the duration of the audio at the measurement's sample rate, so
+//! below 1.0 is faster than real time.
+//!
+//! Also used by the CLI benchmarks, through a `#[path]` module.
+
+use criterion::measurement::{Measurement, ValueFormatter};
+use criterion::Throughput;
+use std::time::{Duration, Instant};
+
+/// Wall-clock time, with throughputs in samples at `sample_rate`
+pub struct RealTime {
+    pub sample_rate: u32,
+}
+
+impl Measurement for RealTime {
+    type Intermediate = Instant;
+    type Value = Duration;
+
+    fn start(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn end(&self, start: Instant) -> Duration {
+        start.elapsed()
+    }
+
+    fn add(&self, a: &Duration, b: &Duration) -> Duration {
+        *a + *b
+    }
+
+    fn zero(&self) -> Duration {
+        Duration::ZERO
+    }
+
+    fn to_f64(&self, value: &Duration) -> f64 {
+        value.as_nanos() as f64
+    }
+
+    fn formatter(&self) -> &dyn ValueFormatter {
+        self
+    }
+}
+
+/// Samples in one throughput, or 0 for byte throughputs, which these benchmarks do not use
+fn samples(throughput: &Throughput) -> f64 {
+    match *throughput {
+        Throughput::Elements(samples) => samples as f64,
+        _ => 0.0,
+    }
+}
+
+impl ValueFormatter for RealTime {
+    fn format_throughput(&self, throughput: &Throughput, ns: f64) -> String {
+        let mut values = [ns];
+        let unit = self.scale_throughputs(ns, throughput, &mut values);
+        let rtf = ns / (samples(throughput) / self.sample_rate as f64 * 1e9);
+        // Three significant digits however far below real time
+        let decimals = (2.0 - rtf.log10().floor()).clamp(0.0, 12.0) as usize;
+        format!("{:.2} {} (RTF {:.*})", values[0], unit, decimals, rtf)
+    }
+
+    fn scale_values(&self, typical_ns: f64, values: &mut [f64]) -> &'static str {
+        let (factor, unit) = match typical_ns {
+            ns if ns < 1e3 => (1.0, "ns"),
+            ns if ns < 1e6 => (1e-3, "µs"),
+            ns if ns < 1e9 => (1e-6, "ms"),
+            _ => (1e-9, "s"),
+        };
+        for value in values {
+            *value *= factor;
+        }
+        unit
+    }
+
+    fn scale_throughputs(&self, typical_ns: f64, throughput: &Throughput, values: &mut [f64]) -> &'static str {
+        let samples = samples(throughput);
+        let (divisor, unit) = match samples * 1e9 / typical_ns {
+            rate if rate < 1e3 => (1.0, "samples/s"),
+            rate if rate < 1e6 => (1e3, "Ksamples/s"),
+            rate if rate < 1e9 => (1e6, "Msamples/s"),
+            _ => (1e9, "Gsamples/s"),
+        };
+        for value in values {
+            *value = samples * 1e9 / *value / divisor;
+        }
+        unit
+    }
+
+    fn scale_for_machines(&self, _values: &mut [f64]) -> &'static str {
+        "ns"
+    }
+}
@@ -0,0 +1,118 @@
+// Throughput and latency benchmarks of the core processing
+// Run with: cargo bench -p bg-noise-reduction-core --bench processing
+//
+// Inputs are seeded speech-like signals in pink noise at 10 dB SNR, so runs
+// are comparable across machines and commits. Every benchmark reports
+// samples per second and the real-time factor at SAMPLE_RATE; see common/mod.rs.
+
+mod common;
+
+use bg_noise_reduction_core::testsignal::{self, NoiseColor};
+use bg_noise_reduction_core::{AudioProcessor, NoiseReductionConfig, NoiseTrackingConfig, FRAME_SIZE};
+use common::RealTime;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::hint::black_box;
+
+const SAMPLE_RATE: u32 = 16000;
+
+fn noisy_speech(len: usize) -> Vec<f32> {
+    let speech = testsignal::speech_like(len, SAMPLE_RATE, 1);
+    testsignal::mix_at_snr(&speech, &testsignal::noise(NoiseColor::Pink, len, 2), 10.0)
+}
+
+fn config() -> NoiseReductionConfig {
+    NoiseReductionConfig { sample_rate: SAMPLE_RATE, ..NoiseReductionConfig::default() }
+}
+
+/// `process` over ten seconds with STFT frames from 512 to 8192 samples
+fn bench_frame_size(c: &mut Criterion<RealTime>) {
+    let mut group = c.benchmark_group("process_frame_size");
+    group.sample_size(20);
+    let config = config();
+    let samples = noisy_speech(10 * SAMPLE_RATE as usize);
+    group.throughput(Throughput::Elements(samples.len() as u64));
+    for frame_size in [512, 1024, FRAME_SIZE, 4096, 8192] {
+        let mut processor = AudioProcessor::new(frame_size);
+        group.bench_function(BenchmarkId::from_parameter(frame_size), |b| {
+            b.iter(|| processor.process(black_box(&samples), &config))
+        });
+    }
+    group.finish();
+}
+
+/// `process` at the default frame size over blocks from a single frame, its per-call
+/// latency, up to a minute of audio
+fn bench_block_length(c: &mut Criterion<RealTime>) {
+    let mut group = c.benchmark_group("process_block_length");
+    group.sample_size(20);
+    let config = config();
+    let mut processor = AudioProcessor::new(FRAME_SIZE);
+    for frames in [1, 4, 16, 64, 469] {
+        let samples = noisy_speech(frames * FRAME_SIZE);
+        group.throughput(Throughput::Elements(samples.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(samples.len()), &samples, |b, samples| {
+            b.iter(|| processor.process(black_box(samples), &config))
+        });
+    }
+    group.finish();
+}
+
+/// `process` over ten seconds with each suppression algorithm and with noise tracking
+fn bench_algorithms(c: &mut Criterion<RealTime>) {
+    let mut group = c.benchmark_group("algorithm");
+    group.sample_size(20);
+    let samples = noisy_speech(10 * SAMPLE_RATE as usize);
+    group.throughput(Throughput::Elements(samples.len() as u64));
+    let mut processor = AudioProcessor::new(FRAME_SIZE);
+    for name in ["subtraction", "spectral-gate", "spectral-gate-nonstationary", "noise-tracking"] {
+        let config = match name {
+            "noise-tracking" => NoiseReductionConfig { noise_tracking: Some(NoiseTrackingConfig::default()), ..config() },
+            algorithm => NoiseReductionConfig { algorithm: algorithm.parse().unwrap(), ..config() },
+        };
+        group.bench_function(name, |b| b.iter(|| processor.process(black_box(&samples), &config)));
+    }
+    group.finish();
+}
+
+/// Noise spectrum estimation from noise-only recordings of increasing length
+fn bench_noise_estimation(c: &mut Criterion<RealTime>) {
+    let mut group = c.benchmark_group("noise_estimation");
+    let config = config();
+    let mut processor = AudioProcessor::new(FRAME_SIZE);
+    for frames in [10, 40, 160] {
+        let noise = testsignal::noise(NoiseColor::Pink, FRAME_SIZE + (frames - 1) * processor.hop_size(), 3);
+        group.throughput(Throughput::Elements(noise.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(frames), &noise, |b, noise| {
+            b.iter(|| processor.learn_noise_profile(black_box(noise), &config).unwrap())
+        });
+    }
+    group.finish();
+}
+
+/// Gain computation for one frame, given its magnitudes and the noise estimate
+fn bench_frame_gains(c: &mut Criterion<RealTime>) {
+    let mut group = c.benchmark_group("frame_gains");
+    let mut processor = AudioProcessor::new(FRAME_SIZE);
+    // Full-length spectra, mirrored from the STFT's non-negative frequency bins as in the processing
+    let full = |half: &[f32]| -> Vec<f32> { half.iter().chain(half[1..FRAME_SIZE / 2].iter().rev()).copied().collect() };
+    let magnitudes = full(&processor.stft_magnitudes(&noisy_speech(FRAME_SIZE))[0]);
+    let noise = full(&processor.learn_noise_profile(&testsignal::noise(NoiseColor::Pink, 4 * FRAME_SIZE, 3), &config()).unwrap().magnitudes);
+    let config = config();
+    group.throughput(Throughput::Elements(processor.hop_size() as u64));
+    let mut gains = vec![0.0f32; FRAME_SIZE];
+    group.bench_function("subtraction", |b| {
+        b.iter(|| {
+            for ((gain, &magnitude), &noise) in gains.iter_mut().zip(black_box(&magnitudes)).zip(black_box(&noise)) {
+                *gain = AudioProcessor::subtraction_gain(magnitude, noise, &config);
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().with_measurement(RealTime { sample_rate: SAMPLE_RATE });
+    targets = bench_frame_size, bench_block_length, bench_algorithms, bench_noise_estimation, bench_frame_gains
+}
+criterion_main!(benches);
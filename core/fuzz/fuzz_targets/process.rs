@@ -0,0 +1,139 @@
+//! Fuzz the processing API with arbitrary audio and parameters
+//!
+//! Run with: cargo +nightly fuzz run process (from core/)
+
+#![no_main]
+
+use bg_noise_reduction_core::{
+    Algorithm, AudioProcessor, ChannelMode, DereverbConfig, FilterKind, FilterSpec, GateConfig, GateKey, GateMode, LimiterConfig,
+    LoudnessTarget, NoiseReductionConfig, NoiseTrackingConfig, TrackingMethod, FRAME_SIZE,
+};
+use libfuzzer_sys::arbitrary::{self, Arbitrary};
+use libfuzzer_sys::fuzz_target;
+
+/// Longest input processed, to keep iterations fast
+const MAX_SAMPLES: usize = 6 * FRAME_SIZE;
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    samples: Vec<f32>,
+    far_end: Option<Vec<f32>>,
+    channels: u8,
+    noise_frames: u8,
+    spectral_floor: f32,
+    over_subtraction: f32,
+    makeup_gain: f32,
+    algorithm: u8,
+    mid_side: bool,
+    remove_dc: bool,
+    sample_rate: u16,
+    pre_filters: Vec<Filter>,
+    post_filters: Vec<Filter>,
+    noise_tracking: Option<(f32, bool, f32, f32)>,
+    dereverb: Option<(Option<f32>, f32, f32, f32)>,
+    gate: Option<Gate>,
+    loudness: Option<(f32, f32)>,
+    limiter: Option<(f32, f32, f32)>,
+}
+
+#[derive(Debug, Arbitrary)]
+struct Filter {
+    kind: u8,
+    frequency: f32,
+    gain_db: f32,
+    q: f32,
+}
+
+#[derive(Debug, Arbitrary)]
+struct Gate {
+    threshold_db: f32,
+    ratio: f32,
+    range_db: f32,
+    attack_ms: f32,
+    hold_ms: f32,
+    release_ms: f32,
+    per_band: bool,
+    key: u8,
+}
+
+/// At most four filters, of any kind and with any parameters
+fn filters(filters: &[Filter]) -> Vec<FilterSpec> {
+    filters
+        .iter()
+        .take(4)
+        .map(|filter| FilterSpec {
+            kind: [FilterKind::HighPass, FilterKind::LowPass, FilterKind::LowShelf, FilterKind::HighShelf, FilterKind::Peaking]
+                [filter.kind as usize % 5],
+            frequency: filter.frequency,
+            gain_db: filter.gain_db,
+            q: filter.q,
+        })
+        .collect()
+}
+
+/// Audio in -1.0 to 1.0; NaN and infinities are kept to exercise their handling
+fn bounded(samples: &[f32]) -> Vec<f32> {
+    samples.iter().take(MAX_SAMPLES).map(|&s| if s.is_finite() { s.clamp(-1.0, 1.0) } else { s }).collect()
+}
+
+fuzz_target!(|input: Input| {
+    let config = NoiseReductionConfig {
+        noise_frames: input.noise_frames as usize,
+        spectral_floor: input.spectral_floor,
+        over_subtraction: input.over_subtraction,
+        makeup_gain: input.makeup_gain,
+        algorithm: match input.algorithm % 3 {
+            0 => Algorithm::SpectralSubtraction,
+            1 => "spectral-gate".parse().unwrap(),
+            _ => "spectral-gate-nonstationary".parse().unwrap(),
+        },
+        channel_mode: if input.mid_side { ChannelMode::MidSide } else { ChannelMode::default() },
+        remove_dc: input.remove_dc,
+        sample_rate: input.sample_rate as u32,
+        pre_filters: filters(&input.pre_filters),
+        post_filters: filters(&input.post_filters),
+        noise_tracking: input.noise_tracking.map(|(window_s, minimum, percentile, smoothing)| NoiseTrackingConfig {
+            window_s,
+            method: if minimum { TrackingMethod::Minimum } else { TrackingMethod::Percentile },
+            percentile,
+            smoothing,
+        }),
+        dereverb: input.dereverb.map(|(t60, late_onset_ms, strength, floor)| DereverbConfig { t60, late_onset_ms, strength, floor }),
+        gate: input.gate.map(|gate| GateConfig {
+            threshold_db: gate.threshold_db,
+            ratio: gate.ratio,
+            range_db: gate.range_db,
+            attack_ms: gate.attack_ms,
+            hold_ms: gate.hold_ms,
+            release_ms: gate.release_ms,
+            mode: if gate.per_band { GateMode::PerBand } else { GateMode::Broadband },
+            key: [GateKey::Level, GateKey::Snr, GateKey::Vad][gate.key as usize % 3],
+        }),
+        loudness: input.loudness.map(|(integrated_lufs, true_peak_ceiling)| LoudnessTarget { integrated_lufs, true_peak_ceiling }),
+        limiter: input.limiter.map(|(ceiling, release_ms, lookahead_ms)| LimiterConfig { ceiling, release_ms, lookahead_ms }),
+        ..NoiseReductionConfig::default()
+    };
+    let samples = bounded(&input.samples);
+    let far_end = input.far_end.as_deref().map(bounded);
+    let mut processor = AudioProcessor::new(FRAME_SIZE);
+
+    let channel_count = (input.channels % 3) as usize + 1;
+    let outputs = if channel_count == 1 {
+        vec![match &far_end {
+            Some(far_end) => processor.process_with_far_end(&samples, far_end, &config),
+            None => processor.process(&samples, &config),
+        }]
+    } else {
+        let channels = bg_noise_reduction_core::multichannel::deinterleave(&samples, channel_count);
+        match &far_end {
+            Some(far_end) => processor.process_channels_with_far_end(&channels, far_end, &config),
+            None => processor.process_channels(&channels, &config),
+        }
+    };
+
+    let expected_len = samples.len() / channel_count;
+    for output in &outputs {
+        assert_eq!(output.len(), expected_len);
+        assert!(output.iter().all(|s| s.is_finite()), "non-finite output");
+    }
+});
@@ -0,0 +1,43 @@
+//! Fuzz the WAV reading path: arbitrary bytes parsed as a WAV file, then
+//! analyzed and denoised the way the CLI does
+//!
+//! The decoding mirrors `read_channels` and `read_samples` in cli/src/audio.rs.
+//!
+//! Run with: cargo +nightly fuzz run wav (from core/)
+
+#![no_main]
+
+use bg_noise_reduction_core::{analysis, multichannel, AudioProcessor, NoiseReductionConfig, FRAME_SIZE};
+use libfuzzer_sys::fuzz_target;
+use std::io::Cursor;
+
+/// Most samples decoded, to keep iterations fast
+const MAX_SAMPLES: usize = 1 << 16;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(reader) = hound::WavReader::new(Cursor::new(data)) else {
+        return;
+    };
+    let spec = reader.spec();
+    if spec.sample_format != hound::SampleFormat::Int || spec.bits_per_sample != 16 || spec.channels == 0 || spec.sample_rate == 0 {
+        return;
+    }
+    let samples: Vec<f32> = reader
+        .into_samples::<i16>()
+        .take(MAX_SAMPLES)
+        .filter_map(|s| s.ok())
+        .map(|s| s as f32 / i16::MAX as f32)
+        .collect();
+    let channels = multichannel::deinterleave(&samples, spec.channels as usize);
+
+    let report = analysis::report(&channels, spec.sample_rate);
+    assert!(report.duration_s >= 0.0);
+
+    let config = NoiseReductionConfig { sample_rate: spec.sample_rate, ..NoiseReductionConfig::default() };
+    let outputs = AudioProcessor::new(FRAME_SIZE).process_channels(&channels, &config);
+    assert_eq!(outputs.len(), channels.len());
+    for output in &outputs {
+        assert_eq!(output.len(), channels[0].len());
+        assert!(output.iter().all(|s| s.is_finite()));
+    }
+});
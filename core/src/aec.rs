@@ -0,0 +1,122 @@
+//! # Acoustic Echo Cancellation
+//!
+//! Removes far-end playback picked up by the microphone in call recordings,
+//! given the far-end signal. Runs inside the STFT loop of
+//! [`AudioProcessor::process_with_far_end`](crate::AudioProcessor::process_with_far_end):
+//! the echo path is modelled per bin as a partitioned-block frequency-domain
+//! adaptive filter over the last few far-end frames, so echo tails longer
+//! than one frame are covered without a longer FFT.
+//!
+//! Adaptation is frozen during double talk, detected with a Geigel detector
+//! (near-end speech is declared when the microphone peak exceeds a fraction
+//! of the recent far-end peak), so the filter does not diverge on the local
+//! talker. Whatever echo the linear filter misses is handed to spectral
+//! subtraction as extra noise (residual echo suppression).
+
+use num_complex::Complex;
+use std::collections::VecDeque;
+
+/// Frames adaptation stays frozen after double talk is detected
+const DOUBLE_TALK_HANGOVER: usize = 2;
+/// Regularization of the per-bin power normalization, relative to full scale
+const REGULARIZATION: f32 = 1e-6;
+
+/// Configuration for the acoustic echo canceller
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default, deny_unknown_fields))]
+pub struct AecConfig {
+    /// Longest echo path covered, in milliseconds; must include the playback-to-capture delay (default: 250.0)
+    pub tail_ms: f32,
+    /// Normalized adaptation step size 0.0-1.0 (default: 0.5)
+    pub step_size: f32,
+    /// Geigel double-talk threshold: microphone peak over recent far-end peak above which adaptation freezes (default: 0.5)
+    pub double_talk_threshold: f32,
+    /// Weight of the echo estimate added to the noise spectrum for residual echo suppression; 0 disables (default: 0.5)
+    pub residual_suppression: f32,
+}
+
+impl Default for AecConfig {
+    fn default() -> Self {
+        Self {
+            tail_ms: 250.0,
+            step_size: 0.5,
+            double_talk_threshold: 0.5,
+            residual_suppression: 0.5,
+        }
+    }
+}
+
+/// Per-channel echo canceller state carried across STFT frames
+pub(crate) struct EchoCanceller {
+    config: AecConfig,
+    /// Filter coefficients, one partition per past far-end frame, bins 0..=N/2
+    weights: Vec<Vec<Complex<f32>>>,
+    /// Far-end spectra, newest first
+    history: VecDeque<Vec<Complex<f32>>>,
+    far_peaks: VecDeque<f32>,
+    hangover: usize,
+}
+
+impl EchoCanceller {
+    pub(crate) fn new(config: &AecConfig, sample_rate: u32, frame_size: usize, hop_size: usize) -> Self {
+        let tail_samples = config.tail_ms.max(0.0) / 1000.0 * sample_rate as f32;
+        // A frame spans frame_size samples, so the oldest partition only needs to reach back the remainder
+        let partitions = ((tail_samples - (frame_size - hop_size) as f32).max(0.0) / hop_size as f32).ceil() as usize + 1;
+        let bins = frame_size / 2 + 1;
+
+        EchoCanceller {
+            config: *config,
+            weights: vec![vec![Complex::new(0.0, 0.0); bins]; partitions],
+            history: VecDeque::from(vec![vec![Complex::new(0.0, 0.0); bins]; partitions]),
+            far_peaks: VecDeque::from(vec![0.0; partitions]),
+            hangover: 0,
+        }
+    }
+
+    /// Subtract the estimated echo from a full (two-sided) microphone spectrum in place
+    ///
+    /// `far_end` is the far-end spectrum of the same frame, windowed the same
+    /// way. The peaks are the largest absolute samples of the two frames.
+    /// Returns the full-spectrum residual echo magnitude to suppress on top of
+    /// the noise.
+    pub(crate) fn process_frame(&mut self, spectrum: &mut [Complex<f32>], far_end: &[Complex<f32>], mic_peak: f32, far_peak: f32) -> Vec<f32> {
+        let frame_size = spectrum.len();
+        let bins = self.weights[0].len();
+
+        self.history.pop_back();
+        self.history.push_front(far_end[..bins].to_vec());
+        self.far_peaks.pop_back();
+        self.far_peaks.push_front(far_peak);
+
+        let recent_far_peak = self.far_peaks.iter().copied().fold(0.0, f32::max);
+        if mic_peak > self.config.double_talk_threshold * recent_far_peak {
+            self.hangover = DOUBLE_TALK_HANGOVER;
+        } else {
+            self.hangover = self.hangover.saturating_sub(1);
+        }
+        let adapt = self.hangover == 0 && recent_far_peak > 0.0;
+
+        let regularization = REGULARIZATION * frame_size as f32;
+        let mut residual = vec![0.0f32; frame_size];
+        for bin in 0..bins {
+            let echo: Complex<f32> = self.weights.iter().zip(&self.history).map(|(w, x)| w[bin] * x[bin]).sum();
+            let error = spectrum[bin] - echo;
+
+            if adapt {
+                let power: f32 = self.history.iter().map(|x| x[bin].norm_sqr()).sum();
+                let scale = self.config.step_size / (power + regularization);
+                for (w, x) in self.weights.iter_mut().zip(&self.history) {
+                    w[bin] += x[bin].conj() * error * scale;
+                }
+            }
+
+            spectrum[bin] = error;
+            residual[bin] = self.config.residual_suppression * echo.norm();
+            if bin > 0 && bin < frame_size - bin {
+                spectrum[frame_size - bin] = error.conj();
+                residual[frame_size - bin] = residual[bin];
+            }
+        }
+        residual
+    }
+}
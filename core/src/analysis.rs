@@ -0,0 +1,421 @@
+//! # Signal Analysis and Automatic Settings
+//!
+//! Measures what a recording needs before it is processed: the noise floor,
+//! the speech level above it (SNR), how noise-like the quiet parts are
+//! (spectral flatness) and how much noise-only audio leads the file. The
+//! `auto` preset maps these to the spectral subtraction parameters instead of
+//! picking Light/Medium/Heavy/Extreme by ear.
+//!
+//! Levels come from 20 ms frames: the noise floor is a low percentile of the
+//! frame energies and the signal level a high one, so neither needs a voice
+//! activity detector.
+//!
+//! [`report`] adds the statistics worth checking before choosing settings:
+//! RMS, DC offset, clipping, the noise floor per octave band, mains hum and the
+//! flatness of the whole spectrum. Each is also available on its own.
+
+use crate::{AudioProcessor, NoiseReductionConfig, PresetDefinition, FRAME_SIZE, HOP_SIZE};
+use num_complex::Complex;
+use rustfft::FftPlanner;
+use std::f32::consts::PI;
+
+/// Length of the level-measurement frames in seconds
+const FRAME_SECONDS: f32 = 0.02;
+/// Percentile of frame energies taken as the noise floor
+const NOISE_PERCENTILE: f32 = 0.1;
+/// Percentile of frame energies taken as the signal level
+const SIGNAL_PERCENTILE: f32 = 0.95;
+/// Frames within this many dB of the noise floor count as noise
+const NOISE_MARGIN_DB: f32 = 6.0;
+/// Level reported for digital silence
+const SILENCE_DB: f32 = -120.0;
+/// Samples at or above this magnitude count as clipped
+const CLIP_LEVEL: f32 = 0.999;
+/// Octave bands the noise floor is reported in, as powers of two around 1 kHz (31.25 Hz to 16 kHz)
+const OCTAVES: std::ops::RangeInclusive<i32> = -5..=4;
+/// Mains frequencies checked for hum
+const MAINS_HZ: [f32; 2] = [50.0, 60.0];
+/// Harmonics of the mains frequency checked for hum
+const HUM_HARMONICS: usize = 8;
+/// Harmonics that must stand out before a recording counts as humming, so a single tone does not
+const MIN_HUM_HARMONICS: usize = 2;
+/// A harmonic counts as hum when it stands this many dB above its neighbourhood
+const HUM_PROMINENCE_DB: f32 = 12.0;
+
+/// Measurements of a recording's noise and signal levels
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SignalAnalysis {
+    /// Noise floor in dBFS (RMS of the quiet frames)
+    pub noise_floor_db: f32,
+    /// Level of the loud (speech) frames in dBFS
+    pub signal_level_db: f32,
+    /// Estimated signal-to-noise ratio in dB
+    pub snr_db: f32,
+    /// Spectral flatness of the noise, 0.0 (tonal: hum, whine) to 1.0 (white)
+    pub noise_flatness: f32,
+    /// Duration of noise-only audio at the start of the recording, in seconds
+    pub leading_noise_s: f32,
+    /// Sample peak, 0.0-1.0 for full scale
+    pub peak: f32,
+}
+
+/// Noise floor of one frequency band
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BandNoise {
+    /// Lower band edge in Hz
+    pub low_hz: f32,
+    /// Upper band edge in Hz
+    pub high_hz: f32,
+    /// Noise floor in dBFS: the level of the band in its quiet frames
+    pub noise_floor_db: f32,
+}
+
+/// Mains hum found in a recording
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hum {
+    /// Mains frequency, 50 or 60 Hz
+    pub fundamental_hz: f32,
+    /// Harmonic numbers standing out of the spectrum, 1 being the fundamental
+    pub harmonics: Vec<usize>,
+    /// Combined level of those harmonics in dBFS
+    pub level_db: f32,
+}
+
+/// Full statistics of a recording, as reported by the `analyze` command
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnalysisReport {
+    /// Sample rate in Hz
+    pub sample_rate: u32,
+    /// Number of channels
+    pub channels: usize,
+    /// Duration in seconds
+    pub duration_s: f32,
+    /// Sample peak over all channels in dBFS
+    pub peak_db: f32,
+    /// RMS level over all channels in dBFS
+    pub rms_db: f32,
+    /// Mean of each channel, -1.0 to 1.0
+    pub dc_offset: Vec<f32>,
+    /// Samples at full scale, over all channels
+    pub clipped_samples: usize,
+    /// Noise floor, signal level and SNR of the channel average
+    pub levels: SignalAnalysis,
+    /// Noise floor per octave band up to the Nyquist frequency
+    pub bands: Vec<BandNoise>,
+    /// Mains hum, if any
+    pub hum: Option<Hum>,
+    /// Spectral flatness of the whole recording, 0.0 (tonal) to 1.0 (white)
+    pub spectral_flatness: f32,
+}
+
+/// Spectral subtraction parameters derived from a [`SignalAnalysis`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AutoSettings {
+    /// Frames of the noise-only intro used for the noise profile, 3-40
+    pub noise_frames: usize,
+    /// Spectral floor, 0.01-0.3
+    pub spectral_floor: f32,
+    /// Over-subtraction factor, 1.0-4.5
+    pub over_subtraction: f32,
+    /// Makeup gain, 1.0-2.0 and limited by the input peak
+    pub makeup_gain: f32,
+}
+
+/// Analyze mono samples
+pub fn analyze(samples: &[f32], sample_rate: u32) -> SignalAnalysis {
+    let frame_len = ((sample_rate as f32 * FRAME_SECONDS) as usize).max(1);
+    let energies: Vec<f32> = samples
+        .chunks_exact(frame_len)
+        .map(|frame| frame.iter().map(|s| s * s).sum::<f32>() / frame_len as f32)
+        .collect();
+    let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+
+    if energies.is_empty() {
+        return SignalAnalysis {
+            noise_floor_db: SILENCE_DB,
+            signal_level_db: SILENCE_DB,
+            snr_db: 0.0,
+            noise_flatness: 1.0,
+            leading_noise_s: 0.0,
+            peak,
+        };
+    }
+
+    let mut sorted = energies.clone();
+    sorted.sort_by(f32::total_cmp);
+    let percentile = |q: f32| sorted[((sorted.len() - 1) as f32 * q).round() as usize];
+    let noise_power = percentile(NOISE_PERCENTILE);
+    let signal_power = percentile(SIGNAL_PERCENTILE);
+
+    let noise_floor_db = to_db(noise_power);
+    let signal_level_db = to_db(signal_power);
+    let snr_db = if noise_power > 0.0 {
+        to_db((signal_power - noise_power).max(0.0) / noise_power).max(0.0)
+    } else if signal_power > 0.0 {
+        -SILENCE_DB
+    } else {
+        0.0
+    };
+
+    let noise_limit = noise_power * 10f32.powf(NOISE_MARGIN_DB / 10.0);
+    let noise_frames: Vec<&[f32]> = samples
+        .chunks_exact(frame_len)
+        .zip(&energies)
+        .filter(|(_, &energy)| energy <= noise_limit)
+        .map(|(frame, _)| frame)
+        .collect();
+    let leading = energies.iter().take_while(|&&energy| energy <= noise_limit).count();
+
+    SignalAnalysis {
+        noise_floor_db,
+        signal_level_db,
+        snr_db,
+        noise_flatness: spectral_flatness(&noise_frames),
+        leading_noise_s: leading as f32 * frame_len as f32 / sample_rate as f32,
+        peak,
+    }
+}
+
+/// Analyze one or more channels of equal length
+///
+/// Levels, bands, hum and flatness are measured on the channel average;
+/// peak, RMS, DC offset and clipping look at every channel.
+pub fn report(channels: &[Vec<f32>], sample_rate: u32) -> AnalysisReport {
+    let len = channels.first().map_or(0, Vec::len);
+    let mono: Vec<f32> = (0..len)
+        .map(|i| channels.iter().map(|channel| channel[i]).sum::<f32>() / channels.len() as f32)
+        .collect();
+    let all = || channels.iter().flatten().copied();
+    let count = (len * channels.len()).max(1);
+
+    AnalysisReport {
+        sample_rate,
+        channels: channels.len(),
+        duration_s: len as f32 / sample_rate as f32,
+        peak_db: to_db(all().fold(0.0f32, |peak, s| peak.max(s * s))),
+        rms_db: to_db(all().map(|s| s * s).sum::<f32>() / count as f32),
+        dc_offset: channels.iter().map(|channel| dc_offset(channel)).collect(),
+        clipped_samples: channels.iter().map(|channel| clipped_samples(channel)).sum(),
+        levels: analyze(&mono, sample_rate),
+        bands: band_noise_floors(&mono, sample_rate),
+        hum: detect_hum(&mono, sample_rate),
+        spectral_flatness: flatness(&mono, sample_rate),
+    }
+}
+
+/// RMS level in dBFS
+pub fn rms_db(samples: &[f32]) -> f32 {
+    to_db(samples.iter().map(|s| s * s).sum::<f32>() / samples.len().max(1) as f32)
+}
+
+/// Mean of the samples
+pub fn dc_offset(samples: &[f32]) -> f32 {
+    samples.iter().sum::<f32>() / samples.len().max(1) as f32
+}
+
+/// Number of samples at full scale
+pub fn clipped_samples(samples: &[f32]) -> usize {
+    samples.iter().filter(|s| s.abs() >= CLIP_LEVEL).count()
+}
+
+/// Noise floor per octave band, from the quiet frames of each band
+///
+/// Uses the noise reduction's own STFT frames; each band's floor is the same
+/// low percentile of its frame levels that [`analyze`] takes over the full band.
+pub fn band_noise_floors(samples: &[f32], sample_rate: u32) -> Vec<BandNoise> {
+    let spectra = AudioProcessor::new(FRAME_SIZE).stft_magnitudes(samples);
+    let nyquist = sample_rate as f32 / 2.0;
+    let bin_hz = sample_rate as f32 / FRAME_SIZE as f32;
+    let window_energy: f32 = (0..FRAME_SIZE)
+        .map(|i| (0.5 * (1.0 - (2.0 * PI * i as f32 / (FRAME_SIZE - 1) as f32).cos())).powi(2))
+        .sum();
+
+    OCTAVES
+        .map(|octave| 1000.0 * 2f32.powi(octave))
+        .map(|center| (center / 2f32.sqrt(), (center * 2f32.sqrt()).min(nyquist)))
+        .filter(|&(low, high)| low < high)
+        .map(|(low_hz, high_hz)| {
+            let bins = (low_hz / bin_hz).ceil() as usize..=((high_hz / bin_hz).floor() as usize).min(FRAME_SIZE / 2);
+            // One-sided Parseval: mean square of the band within the windowed frame
+            let mut powers: Vec<f32> = spectra
+                .iter()
+                .map(|spectrum| {
+                    2.0 * spectrum[bins.clone()].iter().map(|m| m * m).sum::<f32>() / (FRAME_SIZE as f32 * window_energy)
+                })
+                .collect();
+            powers.sort_by(f32::total_cmp);
+            let noise_floor_db = match powers.len() {
+                0 => SILENCE_DB,
+                n => to_db(powers[((n - 1) as f32 * NOISE_PERCENTILE).round() as usize]),
+            };
+            BandNoise { low_hz, high_hz, noise_floor_db }
+        })
+        .collect()
+}
+
+/// Look for 50 or 60 Hz mains hum and its harmonics
+///
+/// Averages one-second spectra (about 1 Hz resolution) over the recording and
+/// checks each harmonic for a peak well above the bins around it. The mains
+/// frequency with more such harmonics wins; `None` when neither has at least
+/// two, so a single steady tone is not taken for hum.
+pub fn detect_hum(samples: &[f32], sample_rate: u32) -> Option<Hum> {
+    let size = (sample_rate as usize).next_power_of_two();
+    if samples.len() < size {
+        return None;
+    }
+    let fft = FftPlanner::<f32>::new().plan_fft_forward(size);
+    let window: Vec<f32> = (0..size).map(|i| 0.5 * (1.0 - (2.0 * PI * i as f32 / (size - 1) as f32).cos())).collect();
+    let window_energy: f32 = window.iter().map(|w| w * w).sum();
+
+    let mut power = vec![0.0f32; size / 2 + 1];
+    let segments = samples.chunks_exact(size);
+    let count = segments.len();
+    for segment in segments {
+        let mut buffer: Vec<Complex<f32>> = segment.iter().zip(&window).map(|(s, w)| Complex::new(s * w, 0.0)).collect();
+        fft.process(&mut buffer);
+        for (p, bin) in power.iter_mut().zip(&buffer) {
+            *p += bin.norm_sqr() / count as f32;
+        }
+    }
+
+    let bin_hz = sample_rate as f32 / size as f32;
+    let prominence = 10f32.powf(HUM_PROMINENCE_DB / 10.0);
+    MAINS_HZ
+        .iter()
+        .filter_map(|&fundamental_hz| {
+            let mut harmonics = Vec::new();
+            let mut hum_power = 0.0;
+            for harmonic in 1..=HUM_HARMONICS {
+                let center = (fundamental_hz * harmonic as f32 / bin_hz).round() as usize;
+                // Neighbourhood of +-10 Hz, leaving out the main lobe
+                let reach = (10.0 / bin_hz).ceil() as usize;
+                if center < reach || center + reach >= power.len() {
+                    break;
+                }
+                let peak: f32 = power[center - 2..=center + 2].iter().sum();
+                let mut around: Vec<f32> = power[center - reach..center - 2]
+                    .iter()
+                    .chain(&power[center + 3..=center + reach])
+                    .copied()
+                    .collect();
+                around.sort_by(f32::total_cmp);
+                let background = around[around.len() / 2] * 5.0;
+                if peak > background * prominence && peak > 0.0 {
+                    harmonics.push(harmonic);
+                    hum_power += 2.0 * (peak - background) / (size as f32 * window_energy);
+                }
+            }
+            (harmonics.len() >= MIN_HUM_HARMONICS).then(|| Hum { fundamental_hz, harmonics, level_db: to_db(hum_power) })
+        })
+        .max_by(|a, b| a.harmonics.len().cmp(&b.harmonics.len()).then(a.level_db.total_cmp(&b.level_db)))
+}
+
+/// Spectral flatness of the whole recording, over 20 ms frames
+pub fn flatness(samples: &[f32], sample_rate: u32) -> f32 {
+    let frame_len = ((sample_rate as f32 * FRAME_SECONDS) as usize).max(1);
+    let frames: Vec<&[f32]> = samples.chunks_exact(frame_len).collect();
+    spectral_flatness(&frames)
+}
+
+impl AutoSettings {
+    /// Choose settings for a recording with the given analysis
+    ///
+    /// Low SNR moves from the Light towards the Extreme preset. Tonal noise
+    /// is subtracted harder with a lower floor, since it sits in few bins;
+    /// broadband noise keeps a higher floor to avoid musical noise. Makeup
+    /// gain follows the presets but never pushes the input peak past full scale.
+    pub fn from_analysis(analysis: &SignalAnalysis, sample_rate: u32) -> Self {
+        let severity = ((30.0 - analysis.snr_db) / 25.0).clamp(0.0, 1.0);
+        let tonality = 1.0 - analysis.noise_flatness.clamp(0.0, 1.0);
+
+        let over_subtraction = 1.0 + 3.0 * severity + 0.5 * tonality;
+        let spectral_floor = (0.25 * (0.02f32 / 0.25).powf(severity) * (1.5 - tonality)).clamp(0.01, 0.3);
+        let headroom = if analysis.peak > 0.0 { 0.99 / analysis.peak } else { f32::INFINITY };
+        let makeup_gain = (1.2 + 0.3 * (over_subtraction - 1.0)).min(2.0).min(headroom).max(1.0);
+
+        let leading_samples = (analysis.leading_noise_s * sample_rate as f32) as usize;
+        let noise_frames = (leading_samples.saturating_sub(FRAME_SIZE) / HOP_SIZE + 1).clamp(3, 40);
+
+        AutoSettings {
+            noise_frames,
+            spectral_floor,
+            over_subtraction,
+            makeup_gain,
+        }
+    }
+
+    /// Analyze mono samples and choose settings for them
+    pub fn for_samples(samples: &[f32], sample_rate: u32) -> Self {
+        Self::from_analysis(&analyze(samples, sample_rate), sample_rate)
+    }
+
+    /// Write the chosen values into `config`, leaving all other options alone
+    pub fn apply(&self, config: &mut NoiseReductionConfig) {
+        self.to_preset().apply(config);
+    }
+
+    /// The chosen values as a preset named "auto"
+    pub fn to_preset(&self) -> PresetDefinition {
+        PresetDefinition {
+            name: "auto".to_string(),
+            description: "Chosen from the measured SNR and noise".to_string(),
+            noise_frames: self.noise_frames,
+            spectral_floor: self.spectral_floor,
+            over_subtraction: self.over_subtraction,
+            makeup_gain: self.makeup_gain,
+        }
+    }
+}
+
+fn to_db(power: f32) -> f32 {
+    if power > 0.0 {
+        (10.0 * power.log10()).max(SILENCE_DB)
+    } else {
+        SILENCE_DB
+    }
+}
+
+/// Flatness (geometric over arithmetic mean) of the average power spectrum of `frames`
+fn spectral_flatness(frames: &[&[f32]]) -> f32 {
+    let Some(first) = frames.first() else {
+        return 1.0;
+    };
+    let size = first.len().next_power_of_two();
+    if size < 4 {
+        return 1.0;
+    }
+    let fft = FftPlanner::<f32>::new().plan_fft_forward(size);
+    let window: Vec<f32> = (0..first.len())
+        .map(|i| 0.5 * (1.0 - (2.0 * PI * i as f32 / (first.len() - 1).max(1) as f32).cos()))
+        .collect();
+
+    let mut power = vec![0.0f32; size / 2];
+    for frame in frames {
+        let mut buffer: Vec<Complex<f32>> = frame
+            .iter()
+            .zip(&window)
+            .map(|(s, w)| Complex::new(s * w, 0.0))
+            .chain(std::iter::repeat(Complex::new(0.0, 0.0)))
+            .take(size)
+            .collect();
+        fft.process(&mut buffer);
+        for (p, bin) in power.iter_mut().zip(&buffer) {
+            *p += bin.norm_sqr();
+        }
+    }
+
+    // Skip DC, which the noise floor measurement does not care about
+    let bins = &power[1..];
+    let mean = bins.iter().sum::<f32>() / bins.len() as f32;
+    if mean <= 0.0 {
+        return 1.0;
+    }
+    let log_mean = bins.iter().map(|&p| (p.max(mean * 1e-12)).ln()).sum::<f32>() / bins.len() as f32;
+    (log_mean.exp() / mean).clamp(0.0, 1.0)
+}
@@ -0,0 +1,239 @@
+//! # Adaptive Noise Cancellation
+//!
+//! Two-channel noise canceller for rigs with a dedicated noise-reference
+//! microphone (e.g. next to a fan). An adaptive FIR filter learns the path
+//! from the reference to the primary microphone; its output is the part of
+//! the primary signal that is correlated with the reference, and subtracting
+//! it leaves the speech. Runs before spectral noise reduction, which then only
+//! has to deal with the uncorrelated remainder.
+//!
+//! - **NLMS**: normalized least mean squares, O(L) per sample.
+//! - **RLS**: recursive least squares, converges much faster but costs O(L²)
+//!   per sample; keep `filter_length` short.
+//! - **Frequency-domain**: constrained overlap-save block filter with per-bin
+//!   step normalization; O(log L) per sample, suited to long filters.
+
+use num_complex::Complex;
+use rustfft::FftPlanner;
+use std::fmt;
+use std::str::FromStr;
+
+/// Smoothing of the per-bin reference power in the frequency-domain filter
+const POWER_SMOOTHING: f32 = 0.9;
+/// Regularization added to power normalizations
+const EPSILON: f32 = 1e-8;
+
+/// Adaptation algorithm
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "kebab-case"))]
+pub enum AncMethod {
+    Nlms,
+    Rls,
+    FrequencyDomain,
+}
+
+impl FromStr for AncMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "nlms" => Ok(AncMethod::Nlms),
+            "rls" => Ok(AncMethod::Rls),
+            "fdaf" | "frequency-domain" => Ok(AncMethod::FrequencyDomain),
+            _ => Err(format!("unknown adaptive filter '{}' (expected nlms, rls or fdaf)", s)),
+        }
+    }
+}
+
+impl fmt::Display for AncMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AncMethod::Nlms => "nlms",
+            AncMethod::Rls => "rls",
+            AncMethod::FrequencyDomain => "fdaf",
+        })
+    }
+}
+
+/// Configuration for the adaptive noise canceller
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default, deny_unknown_fields))]
+pub struct AncConfig {
+    /// Adaptation algorithm (default: Nlms)
+    pub method: AncMethod,
+    /// Adaptive filter length in taps; must cover the reference-to-primary delay plus the room response (default: 256)
+    pub filter_length: usize,
+    /// Normalized step size 0.0-1.0 for NLMS and the frequency-domain filter (default: 0.1)
+    pub step_size: f32,
+    /// RLS forgetting factor just below 1.0; lower tracks faster but is noisier (default: 0.9995)
+    pub forgetting_factor: f32,
+}
+
+impl Default for AncConfig {
+    fn default() -> Self {
+        Self {
+            method: AncMethod::Nlms,
+            filter_length: 256,
+            step_size: 0.1,
+            forgetting_factor: 0.9995,
+        }
+    }
+}
+
+/// Remove the part of `primary` that is correlated with `reference`
+///
+/// Both signals must be time-aligned recordings. The result has the length
+/// of `primary`: a shorter reference is padded with silence, which leaves the
+/// rest of `primary` as it is once the filter has run out of reference, and a
+/// longer one is cut to length.
+pub fn cancel(primary: &[f32], reference: &[f32], config: &AncConfig) -> Vec<f32> {
+    let mut reference = reference[..reference.len().min(primary.len())].to_vec();
+    reference.resize(primary.len(), 0.0);
+    let reference = reference.as_slice();
+    let taps = config.filter_length.max(1);
+
+    match config.method {
+        AncMethod::Nlms => nlms(primary, reference, taps, config.step_size),
+        AncMethod::Rls => rls(primary, reference, taps, config.forgetting_factor),
+        AncMethod::FrequencyDomain => frequency_domain(primary, reference, taps, config.step_size),
+    }
+}
+
+fn nlms(primary: &[f32], reference: &[f32], taps: usize, step_size: f32) -> Vec<f32> {
+    let mut weights = vec![0.0f32; taps];
+    // Reference history, newest first, doubled so every window is a contiguous slice
+    let mut history = vec![0.0f32; 2 * taps];
+    let mut head = taps;
+    let mut power = 0.0f32;
+
+    primary
+        .iter()
+        .zip(reference)
+        .map(|(&desired, &input)| {
+            let oldest = history[head + taps - 1];
+            head = if head == 0 { taps - 1 } else { head - 1 };
+            history[head] = input;
+            history[head + taps] = input;
+            power = (power + input * input - oldest * oldest).max(0.0);
+
+            let window = &history[head..head + taps];
+            let estimate: f32 = weights.iter().zip(window).map(|(w, x)| w * x).sum();
+            let error = desired - estimate;
+
+            let scale = step_size * error / (power + EPSILON);
+            for (w, x) in weights.iter_mut().zip(window) {
+                *w += scale * x;
+            }
+            error
+        })
+        .collect()
+}
+
+fn rls(primary: &[f32], reference: &[f32], taps: usize, forgetting_factor: f32) -> Vec<f32> {
+    let lambda = forgetting_factor.clamp(0.9, 1.0) as f64;
+    let mut weights = vec![0.0f64; taps];
+    let mut window = vec![0.0f64; taps];
+    // Inverse correlation matrix, started at a large multiple of the identity
+    let mut inverse = vec![0.0f64; taps * taps];
+    for i in 0..taps {
+        inverse[i * taps + i] = 100.0;
+    }
+    let mut gain = vec![0.0f64; taps];
+
+    primary
+        .iter()
+        .zip(reference)
+        .map(|(&desired, &input)| {
+            window.rotate_right(1);
+            window[0] = input as f64;
+            // Without reference there is nothing to estimate, and updating would only inflate P by 1/λ
+            if window.iter().all(|&x| x == 0.0) {
+                return desired;
+            }
+
+            // gain = P·x / (λ + xᵀ·P·x)
+            for (i, g) in gain.iter_mut().enumerate() {
+                *g = inverse[i * taps..(i + 1) * taps].iter().zip(&window).map(|(p, x)| p * x).sum();
+            }
+            let denominator = lambda + window.iter().zip(&gain).map(|(x, g)| x * g).sum::<f64>();
+            for g in &mut gain {
+                *g /= denominator;
+            }
+
+            let estimate: f64 = weights.iter().zip(&window).map(|(w, x)| w * x).sum();
+            let error = desired as f64 - estimate;
+            for (w, g) in weights.iter_mut().zip(&gain) {
+                *w += g * error;
+            }
+
+            // P = (P - gain·xᵀ·P) / λ, using P symmetric so xᵀ·P = (P·x)ᵀ = gain·denominator
+            for i in 0..taps {
+                for j in 0..taps {
+                    let index = i * taps + j;
+                    inverse[index] = (inverse[index] - gain[i] * gain[j] * denominator) / lambda;
+                }
+            }
+            error as f32
+        })
+        .collect()
+}
+
+fn frequency_domain(primary: &[f32], reference: &[f32], taps: usize, step_size: f32) -> Vec<f32> {
+    let block = taps;
+    let size = 2 * block;
+    let mut planner = FftPlanner::<f32>::new();
+    let fft = planner.plan_fft_forward(size);
+    let ifft = planner.plan_fft_inverse(size);
+
+    let mut weights = vec![Complex::new(0.0f32, 0.0); size];
+    let mut power = vec![0.0f32; size];
+    let mut previous = vec![0.0f32; block];
+    let mut output = Vec::with_capacity(primary.len());
+
+    for (desired, input) in primary.chunks(block).zip(reference.chunks(block)) {
+        // Overlap-save: last block followed by the current one (zero-padded at the end of the signal)
+        let mut spectrum: Vec<Complex<f32>> = previous
+            .iter()
+            .chain(input)
+            .map(|&x| Complex::new(x, 0.0))
+            .chain(std::iter::repeat(Complex::new(0.0, 0.0)))
+            .take(size)
+            .collect();
+        fft.process(&mut spectrum);
+
+        let mut estimate: Vec<Complex<f32>> = spectrum.iter().zip(&weights).map(|(x, w)| x * w).collect();
+        ifft.process(&mut estimate);
+        let errors: Vec<f32> = desired
+            .iter()
+            .zip(&estimate[block..])
+            .map(|(d, y)| d - y.re / size as f32)
+            .collect();
+        output.extend_from_slice(&errors);
+
+        let mut gradient: Vec<Complex<f32>> = std::iter::repeat_n(Complex::new(0.0, 0.0), block)
+            .chain(errors.iter().map(|&e| Complex::new(e, 0.0)))
+            .chain(std::iter::repeat(Complex::new(0.0, 0.0)))
+            .take(size)
+            .collect();
+        fft.process(&mut gradient);
+        for ((g, x), p) in gradient.iter_mut().zip(&spectrum).zip(power.iter_mut()) {
+            *p = POWER_SMOOTHING * *p + (1.0 - POWER_SMOOTHING) * x.norm_sqr();
+            *g = x.conj() * *g / (*p + EPSILON);
+        }
+
+        // Gradient constraint: keep the update causal and `taps` long
+        ifft.process(&mut gradient);
+        for value in &mut gradient[block..] {
+            *value = Complex::new(0.0, 0.0);
+        }
+        fft.process(&mut gradient);
+        for (w, g) in weights.iter_mut().zip(&gradient) {
+            *w += g * (step_size / size as f32);
+        }
+
+        previous.fill(0.0);
+        previous[..input.len()].copy_from_slice(input);
+    }
+
+    output
+}
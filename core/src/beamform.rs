@@ -0,0 +1,386 @@
+//! # Beamforming
+//!
+//! Multi-microphone front-end that turns an N-channel recording into one
+//! enhanced mono stream before noise reduction. Channels are aligned towards
+//! the talker in the STFT domain, using delays from the array geometry (far
+//! field) or estimated with GCC-PHAT against the first channel.
+//!
+//! - **Delay-and-sum**: average of the aligned channels. Uncorrelated noise
+//!   drops by up to `10·log10(N)` dB.
+//! - **MVDR**: per-bin weights `R⁻¹d / (dᴴR⁻¹d)` with the noise covariance `R`
+//!   taken from the first `noise_frames` frames, like the noise profile. Passes
+//!   the look direction undistorted and steers nulls at directional noise.
+//!
+//! Frames are the noise reduction's: FRAME_SIZE long, HOP_SIZE apart, with
+//! the input zero-padded so the tail is covered.
+
+use crate::{hann_window, normalize_by_window_sum, padded_len, AudioProcessor, FRAME_SIZE, HOP_SIZE};
+use num_complex::Complex;
+use rustfft::FftPlanner;
+use std::f32::consts::PI;
+use std::fmt;
+use std::str::FromStr;
+
+/// Speed of sound in m/s
+const SPEED_OF_SOUND: f32 = 343.0;
+/// Length of the blocks GCC-PHAT accumulates cross-spectra over
+const CORRELATION_BLOCK: usize = 16384;
+
+/// Beamformer type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "kebab-case"))]
+pub enum BeamformMethod {
+    DelayAndSum,
+    Mvdr,
+}
+
+impl FromStr for BeamformMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "delay-and-sum" | "das" => Ok(BeamformMethod::DelayAndSum),
+            "mvdr" => Ok(BeamformMethod::Mvdr),
+            _ => Err(format!("unknown beamformer '{}' (expected delay-and-sum or mvdr)", s)),
+        }
+    }
+}
+
+impl fmt::Display for BeamformMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            BeamformMethod::DelayAndSum => "delay-and-sum",
+            BeamformMethod::Mvdr => "mvdr",
+        })
+    }
+}
+
+/// Where the beam points
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "kebab-case"))]
+pub enum Steering {
+    /// Estimate each channel's delay against channel 0 with GCC-PHAT
+    #[default]
+    Estimated,
+    /// Far-field look direction for microphones at known positions
+    Geometry {
+        /// Microphone positions in metres, one per channel
+        positions: Vec<[f32; 3]>,
+        /// Look direction in the x-y plane in degrees, 0 along +x, 90 along +y
+        azimuth_deg: f32,
+        /// Look direction above the x-y plane in degrees
+        elevation_deg: f32,
+    },
+}
+
+/// Configuration for the beamforming front-end
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default, deny_unknown_fields))]
+pub struct BeamformConfig {
+    /// Delay-and-sum or MVDR (default: DelayAndSum)
+    pub method: BeamformMethod,
+    /// Array geometry or estimated delays (default: Estimated)
+    pub steering: Steering,
+    /// Largest delay searched by GCC-PHAT in milliseconds, about the array aperture / 343 m/s (default: 1.0)
+    pub max_delay_ms: f32,
+    /// Frames at the start used for the MVDR noise covariance (default: 10)
+    pub noise_frames: usize,
+    /// MVDR diagonal loading relative to the mean noise power, trades nulling for robustness (default: 0.01)
+    pub diagonal_loading: f32,
+}
+
+impl Default for BeamformConfig {
+    fn default() -> Self {
+        Self {
+            method: BeamformMethod::DelayAndSum,
+            steering: Steering::Estimated,
+            max_delay_ms: 1.0,
+            noise_frames: 10,
+            diagonal_loading: 0.01,
+        }
+    }
+}
+
+/// Far-field arrival delay of each microphone relative to the first, in seconds
+///
+/// Positive delays mean the wavefront reaches that microphone later.
+pub fn geometry_delays(positions: &[[f32; 3]], azimuth_deg: f32, elevation_deg: f32) -> Vec<f32> {
+    let (azimuth, elevation) = (azimuth_deg.to_radians(), elevation_deg.to_radians());
+    let direction = [elevation.cos() * azimuth.cos(), elevation.cos() * azimuth.sin(), elevation.sin()];
+    let Some(origin) = positions.first() else {
+        return Vec::new();
+    };
+
+    positions
+        .iter()
+        .map(|position| {
+            let projection: f32 = (0..3).map(|axis| (position[axis] - origin[axis]) * direction[axis]).sum();
+            -projection / SPEED_OF_SOUND
+        })
+        .collect()
+}
+
+/// Delay of `channel` relative to `reference` in seconds, by GCC-PHAT
+///
+/// The peak of the whitened cross-correlation within `±max_delay_ms` is
+/// refined with parabolic interpolation. Positive values mean `channel` lags
+/// `reference`.
+pub fn gcc_phat(reference: &[f32], channel: &[f32], sample_rate: u32, max_delay_ms: f32) -> f32 {
+    let max_lag = ((max_delay_ms / 1000.0 * sample_rate as f32).ceil() as usize).max(1);
+    let correlation = phat_correlation(reference, channel);
+    let peak = correlation.peak(max_lag);
+
+    let (left, center, right) = (correlation.at(peak - 1), correlation.at(peak), correlation.at(peak + 1));
+    let curvature = left - 2.0 * center + right;
+    let offset = if curvature < 0.0 { (0.5 * (left - right) / curvature).clamp(-0.5, 0.5) } else { 0.0 };
+    (peak as f32 + offset) / sample_rate as f32
+}
+
+/// Cross-correlation of two signals whitened by the phase transform, from [`phat_correlation`]
+pub(crate) struct Correlation(Vec<f32>);
+
+impl Correlation {
+    /// Value at `lag` samples; positive lags mean the signal lags the reference
+    pub(crate) fn at(&self, lag: isize) -> f32 {
+        match self.0.len() {
+            0 => 0.0,
+            len => self.0[lag.rem_euclid(len as isize) as usize],
+        }
+    }
+
+    /// Lag of the highest value within `±max_lag`, the smaller lag on ties
+    pub(crate) fn peak(&self, max_lag: usize) -> isize {
+        let reach = max_lag.min((self.0.len() / 2).saturating_sub(1)) as isize;
+        (-reach..=reach)
+            .max_by(|&a, &b| self.at(a).total_cmp(&self.at(b)).then(b.abs().cmp(&a.abs())))
+            .unwrap_or(0)
+    }
+}
+
+/// GCC-PHAT: cross-correlation of `signal` against `reference`, whitened by the phase transform
+///
+/// Cross-spectra of zero-padded blocks are accumulated, so the correlation is
+/// linear rather than circular, and whitening keeps tonal content from making
+/// the peak ambiguous. Lags up to a quarter of the block are well covered.
+pub(crate) fn phat_correlation(reference: &[f32], signal: &[f32]) -> Correlation {
+    let len = reference.len().min(signal.len());
+    if len == 0 {
+        return Correlation(Vec::new());
+    }
+    let size = 2 * CORRELATION_BLOCK.min(len).next_power_of_two();
+    let mut planner = FftPlanner::<f32>::new();
+    let fft = planner.plan_fft_forward(size);
+    let ifft = planner.plan_fft_inverse(size);
+
+    let padded = |samples: &[f32]| -> Vec<Complex<f32>> {
+        let mut buffer: Vec<Complex<f32>> = samples.iter().map(|&s| Complex::new(s, 0.0)).collect();
+        buffer.resize(size, Complex::new(0.0, 0.0));
+        buffer
+    };
+    let mut spectrum = vec![Complex::new(0.0f32, 0.0); size];
+    for start in (0..len).step_by(size / 2) {
+        let end = (start + size / 2).min(len);
+        let mut a = padded(&reference[start..end]);
+        let mut b = padded(&signal[start..end]);
+        fft.process(&mut a);
+        fft.process(&mut b);
+        for ((s, a), b) in spectrum.iter_mut().zip(&a).zip(&b) {
+            *s += a.conj() * b;
+        }
+    }
+    for bin in &mut spectrum {
+        let magnitude = bin.norm();
+        if magnitude > 0.0 {
+            *bin /= magnitude;
+        }
+    }
+    ifft.process(&mut spectrum);
+    Correlation(spectrum.iter().map(|bin| bin.re).collect())
+}
+
+/// Steering delays for `channels` in seconds, relative to channel 0
+pub fn steering_delays(channels: &[Vec<f32>], sample_rate: u32, config: &BeamformConfig) -> Vec<f32> {
+    match &config.steering {
+        Steering::Geometry { positions, azimuth_deg, elevation_deg } => {
+            let mut delays = geometry_delays(positions, *azimuth_deg, *elevation_deg);
+            delays.resize(channels.len(), 0.0);
+            delays
+        }
+        Steering::Estimated => channels
+            .iter()
+            .map(|channel| gcc_phat(&channels[0], channel, sample_rate, config.max_delay_ms))
+            .collect(),
+    }
+}
+
+/// Combine equal-length `channels` into one enhanced channel
+pub fn beamform(channels: &[Vec<f32>], sample_rate: u32, config: &BeamformConfig) -> Vec<f32> {
+    match channels.len() {
+        0 => return Vec::new(),
+        1 => return channels[0].clone(),
+        _ => {}
+    }
+    let len = channels.iter().map(Vec::len).min().unwrap_or(0);
+    if len < FRAME_SIZE {
+        return channels[0][..len].to_vec();
+    }
+
+    let delays = steering_delays(channels, sample_rate, config);
+    let mut processor = AudioProcessor::new(FRAME_SIZE);
+    let spectra = stft(&mut processor, channels);
+
+    // Steering vector per bin: d_m = exp(-jωτ_m)
+    let steering: Vec<Vec<Complex<f64>>> = (0..=FRAME_SIZE / 2)
+        .map(|bin| {
+            let omega = 2.0 * PI * bin as f32 * sample_rate as f32 / FRAME_SIZE as f32;
+            delays.iter().map(|&tau| Complex::from_polar(1.0, -(omega * tau) as f64)).collect()
+        })
+        .collect();
+
+    let weights: Vec<Vec<Complex<f64>>> = match config.method {
+        BeamformMethod::DelayAndSum => {
+            let count = channels.len() as f64;
+            steering.iter().map(|d| d.iter().map(|value| value / count).collect()).collect()
+        }
+        BeamformMethod::Mvdr => mvdr_weights(&spectra, &steering, config),
+    };
+
+    let mut frames = Vec::with_capacity(spectra.len());
+    for frame in &spectra {
+        let mut output = vec![Complex::new(0.0f32, 0.0); FRAME_SIZE];
+        for bin in 0..=FRAME_SIZE / 2 {
+            // y = wᴴx
+            let value: Complex<f64> = weights[bin]
+                .iter()
+                .zip(frame)
+                .map(|(w, x)| w.conj() * Complex::new(x[bin].re as f64, x[bin].im as f64))
+                .sum();
+            output[bin] = Complex::new(value.re as f32, value.im as f32);
+            if bin > 0 && bin < FRAME_SIZE / 2 {
+                output[FRAME_SIZE - bin] = output[bin].conj();
+            }
+        }
+        frames.push(output);
+    }
+
+    overlap_add(&mut processor, frames, len)
+}
+
+/// Windowed spectra of the zero-padded channels, indexed `[frame][channel][bin]`; empty below one frame
+fn stft(processor: &mut AudioProcessor, channels: &[Vec<f32>]) -> Vec<Vec<Vec<Complex<f32>>>> {
+    let len = channels.iter().map(Vec::len).min().unwrap_or(0);
+    if len < FRAME_SIZE {
+        return Vec::new();
+    }
+    let padded: Vec<Vec<f32>> = channels
+        .iter()
+        .map(|channel| {
+            let mut channel = channel[..len].to_vec();
+            channel.resize(padded_len(len, FRAME_SIZE, HOP_SIZE), 0.0);
+            channel
+        })
+        .collect();
+
+    (0..=(padded_len(len, FRAME_SIZE, HOP_SIZE) - FRAME_SIZE) / HOP_SIZE)
+        .map(|index| {
+            padded
+                .iter()
+                .map(|channel| {
+                    let mut frame = Vec::from(&channel[index * HOP_SIZE..index * HOP_SIZE + FRAME_SIZE]);
+                    processor.apply_hann_window(&mut frame);
+                    processor.fft_forward(&frame)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Inverse STFT normalized by the window sum, so untouched spectra reconstruct the first `len` input samples
+fn overlap_add(processor: &mut AudioProcessor, frames: Vec<Vec<Complex<f32>>>, len: usize) -> Vec<f32> {
+    let window = hann_window(FRAME_SIZE);
+    let mut output = vec![0.0f32; padded_len(len, FRAME_SIZE, HOP_SIZE)];
+    let mut window_sum = vec![0.0f32; padded_len(len, FRAME_SIZE, HOP_SIZE)];
+
+    for (index, mut spectrum) in frames.into_iter().enumerate() {
+        let pos = index * HOP_SIZE;
+        for (i, sample) in processor.fft_inverse(&mut spectrum).into_iter().enumerate() {
+            output[pos + i] += sample;
+            window_sum[pos + i] += window[i];
+        }
+    }
+
+    normalize_by_window_sum(&mut output, &window_sum);
+    output.truncate(len);
+    output
+}
+
+fn mvdr_weights(
+    spectra: &[Vec<Vec<Complex<f32>>>],
+    steering: &[Vec<Complex<f64>>],
+    config: &BeamformConfig,
+) -> Vec<Vec<Complex<f64>>> {
+    let channels = steering[0].len();
+    let noise_frames = &spectra[..config.noise_frames.clamp(1, spectra.len())];
+
+    steering
+        .iter()
+        .enumerate()
+        .map(|(bin, d)| {
+            let mut covariance = vec![vec![Complex::new(0.0f64, 0.0); channels]; channels];
+            for frame in noise_frames {
+                for (i, row) in covariance.iter_mut().enumerate() {
+                    let xi = Complex::new(frame[i][bin].re as f64, frame[i][bin].im as f64);
+                    for (j, value) in row.iter_mut().enumerate() {
+                        let xj = Complex::new(frame[j][bin].re as f64, frame[j][bin].im as f64);
+                        *value += xi * xj.conj();
+                    }
+                }
+            }
+
+            let trace: f64 = (0..channels).map(|i| covariance[i][i].re).sum();
+            let loading = (config.diagonal_loading as f64 * trace / channels as f64).max(1e-12);
+            for (i, row) in covariance.iter_mut().enumerate() {
+                row[i] += loading;
+            }
+
+            match solve(covariance, d.clone()) {
+                Some(r_inv_d) => {
+                    let denominator: Complex<f64> = d.iter().zip(&r_inv_d).map(|(di, ri)| di.conj() * ri).sum();
+                    r_inv_d.iter().map(|value| value / denominator).collect()
+                }
+                None => d.iter().map(|value| value / channels as f64).collect(),
+            }
+        })
+        .collect()
+}
+
+/// Solve `a·x = b` by Gaussian elimination with partial pivoting
+fn solve(mut a: Vec<Vec<Complex<f64>>>, mut b: Vec<Complex<f64>>) -> Option<Vec<Complex<f64>>> {
+    let n = b.len();
+    for column in 0..n {
+        let pivot = (column..n).max_by(|&i, &j| a[i][column].norm().total_cmp(&a[j][column].norm()))?;
+        if a[pivot][column].norm() < 1e-300 {
+            return None;
+        }
+        a.swap(column, pivot);
+        b.swap(column, pivot);
+
+        let (upper, lower) = a.split_at_mut(column + 1);
+        let pivot_row = &upper[column];
+        for (offset, row) in lower.iter_mut().enumerate() {
+            let factor = row[column] / pivot_row[column];
+            for (value, &pivot_value) in row[column..].iter_mut().zip(&pivot_row[column..]) {
+                *value -= factor * pivot_value;
+            }
+            let pivot_b = b[column];
+            b[column + 1 + offset] -= factor * pivot_b;
+        }
+    }
+
+    let mut x = vec![Complex::new(0.0, 0.0); n];
+    for row in (0..n).rev() {
+        let sum: Complex<f64> = (row + 1..n).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+    Some(x)
+}
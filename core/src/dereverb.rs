@@ -0,0 +1,167 @@
+//! # Dereverberation
+//!
+//! Single-channel late-reverberation suppression (Lebart / Habets). Late
+//! reverberation is modelled as an exponential decay, so its spectral variance
+//! at frame `n` is predicted from the smoothed observed spectrum
+//! `late_onset_ms` earlier, attenuated by the decay over that interval. The
+//! prediction is then removed with a spectral-subtraction gain inside the
+//! existing STFT loop. The decay rate comes from a configured T60 or is
+//! estimated blindly from free decays in the recording.
+
+use crate::filter::FilterBank;
+use num_complex::Complex;
+use std::collections::VecDeque;
+
+/// T60 used when blind estimation finds no usable decays, in seconds
+pub const FALLBACK_T60: f32 = 0.5;
+/// Recursive smoothing factor for the observed power spectrum
+const PSD_SMOOTHING: f32 = 0.5;
+/// Energy envelope resolution for T60 estimation, in milliseconds
+const ENVELOPE_STEP_MS: f32 = 10.0;
+/// Minimum length of a free decay used for T60 estimation, in milliseconds
+const MIN_DECAY_MS: f32 = 80.0;
+/// Minimum level drop of a free decay used for T60 estimation, in dB
+const MIN_DECAY_DB: f32 = 10.0;
+/// Band used for T60 estimation, where speech and room decay dominate, in Hz
+const ESTIMATION_BAND_HZ: (f32, f32) = (500.0, 4000.0);
+
+/// Configuration for late-reverberation suppression
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default, deny_unknown_fields))]
+pub struct DereverbConfig {
+    /// Reverberation time in seconds, `None` to estimate it from the input (default: None)
+    pub t60: Option<f32>,
+    /// Time after the direct sound where late reverberation starts, in milliseconds (default: 50.0)
+    pub late_onset_ms: f32,
+    /// Over-suppression factor for the predicted reverberation (default: 1.0)
+    pub strength: f32,
+    /// Minimum gain 0.0-1.0, limits musical noise (default: 0.1)
+    pub floor: f32,
+}
+
+impl Default for DereverbConfig {
+    fn default() -> Self {
+        Self {
+            t60: None,
+            late_onset_ms: 50.0,
+            strength: 1.0,
+            floor: 0.1,
+        }
+    }
+}
+
+/// Late-reverberation suppressor state carried across STFT frames
+pub(crate) struct Dereverb {
+    history: VecDeque<Vec<f32>>,
+    smoothed: Vec<f32>,
+    delay_frames: usize,
+    decay: f32,
+    strength: f32,
+    floor: f32,
+}
+
+impl Dereverb {
+    pub(crate) fn new(config: &DereverbConfig, t60: f32, sample_rate: u32, frame_size: usize, hop_size: usize) -> Self {
+        let hop_seconds = hop_size as f32 / sample_rate as f32;
+        let delay_frames = ((config.late_onset_ms / 1000.0 / hop_seconds).round() as usize).max(1);
+        let delay_seconds = delay_frames as f32 * hop_seconds;
+        let decay_rate = 3.0 * std::f32::consts::LN_10 / t60.max(0.01);
+
+        Dereverb {
+            history: VecDeque::with_capacity(delay_frames + 1),
+            smoothed: vec![0.0; frame_size],
+            delay_frames,
+            decay: (-2.0 * decay_rate * delay_seconds).exp(),
+            strength: config.strength,
+            floor: config.floor.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Suppress predicted late reverberation in a full (two-sided) spectrum
+    pub(crate) fn process_frame(&mut self, spectrum: &mut [Complex<f32>]) {
+        let power: Vec<f32> = spectrum.iter().map(|bin| bin.norm_sqr()).collect();
+
+        if self.history.len() == self.delay_frames {
+            let past = &self.history[0];
+            for ((bin, &current), &previous) in spectrum.iter_mut().zip(&power).zip(past) {
+                let late = self.decay * previous;
+                let gain = if current > 0.0 {
+                    (1.0 - self.strength * late / current).max(0.0).sqrt().max(self.floor)
+                } else {
+                    self.floor
+                };
+                *bin *= gain;
+            }
+        }
+
+        for (smoothed, &current) in self.smoothed.iter_mut().zip(&power) {
+            *smoothed = PSD_SMOOTHING * *smoothed + (1.0 - PSD_SMOOTHING) * current;
+        }
+        if self.history.len() == self.delay_frames {
+            self.history.pop_front();
+        }
+        self.history.push_back(self.smoothed.clone());
+    }
+}
+
+/// Blind T60 estimate in seconds from free decays in the signal
+///
+/// Looks for stretches where the band-limited energy envelope falls steadily
+/// (speech offsets), fits a decay slope to each and takes a low percentile,
+/// since a decay can never be faster than the room allows. Returns `None`
+/// when the signal has no usable decays.
+pub fn estimate_t60(samples: &[f32], sample_rate: u32) -> Option<f32> {
+    let mut band = samples.to_vec();
+    FilterBank::new(sample_rate)
+        .highpass(ESTIMATION_BAND_HZ.0, crate::filter::DEFAULT_Q)
+        .lowpass(ESTIMATION_BAND_HZ.1, crate::filter::DEFAULT_Q)
+        .process(&mut band);
+
+    let step = ((ENVELOPE_STEP_MS / 1000.0 * sample_rate as f32) as usize).max(1);
+    let envelope: Vec<f32> = band
+        .chunks_exact(step)
+        .map(|block| {
+            let power = block.iter().map(|s| s * s).sum::<f32>() / block.len() as f32;
+            10.0 * power.max(1e-12).log10()
+        })
+        .collect();
+
+    let step_seconds = step as f32 / sample_rate as f32;
+    let min_len = (MIN_DECAY_MS / ENVELOPE_STEP_MS).ceil() as usize;
+    let mut candidates = Vec::new();
+
+    let mut start = 0;
+    while start + 1 < envelope.len() {
+        let mut end = start;
+        while end + 1 < envelope.len() && envelope[end + 1] < envelope[end] {
+            end += 1;
+        }
+
+        let run = &envelope[start..=end];
+        if run.len() >= min_len && run[0] - run[run.len() - 1] >= MIN_DECAY_DB {
+            let slope = regression_slope(run) / step_seconds;
+            if slope < 0.0 {
+                candidates.push(-60.0 / slope);
+            }
+        }
+        start = end + 1;
+    }
+
+    if candidates.is_empty() {
+        return None;
+    }
+    candidates.sort_by(|a, b| a.total_cmp(b));
+    Some(candidates[candidates.len() / 4].clamp(0.1, 5.0))
+}
+
+/// Least-squares slope of `values` against their index
+fn regression_slope(values: &[f32]) -> f32 {
+    let n = values.len() as f32;
+    let mean_x = (n - 1.0) / 2.0;
+    let mean_y = values.iter().sum::<f32>() / n;
+    let (covariance, variance) = values.iter().enumerate().fold((0.0, 0.0), |(c, v), (i, &y)| {
+        let dx = i as f32 - mean_x;
+        (c + dx * (y - mean_y), v + dx * dx)
+    });
+    covariance / variance
+}
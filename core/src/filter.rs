@@ -0,0 +1,330 @@
+//! # Biquad Filters
+//!
+//! RBJ-cookbook biquads (high-pass, low-pass, shelves, peaking) and a
+//! chainable [`FilterBank`] for the pre- and post-filters around the FFT
+//! stage, plus DC offset removal so offset and rumble don't eat into the noise
+//! profile or the output headroom.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use bg_noise_reduction_core::filter::FilterBank;
+//!
+//! let mut samples = vec![0.0f32; 4410];
+//! FilterBank::new(44100)
+//!     .highpass(80.0, 0.707)
+//!     .peaking(3000.0, 2.0, 1.0)
+//!     .process(&mut samples);
+//! ```
+
+use std::f64::consts::PI;
+use std::fmt;
+use std::str::FromStr;
+
+/// Default Q for high-pass, low-pass and shelving filters (Butterworth / slope 1)
+pub const DEFAULT_Q: f32 = std::f32::consts::FRAC_1_SQRT_2;
+/// Default Q for peaking filters
+pub const DEFAULT_PEAKING_Q: f32 = 1.0;
+/// Largest boost or cut in dB a shelving or peaking filter accepts
+pub const MAX_GAIN_DB: f32 = 60.0;
+
+/// Biquad response type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterKind {
+    HighPass,
+    LowPass,
+    LowShelf,
+    HighShelf,
+    Peaking,
+}
+
+impl FilterKind {
+    fn has_gain(self) -> bool {
+        matches!(self, FilterKind::LowShelf | FilterKind::HighShelf | FilterKind::Peaking)
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            FilterKind::HighPass => "hp",
+            FilterKind::LowPass => "lp",
+            FilterKind::LowShelf => "lowshelf",
+            FilterKind::HighShelf => "highshelf",
+            FilterKind::Peaking => "peak",
+        }
+    }
+}
+
+/// Description of a single filter, independent of sample rate
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(try_from = "String", into = "String"))]
+pub struct FilterSpec {
+    pub kind: FilterKind,
+    /// Cutoff, corner or center frequency in Hz
+    pub frequency: f32,
+    /// Gain in dB for shelving and peaking filters, ignored otherwise
+    pub gain_db: f32,
+    /// Quality factor (or shelf slope as Q)
+    pub q: f32,
+}
+
+impl FilterSpec {
+    pub fn highpass(frequency: f32) -> Self {
+        Self { kind: FilterKind::HighPass, frequency, gain_db: 0.0, q: DEFAULT_Q }
+    }
+
+    pub fn lowpass(frequency: f32) -> Self {
+        Self { kind: FilterKind::LowPass, frequency, gain_db: 0.0, q: DEFAULT_Q }
+    }
+
+    /// Check that frequency and Q are positive and finite and the gain within ±[`MAX_GAIN_DB`]
+    pub fn validate(&self) -> Result<(), String> {
+        let positive = |value: f32| value > 0.0 && value.is_finite();
+        if !(positive(self.frequency) && positive(self.q)) {
+            return Err(format!("frequency and Q must be positive and finite in '{}'", self));
+        }
+        if !(-MAX_GAIN_DB..=MAX_GAIN_DB).contains(&self.gain_db) {
+            return Err(format!("gain must be between -{} and {} dB in '{}'", MAX_GAIN_DB, MAX_GAIN_DB, self));
+        }
+        Ok(())
+    }
+}
+
+/// Parses `<type>:<freq>[:<gain_db>][:<q>]`
+///
+/// Types are `hp`, `lp`, `lowshelf`, `highshelf` and `peak`; gain is required
+/// for shelves and peaking filters only. Examples: `hp:80`, `lp:12000:0.5`,
+/// `lowshelf:200:-3`, `peak:3000:2.5:1.4`.
+impl FromStr for FilterSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split(':').collect();
+        let kind = match parts[0] {
+            "hp" | "highpass" => FilterKind::HighPass,
+            "lp" | "lowpass" => FilterKind::LowPass,
+            "lowshelf" | "ls" => FilterKind::LowShelf,
+            "highshelf" | "hs" => FilterKind::HighShelf,
+            "peak" | "peaking" | "bell" => FilterKind::Peaking,
+            other => return Err(format!("unknown filter type '{}' in '{}'", other, s)),
+        };
+
+        let number = |index: usize, what: &str| -> Result<Option<f32>, String> {
+            parts
+                .get(index)
+                .map(|v| v.parse::<f32>().map_err(|_| format!("invalid {} '{}' in '{}'", what, v, s)))
+                .transpose()
+        };
+
+        let frequency = number(1, "frequency")?.ok_or_else(|| format!("missing frequency in '{}'", s))?;
+        let (gain_db, q_index) = if kind.has_gain() {
+            (number(2, "gain")?.ok_or_else(|| format!("missing gain in '{}'", s))?, 3)
+        } else {
+            (0.0, 2)
+        };
+        let default_q = if kind == FilterKind::Peaking { DEFAULT_PEAKING_Q } else { DEFAULT_Q };
+        let q = number(q_index, "Q")?.unwrap_or(default_q);
+
+        if parts.len() > q_index + 1 {
+            return Err(format!("too many fields in '{}'", s));
+        }
+        let spec = FilterSpec { kind, frequency, gain_db, q };
+        spec.validate()?;
+        Ok(spec)
+    }
+}
+
+impl fmt::Display for FilterSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.kind.has_gain() {
+            write!(f, "{}:{}:{}:{}", self.kind.name(), self.frequency, self.gain_db, self.q)
+        } else {
+            write!(f, "{}:{}:{}", self.kind.name(), self.frequency, self.q)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<String> for FilterSpec {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<FilterSpec> for String {
+    fn from(value: FilterSpec) -> Self {
+        value.to_string()
+    }
+}
+
+/// Second-order IIR section in transposed direct form II
+#[derive(Debug, Clone, Copy)]
+pub struct Biquad {
+    b0: f64,
+    b1: f64,
+    b2: f64,
+    a1: f64,
+    a2: f64,
+    z1: f64,
+    z2: f64,
+}
+
+impl Biquad {
+    /// Build from normalized coefficients (`a0` already divided out)
+    pub fn from_coefficients(b0: f64, b1: f64, b2: f64, a1: f64, a2: f64) -> Self {
+        Biquad { b0, b1, b2, a1, a2, z1: 0.0, z2: 0.0 }
+    }
+
+    /// Design a filter for the given sample rate
+    pub fn new(spec: &FilterSpec, sample_rate: u32) -> Self {
+        let fs = sample_rate as f64;
+        let frequency = (spec.frequency as f64).min(0.49 * fs);
+        let w0 = 2.0 * PI * frequency / fs;
+        let (sin_w0, cos_w0) = w0.sin_cos();
+        let alpha = sin_w0 / (2.0 * spec.q as f64);
+        let a = 10f64.powf(spec.gain_db as f64 / 40.0);
+        let shelf = 2.0 * a.sqrt() * alpha;
+
+        let (b0, b1, b2, a0, a1, a2) = match spec.kind {
+            FilterKind::LowPass => (
+                (1.0 - cos_w0) / 2.0,
+                1.0 - cos_w0,
+                (1.0 - cos_w0) / 2.0,
+                1.0 + alpha,
+                -2.0 * cos_w0,
+                1.0 - alpha,
+            ),
+            FilterKind::HighPass => (
+                (1.0 + cos_w0) / 2.0,
+                -(1.0 + cos_w0),
+                (1.0 + cos_w0) / 2.0,
+                1.0 + alpha,
+                -2.0 * cos_w0,
+                1.0 - alpha,
+            ),
+            FilterKind::Peaking => (
+                1.0 + alpha * a,
+                -2.0 * cos_w0,
+                1.0 - alpha * a,
+                1.0 + alpha / a,
+                -2.0 * cos_w0,
+                1.0 - alpha / a,
+            ),
+            FilterKind::LowShelf => (
+                a * ((a + 1.0) - (a - 1.0) * cos_w0 + shelf),
+                2.0 * a * ((a - 1.0) - (a + 1.0) * cos_w0),
+                a * ((a + 1.0) - (a - 1.0) * cos_w0 - shelf),
+                (a + 1.0) + (a - 1.0) * cos_w0 + shelf,
+                -2.0 * ((a - 1.0) + (a + 1.0) * cos_w0),
+                (a + 1.0) + (a - 1.0) * cos_w0 - shelf,
+            ),
+            FilterKind::HighShelf => (
+                a * ((a + 1.0) + (a - 1.0) * cos_w0 + shelf),
+                -2.0 * a * ((a - 1.0) + (a + 1.0) * cos_w0),
+                a * ((a + 1.0) + (a - 1.0) * cos_w0 - shelf),
+                (a + 1.0) - (a - 1.0) * cos_w0 + shelf,
+                2.0 * ((a - 1.0) - (a + 1.0) * cos_w0),
+                (a + 1.0) - (a - 1.0) * cos_w0 - shelf,
+            ),
+        };
+
+        Self::from_coefficients(b0 / a0, b1 / a0, b2 / a0, a1 / a0, a2 / a0)
+    }
+
+    /// Filter one sample
+    pub fn tick(&mut self, x: f64) -> f64 {
+        let y = self.b0 * x + self.z1;
+        self.z1 = self.b1 * x - self.a1 * y + self.z2;
+        self.z2 = self.b2 * x - self.a2 * y;
+        y
+    }
+
+    /// Filter a buffer in place
+    pub fn process(&mut self, samples: &mut [f32]) {
+        for sample in samples {
+            *sample = self.tick(*sample as f64) as f32;
+        }
+    }
+
+    /// Clear the filter state
+    pub fn reset(&mut self) {
+        self.z1 = 0.0;
+        self.z2 = 0.0;
+    }
+}
+
+/// Chain of biquads applied in order
+#[derive(Debug, Clone)]
+pub struct FilterBank {
+    sample_rate: u32,
+    filters: Vec<Biquad>,
+}
+
+impl FilterBank {
+    /// Create an empty filter bank
+    pub fn new(sample_rate: u32) -> Self {
+        FilterBank { sample_rate, filters: Vec::new() }
+    }
+
+    /// Create a filter bank from a list of specs
+    pub fn from_specs(specs: &[FilterSpec], sample_rate: u32) -> Self {
+        specs.iter().fold(Self::new(sample_rate), |bank, spec| bank.with(spec))
+    }
+
+    /// Append a filter described by `spec`
+    pub fn with(mut self, spec: &FilterSpec) -> Self {
+        self.filters.push(Biquad::new(spec, self.sample_rate));
+        self
+    }
+
+    pub fn highpass(self, frequency: f32, q: f32) -> Self {
+        self.with(&FilterSpec { q, ..FilterSpec::highpass(frequency) })
+    }
+
+    pub fn lowpass(self, frequency: f32, q: f32) -> Self {
+        self.with(&FilterSpec { q, ..FilterSpec::lowpass(frequency) })
+    }
+
+    pub fn low_shelf(self, frequency: f32, gain_db: f32, q: f32) -> Self {
+        self.with(&FilterSpec { kind: FilterKind::LowShelf, frequency, gain_db, q })
+    }
+
+    pub fn high_shelf(self, frequency: f32, gain_db: f32, q: f32) -> Self {
+        self.with(&FilterSpec { kind: FilterKind::HighShelf, frequency, gain_db, q })
+    }
+
+    pub fn peaking(self, frequency: f32, gain_db: f32, q: f32) -> Self {
+        self.with(&FilterSpec { kind: FilterKind::Peaking, frequency, gain_db, q })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+
+    /// Run every filter over the buffer in place
+    pub fn process(&mut self, samples: &mut [f32]) {
+        for filter in &mut self.filters {
+            filter.process(samples);
+        }
+    }
+
+    /// Clear the state of every filter
+    pub fn reset(&mut self) {
+        for filter in &mut self.filters {
+            filter.reset();
+        }
+    }
+}
+
+/// Subtract the mean so the signal is centered on zero
+pub fn remove_dc_offset(samples: &mut [f32]) {
+    if samples.is_empty() {
+        return;
+    }
+    let mean = samples.iter().map(|&s| s as f64).sum::<f64>() / samples.len() as f64;
+    for sample in samples {
+        *sample -= mean as f32;
+    }
+}
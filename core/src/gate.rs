@@ -0,0 +1,363 @@
+//! # Noise Gate / Downward Expander
+//!
+//! Attenuates the low-level residual that spectral subtraction leaves between
+//! phrases (the `spectral_floor` hiss). Runs either broadband on the
+//! time-domain output or per frequency band inside the STFT loop, keyed off
+//! the signal level, the estimated SNR against the noise profile, or a simple
+//! voice activity detector.
+
+use num_complex::Complex;
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+/// Upper edges of the per-band gate bands in Hz; the last band runs to Nyquist
+const BAND_EDGES_HZ: [f32; 7] = [150.0, 300.0, 600.0, 1200.0, 2400.0, 4800.0, 9600.0];
+/// RMS window of the broadband level detector in milliseconds
+const LEVEL_WINDOW_MS: f32 = 10.0;
+/// Frequency range used by the voice activity detector in Hz
+const VOICE_BAND_HZ: (f32, f32) = (300.0, 3400.0);
+/// Speech-band SNR above which a frame counts as voiced, in dB
+const VOICE_SNR_DB: f32 = 6.0;
+
+/// Where the gate is applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "kebab-case"))]
+pub enum GateMode {
+    /// Single gain for the whole signal, applied to the time-domain output
+    Broadband,
+    /// Independent gain per octave band, applied inside the STFT loop
+    PerBand,
+}
+
+/// What opens the gate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "kebab-case"))]
+pub enum GateKey {
+    /// Signal level in dBFS after noise reduction
+    Level,
+    /// Estimated SNR in dB of the input against the noise profile
+    Snr,
+    /// Voice activity: open on voiced frames, fully attenuate otherwise
+    Vad,
+}
+
+impl FromStr for GateMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "broadband" => Ok(GateMode::Broadband),
+            "per-band" => Ok(GateMode::PerBand),
+            _ => Err(format!("unknown gate mode '{}' (expected broadband or per-band)", s)),
+        }
+    }
+}
+
+impl FromStr for GateKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "level" => Ok(GateKey::Level),
+            "snr" => Ok(GateKey::Snr),
+            "vad" => Ok(GateKey::Vad),
+            _ => Err(format!("unknown gate key '{}' (expected level, snr or vad)", s)),
+        }
+    }
+}
+
+impl fmt::Display for GateMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            GateMode::Broadband => "broadband",
+            GateMode::PerBand => "per-band",
+        })
+    }
+}
+
+impl fmt::Display for GateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            GateKey::Level => "level",
+            GateKey::Snr => "snr",
+            GateKey::Vad => "vad",
+        })
+    }
+}
+
+/// Configuration for the gate / downward expander
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default, deny_unknown_fields))]
+pub struct GateConfig {
+    /// Threshold in dB: dBFS for the level key, dB SNR for the snr key (default: -50.0)
+    pub threshold_db: f32,
+    /// Expansion ratio below threshold, `f32::INFINITY` for a hard gate (default: 4.0)
+    #[cfg_attr(feature = "serde", serde(with = "ratio"))]
+    pub ratio: f32,
+    /// Maximum attenuation in dB, negative (default: -30.0)
+    pub range_db: f32,
+    /// Time to open once the key rises above threshold, in milliseconds (default: 5.0)
+    pub attack_ms: f32,
+    /// Time to stay open after the key falls below threshold, in milliseconds (default: 50.0)
+    pub hold_ms: f32,
+    /// Time to close after the hold expires, in milliseconds (default: 150.0)
+    pub release_ms: f32,
+    /// Broadband or per-band operation (default: Broadband)
+    pub mode: GateMode,
+    /// Detector driving the gate (default: Level)
+    pub key: GateKey,
+}
+
+impl Default for GateConfig {
+    fn default() -> Self {
+        Self {
+            threshold_db: -50.0,
+            ratio: 4.0,
+            range_db: -30.0,
+            attack_ms: 5.0,
+            hold_ms: 50.0,
+            release_ms: 150.0,
+            mode: GateMode::Broadband,
+            key: GateKey::Level,
+        }
+    }
+}
+
+impl GateConfig {
+    /// Static expander curve: gain in dB (at most 0) for a detector level in dB
+    fn gain_db(&self, level_db: f32) -> f32 {
+        if level_db >= self.threshold_db {
+            0.0
+        } else {
+            ((level_db - self.threshold_db) * (self.ratio.max(1.0) - 1.0)).max(self.range_db.min(0.0))
+        }
+    }
+}
+
+/// Detector values measured on each STFT frame before subtraction
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct FrameKey {
+    pub snr_db: f32,
+    pub voice: bool,
+}
+
+impl FrameKey {
+    /// Measure frame SNR and voice activity from input and noise magnitudes
+    pub(crate) fn measure(magnitudes: &[f32], noise_spectrum: &[f32], sample_rate: u32) -> Self {
+        let half = magnitudes.len() / 2;
+        let bin_hz = sample_rate as f32 / magnitudes.len() as f32;
+        let voice_bins = ((VOICE_BAND_HZ.0 / bin_hz) as usize).max(1)..((VOICE_BAND_HZ.1 / bin_hz) as usize).min(half);
+
+        FrameKey {
+            snr_db: snr_db(magnitudes, noise_spectrum, 1..half),
+            voice: snr_db(magnitudes, noise_spectrum, voice_bins) > VOICE_SNR_DB,
+        }
+    }
+}
+
+fn power_db(power: f32) -> f32 {
+    10.0 * power.max(1e-20).log10()
+}
+
+fn snr_db(magnitudes: &[f32], noise_spectrum: &[f32], bins: Range<usize>) -> f32 {
+    let signal: f32 = magnitudes[bins.clone()].iter().map(|m| m * m).sum();
+    let noise: f32 = noise_spectrum[bins].iter().map(|m| m * m).sum();
+    power_db(signal) - power_db(noise)
+}
+
+/// Smoothed gain with attack, hold and release, stepped once per detector update
+#[derive(Debug, Clone)]
+struct GainEnvelope {
+    gain_db: f32,
+    hold_remaining: usize,
+    hold_steps: usize,
+    attack: f32,
+    release: f32,
+}
+
+impl GainEnvelope {
+    fn new(config: &GateConfig, step_seconds: f32) -> Self {
+        let coefficient = |ms: f32| {
+            if ms > 0.0 {
+                (-step_seconds / (ms / 1000.0)).exp()
+            } else {
+                0.0
+            }
+        };
+
+        GainEnvelope {
+            gain_db: 0.0,
+            hold_remaining: 0,
+            hold_steps: (config.hold_ms.max(0.0) / 1000.0 / step_seconds) as usize,
+            attack: coefficient(config.attack_ms),
+            release: coefficient(config.release_ms),
+        }
+    }
+
+    fn next(&mut self, target_db: f32) -> f32 {
+        if target_db >= self.gain_db {
+            self.hold_remaining = self.hold_steps;
+            self.gain_db = target_db + (self.gain_db - target_db) * self.attack;
+        } else if self.hold_remaining > 0 {
+            self.hold_remaining -= 1;
+        } else {
+            self.gain_db = target_db + (self.gain_db - target_db) * self.release;
+        }
+        self.gain_db
+    }
+}
+
+fn db_to_gain(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+/// Per-band gate state carried across STFT frames
+pub(crate) struct BandGate {
+    config: GateConfig,
+    bands: Vec<Range<usize>>,
+    envelopes: Vec<GainEnvelope>,
+    window_energy: f32,
+}
+
+impl BandGate {
+    pub(crate) fn new(config: &GateConfig, sample_rate: u32, window: &[f32], hop_size: usize) -> Self {
+        let frame_size = window.len();
+        let half = frame_size / 2;
+        let bin_hz = sample_rate as f32 / frame_size as f32;
+
+        let mut bands = Vec::new();
+        let mut start = 0;
+        for edge in BAND_EDGES_HZ {
+            let end = ((edge / bin_hz).round() as usize).min(half + 1);
+            if end > start {
+                bands.push(start..end);
+                start = end;
+            }
+        }
+        if start < half + 1 {
+            bands.push(start..half + 1);
+        }
+
+        let step_seconds = hop_size as f32 / sample_rate as f32;
+        BandGate {
+            config: *config,
+            envelopes: vec![GainEnvelope::new(config, step_seconds); bands.len()],
+            bands,
+            window_energy: window.iter().map(|w| w * w).sum(),
+        }
+    }
+
+    /// Apply band gains to a full (two-sided) spectrum after subtraction
+    pub(crate) fn process_frame(
+        &mut self,
+        spectrum: &mut [Complex<f32>],
+        input_magnitudes: &[f32],
+        noise_spectrum: &[f32],
+        key: &FrameKey,
+    ) {
+        let frame_size = spectrum.len();
+        for (band, envelope) in self.bands.iter().zip(self.envelopes.iter_mut()) {
+            let target_db = match self.config.key {
+                GateKey::Level => {
+                    let power: f32 = spectrum[band.clone()].iter().map(|c| c.norm_sqr()).sum();
+                    let level_db = power_db(2.0 * power / (frame_size as f32 * self.window_energy));
+                    self.config.gain_db(level_db)
+                }
+                GateKey::Snr => self.config.gain_db(snr_db(input_magnitudes, noise_spectrum, band.clone())),
+                GateKey::Vad => vad_gain_db(&self.config, key.voice),
+            };
+
+            let gain = db_to_gain(envelope.next(target_db));
+            for bin in band.clone() {
+                spectrum[bin] *= gain;
+                if bin > 0 && bin < frame_size - bin {
+                    spectrum[frame_size - bin] *= gain;
+                }
+            }
+        }
+    }
+}
+
+fn vad_gain_db(config: &GateConfig, voice: bool) -> f32 {
+    if voice {
+        0.0
+    } else {
+        config.range_db.min(0.0)
+    }
+}
+
+/// Per-sample broadband gate gains for the time-domain output
+///
+/// `samples` drives the level key; for linked channels pass a combined level
+/// signal. `keys` holds one entry per STFT frame starting at multiples of `hop_size`.
+pub(crate) fn broadband_gains(
+    samples: &[f32],
+    keys: &[FrameKey],
+    config: &GateConfig,
+    sample_rate: u32,
+    frame_size: usize,
+    hop_size: usize,
+) -> Vec<f32> {
+    if samples.is_empty() || keys.is_empty() {
+        return vec![1.0; samples.len()];
+    }
+
+    let half_window = ((LEVEL_WINDOW_MS / 1000.0 * sample_rate as f32) as usize / 2).max(1);
+    let mut cumulative = Vec::with_capacity(samples.len() + 1);
+    let mut sum = 0.0f64;
+    cumulative.push(sum);
+    for &sample in samples.iter() {
+        sum += (sample as f64) * (sample as f64);
+        cumulative.push(sum);
+    }
+
+    let mut envelope = GainEnvelope::new(config, 1.0 / sample_rate as f32);
+    let mut gains = Vec::with_capacity(samples.len());
+    for i in 0..samples.len() {
+        let frame = (i.saturating_sub(frame_size / 2) + hop_size / 2) / hop_size;
+        let key = &keys[frame.min(keys.len() - 1)];
+
+        let target_db = match config.key {
+            GateKey::Level => {
+                let start = i.saturating_sub(half_window);
+                let end = (i + half_window).min(cumulative.len() - 1);
+                let mean_square = (cumulative[end] - cumulative[start]) / (end - start) as f64;
+                config.gain_db(power_db(mean_square as f32))
+            }
+            GateKey::Snr => config.gain_db(key.snr_db),
+            GateKey::Vad => vad_gain_db(config, key.voice),
+        };
+
+        gains.push(db_to_gain(envelope.next(target_db)));
+    }
+    gains
+}
+
+/// Gate ratio as a number, or "inf" for a hard gate, since JSON has no infinity
+#[cfg(feature = "serde")]
+mod ratio {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Ratio {
+        Number(f32),
+        Text(String),
+    }
+
+    pub fn serialize<S: Serializer>(ratio: &f32, serializer: S) -> Result<S::Ok, S::Error> {
+        if ratio.is_infinite() {
+            serializer.serialize_str("inf")
+        } else {
+            serializer.serialize_f32(*ratio)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+        match Ratio::deserialize(deserializer)? {
+            Ratio::Number(ratio) => Ok(ratio),
+            Ratio::Text(text) => text.parse().map_err(|_| serde::de::Error::custom(format!("invalid gate ratio '{}'", text))),
+        }
+    }
+}
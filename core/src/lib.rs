@@ -14,42 +14,350 @@
 //! let mut processor = AudioProcessor::new(2048);
 //!
 //! // Process audio samples (mono, f32, -1.0 to 1.0)
+//! # let input_samples = vec![0.0f32; 44100];
 //! let output = processor.process(&input_samples, &config);
 //! ```
 
+pub mod aec;
+pub mod analysis;
+pub mod anc;
+pub mod beamform;
+pub mod dereverb;
+pub mod filter;
+pub mod gate;
+pub mod harmonic;
+pub mod limiter;
+pub mod loudness;
+pub mod metrics;
+pub mod multichannel;
+pub mod noise_tracking;
+pub mod preset;
+pub mod profile;
+pub mod residual;
+pub mod spectral_gate;
+#[cfg(feature = "testsignal")]
+pub mod testsignal;
+
+pub use aec::AecConfig;
+pub use analysis::{AnalysisReport, AutoSettings, SignalAnalysis};
+pub use anc::{AncConfig, AncMethod};
+pub use beamform::{BeamformConfig, BeamformMethod, Steering};
+pub use dereverb::DereverbConfig;
+pub use filter::{FilterBank, FilterKind, FilterSpec};
+pub use gate::{GateConfig, GateKey, GateMode};
+pub use harmonic::HarmonicConfig;
+pub use limiter::LimiterConfig;
+pub use loudness::{LoudnessStats, LoudnessTarget};
+pub use multichannel::{ChannelMode, LinkSource};
+pub use noise_tracking::{NoiseTrackingConfig, TrackingMethod};
+pub use preset::{Preset, PresetDefinition, PresetRegistry};
+pub use profile::NoiseProfile;
+pub use spectral_gate::SpectralGateConfig;
+
 use num_complex::Complex;
 use rustfft::{Fft, FftPlanner};
 use std::f32::consts::PI;
+use std::fmt;
+use std::str::FromStr;
 
+/// Default STFT frame length in samples, and the one noise profiles are learned at
 pub const FRAME_SIZE: usize = 2048;
-const HOP_SIZE: usize = 1024;
+/// Samples between frames at the default frame size
+const HOP_SIZE: usize = FRAME_SIZE / 2;
+/// Shortest frame [`AudioProcessor::new`] accepts
+pub const MIN_FRAME_SIZE: usize = 64;
+/// Smallest overlap-add window sum divided by; the Hann frames sum to 1.0 away from the edges
+const WINDOW_SUM_FLOOR: f32 = 0.5;
+/// Usable ranges of the spectral subtraction parameters: [`NoiseReductionConfig::validate`]
+/// rejects values outside them and processing clamps to them
+const SPECTRAL_FLOOR_RANGE: std::ops::RangeInclusive<f32> = 0.0..=1.0;
+const OVER_SUBTRACTION_RANGE: std::ops::RangeInclusive<f32> = 0.0..=100.0;
+const MAKEUP_GAIN_RANGE: std::ops::RangeInclusive<f32> = 0.0..=100.0;
+
+/// Noise reduction algorithm applied in the STFT loop
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "kebab-case"))]
+pub enum Algorithm {
+    /// Magnitude spectral subtraction driven by `over_subtraction` and `spectral_floor`
+    #[default]
+    #[cfg_attr(feature = "serde", serde(rename = "subtraction"))]
+    SpectralSubtraction,
+    /// noisereduce-style spectral gating with smoothed masks
+    SpectralGate(SpectralGateConfig),
+}
+
+/// Parses `subtraction`, `spectral-gate` or `spectral-gate-nonstationary` with default settings
+impl FromStr for Algorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "subtraction" => Ok(Algorithm::SpectralSubtraction),
+            "spectral-gate" => Ok(Algorithm::SpectralGate(SpectralGateConfig::default())),
+            "spectral-gate-nonstationary" => Ok(Algorithm::SpectralGate(SpectralGateConfig::non_stationary())),
+            _ => Err(format!(
+                "unknown algorithm '{}' (expected subtraction, spectral-gate or spectral-gate-nonstationary)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Algorithm::SpectralSubtraction => "subtraction",
+            Algorithm::SpectralGate(gating) if gating.stationary => "spectral-gate",
+            Algorithm::SpectralGate(_) => "spectral-gate-nonstationary",
+        })
+    }
+}
 
 /// Configuration for noise reduction processing
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default, deny_unknown_fields))]
 pub struct NoiseReductionConfig {
+    /// Algorithm used to suppress noise in each frame (default: SpectralSubtraction)
+    pub algorithm: Algorithm,
     /// Number of frames to use for noise profile estimation (default: 10)
     pub noise_frames: usize,
+    /// Noise spectrum learned from a separate noise-only recording, used instead of the
+    /// first `noise_frames` frames by spectral subtraction; see [`profile`] (default: None)
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub noise_profile: Option<NoiseProfile>,
+    /// Re-estimate the noise at every frame from a trailing window instead of keeping
+    /// the `noise_frames` profile fixed (default: None)
+    pub noise_tracking: Option<NoiseTrackingConfig>,
     /// Spectral floor value 0.0-1.0, higher preserves more signal (default: 0.1)
     pub spectral_floor: f32,
     /// Over-subtraction factor, higher = more aggressive (default: 2.0)
     pub over_subtraction: f32,
     /// Output gain multiplier to compensate for volume loss (default: 1.5)
     pub makeup_gain: f32,
+    /// Sample rate of the input in Hz, used by time- and loudness-based stages (default: 44100)
+    pub sample_rate: u32,
+    /// How channels are combined in [`AudioProcessor::process_channels`] (default: Linked(Max))
+    pub channel_mode: ChannelMode,
+    /// Beamform multichannel input to one channel before noise reduction; replaces
+    /// `channel_mode` (default: None)
+    pub beamforming: Option<BeamformConfig>,
+    /// Echo canceller settings, used when a far-end signal is given to
+    /// [`AudioProcessor::process_with_far_end`] (default: AecConfig::default())
+    pub echo_cancellation: AecConfig,
+    /// Subtract the DC offset before processing (default: true)
+    pub remove_dc: bool,
+    /// Filters applied before noise estimation, e.g. a rumble high-pass (default: empty)
+    pub pre_filters: Vec<FilterSpec>,
+    /// Filters applied to the output after spectral subtraction (default: empty)
+    pub post_filters: Vec<FilterSpec>,
+    /// Normalize output to a target loudness instead of applying `makeup_gain` (default: None)
+    pub loudness: Option<LoudnessTarget>,
+    /// Rebuild speech harmonics lost to aggressive subtraction (default: None)
+    pub harmonic_regeneration: Option<HarmonicConfig>,
+    /// Late-reverberation suppression inside the STFT loop (default: None)
+    pub dereverb: Option<DereverbConfig>,
+    /// Noise gate / downward expander run after spectral subtraction (default: None)
+    pub gate: Option<GateConfig>,
+    /// Look-ahead true-peak limiter applied as the final stage (default: None)
+    pub limiter: Option<LimiterConfig>,
 }
 
 impl Default for NoiseReductionConfig {
     fn default() -> Self {
         Self {
+            algorithm: Algorithm::SpectralSubtraction,
             noise_frames: 10,
+            noise_profile: None,
+            noise_tracking: None,
             spectral_floor: 0.1,
             over_subtraction: 2.0,
             makeup_gain: 1.5,
+            sample_rate: 44100,
+            channel_mode: ChannelMode::Linked(LinkSource::Max),
+            beamforming: None,
+            echo_cancellation: AecConfig::default(),
+            remove_dc: true,
+            pre_filters: Vec::new(),
+            post_filters: Vec::new(),
+            loudness: None,
+            harmonic_regeneration: None,
+            dereverb: None,
+            gate: None,
+            limiter: None,
+        }
+    }
+}
+
+impl NoiseReductionConfig {
+    /// Check that every value is in its usable range
+    ///
+    /// Front-ends call this on configurations loaded from files or JSON,
+    /// where the per-flag parsing does not apply. The error names the
+    /// offending field.
+    pub fn validate(&self) -> Result<(), String> {
+        fn check(name: &str, value: f32, range: std::ops::RangeInclusive<f32>) -> Result<(), String> {
+            if range.contains(&value) {
+                Ok(())
+            } else {
+                Err(format!("{} must be between {} and {}, got {}", name, range.start(), range.end(), value))
+            }
+        }
+        fn positive(name: &str, value: f32) -> Result<(), String> {
+            if value > 0.0 && value.is_finite() {
+                Ok(())
+            } else {
+                Err(format!("{} must be positive and finite, got {}", name, value))
+            }
+        }
+
+        if self.noise_frames == 0 {
+            return Err("noise_frames must be at least 1".to_string());
+        }
+        if self.sample_rate == 0 {
+            return Err("sample_rate must be positive".to_string());
+        }
+        if let Some(profile) = &self.noise_profile {
+            if profile.magnitudes.len() != FRAME_SIZE / 2 + 1 {
+                return Err(format!("noise_profile must have {} bins, got {}", FRAME_SIZE / 2 + 1, profile.magnitudes.len()));
+            }
+            if profile.magnitudes.iter().any(|magnitude| !(magnitude.is_finite() && *magnitude >= 0.0)) {
+                return Err("noise_profile magnitudes must be finite and non-negative".to_string());
+            }
+        }
+        for filter in self.pre_filters.iter().chain(&self.post_filters) {
+            filter.validate()?;
+        }
+        check("spectral_floor", self.spectral_floor, SPECTRAL_FLOOR_RANGE)?;
+        check("over_subtraction", self.over_subtraction, OVER_SUBTRACTION_RANGE)?;
+        check("makeup_gain", self.makeup_gain, MAKEUP_GAIN_RANGE)?;
+        check("echo_cancellation.step_size", self.echo_cancellation.step_size, 0.0..=1.0)?;
+        check("echo_cancellation.tail_ms", self.echo_cancellation.tail_ms, 0.0..=10_000.0)?;
+        check("echo_cancellation.double_talk_threshold", self.echo_cancellation.double_talk_threshold, 0.0..=10.0)?;
+        check("echo_cancellation.residual_suppression", self.echo_cancellation.residual_suppression, 0.0..=10.0)?;
+
+        if let Algorithm::SpectralGate(gating) = &self.algorithm {
+            check("algorithm.n_std_thresh", gating.n_std_thresh, 0.0..=10.0)?;
+            check("algorithm.prop_decrease", gating.prop_decrease, 0.0..=1.0)?;
+            check("algorithm.freq_mask_smooth_hz", gating.freq_mask_smooth_hz, 0.0..=20_000.0)?;
+            check("algorithm.time_mask_smooth_ms", gating.time_mask_smooth_ms, 0.0..=10_000.0)?;
+            positive("algorithm.time_constant_s", gating.time_constant_s)?;
+            check("algorithm.thresh_n_mult_nonstationary", gating.thresh_n_mult_nonstationary, 0.0..=100.0)?;
+            positive("algorithm.sigmoid_slope_nonstationary", gating.sigmoid_slope_nonstationary)?;
+        }
+        if let Some(tracking) = &self.noise_tracking {
+            check("noise_tracking.window_s", tracking.window_s, 0.01..=60.0)?;
+            check("noise_tracking.percentile", tracking.percentile, 1.0..=99.0)?;
+            check("noise_tracking.smoothing", tracking.smoothing, 0.0..=1.0)?;
+        }
+        if let Some(beamforming) = &self.beamforming {
+            if beamforming.noise_frames == 0 {
+                return Err("beamforming.noise_frames must be at least 1".to_string());
+            }
+            check("beamforming.max_delay_ms", beamforming.max_delay_ms, 0.0..=100.0)?;
+            check("beamforming.diagonal_loading", beamforming.diagonal_loading, 0.0..=100.0)?;
+        }
+        if let Some(harmonic) = &self.harmonic_regeneration {
+            check("harmonic_regeneration.mix", harmonic.mix, 0.0..=1.0)?;
+        }
+        if let Some(dereverb) = &self.dereverb {
+            check("dereverb.late_onset_ms", dereverb.late_onset_ms, 0.0..=1000.0)?;
+            check("dereverb.strength", dereverb.strength, 0.0..=10.0)?;
+            check("dereverb.floor", dereverb.floor, 0.0..=1.0)?;
+            if let Some(t60) = dereverb.t60 {
+                check("dereverb.t60", t60, 0.01..=20.0)?;
+            }
+        }
+        if let Some(gate) = &self.gate {
+            if gate.ratio.is_nan() || gate.ratio < 1.0 {
+                return Err(format!("gate.ratio must be at least 1, got {}", gate.ratio));
+            }
+            check("gate.threshold_db", gate.threshold_db, -200.0..=200.0)?;
+            check("gate.range_db", gate.range_db, -200.0..=0.0)?;
+            check("gate.attack_ms", gate.attack_ms, 0.0..=10_000.0)?;
+            check("gate.hold_ms", gate.hold_ms, 0.0..=10_000.0)?;
+            check("gate.release_ms", gate.release_ms, 0.0..=10_000.0)?;
         }
+        if let Some(loudness) = &self.loudness {
+            check("loudness.integrated_lufs", loudness.integrated_lufs, -70.0..=0.0)?;
+            check("loudness.true_peak_ceiling", loudness.true_peak_ceiling, -60.0..=0.0)?;
+        }
+        if let Some(limiter) = &self.limiter {
+            check("limiter.ceiling", limiter.ceiling, -60.0..=0.0)?;
+            check("limiter.release_ms", limiter.release_ms, 0.0..=10_000.0)?;
+            check("limiter.lookahead_ms", limiter.lookahead_ms, 0.0..=1000.0)?;
+        }
+        Ok(())
+    }
+
+    /// Copy that processing cannot turn into NaN: at least one noise frame, a positive
+    /// sample rate, and the spectral subtraction parameters clamped to the ranges
+    /// [`validate`](Self::validate) accepts, NaN replaced by the default. Filters that
+    /// `validate` rejects are dropped, and a rejected noise tracking, harmonic, dereverb,
+    /// gate, loudness or limiter stage runs with that stage's default settings.
+    fn sanitized(&self) -> Self {
+        fn or_default<T: Clone + Default>(stage: &Option<T>, with: impl Fn(T) -> NoiseReductionConfig) -> Option<T> {
+            stage.clone().map(|stage| if with(stage.clone()).validate().is_ok() { stage } else { T::default() })
+        }
+
+        let defaults = Self::default();
+        let clamp = |value: f32, range: std::ops::RangeInclusive<f32>, default: f32| {
+            if value.is_nan() {
+                default
+            } else {
+                value.clamp(*range.start(), *range.end())
+            }
+        };
+        Self {
+            noise_frames: self.noise_frames.max(1),
+            sample_rate: if self.sample_rate == 0 { defaults.sample_rate } else { self.sample_rate },
+            spectral_floor: clamp(self.spectral_floor, SPECTRAL_FLOOR_RANGE, defaults.spectral_floor),
+            over_subtraction: clamp(self.over_subtraction, OVER_SUBTRACTION_RANGE, defaults.over_subtraction),
+            makeup_gain: clamp(self.makeup_gain, MAKEUP_GAIN_RANGE, defaults.makeup_gain),
+            pre_filters: self.pre_filters.iter().filter(|filter| filter.validate().is_ok()).cloned().collect(),
+            post_filters: self.post_filters.iter().filter(|filter| filter.validate().is_ok()).cloned().collect(),
+            noise_tracking: or_default(&self.noise_tracking, |noise_tracking| Self { noise_tracking: Some(noise_tracking), ..Self::default() }),
+            harmonic_regeneration: or_default(&self.harmonic_regeneration, |harmonic| Self { harmonic_regeneration: Some(harmonic), ..Self::default() }),
+            dereverb: or_default(&self.dereverb, |dereverb| Self { dereverb: Some(dereverb), ..Self::default() }),
+            gate: or_default(&self.gate, |gate| Self { gate: Some(gate), ..Self::default() }),
+            loudness: or_default(&self.loudness, |loudness| Self { loudness: Some(loudness), ..Self::default() }),
+            limiter: or_default(&self.limiter, |limiter| Self { limiter: Some(limiter), ..Self::default() }),
+            ..self.clone()
+        }
+    }
+}
+
+/// Hann window of `frame_size` samples, the analysis window of every STFT frame
+fn hann_window(frame_size: usize) -> Vec<f32> {
+    (0..frame_size)
+        .map(|i| 0.5 * (1.0 - (2.0 * PI * i as f32 / (frame_size - 1) as f32).cos()))
+        .collect()
+}
+
+/// Length a signal of `len >= frame_size` samples is zero-padded to so every sample lies
+/// under two frames; the tail after the last full frame is lost otherwise
+fn padded_len(len: usize, frame_size: usize, hop_size: usize) -> usize {
+    len + (hop_size - (len - frame_size) % hop_size) % hop_size + hop_size
+}
+
+/// Normalize an overlap-added signal by its window sum
+///
+/// The floor stops the first samples, where the sum tends to zero, from
+/// amplifying what the gains spread there into huge spikes.
+fn normalize_by_window_sum(output: &mut [f32], window_sum: &[f32]) {
+    for (sample, ws) in output.iter_mut().zip(window_sum) {
+        *sample /= ws.max(WINDOW_SUM_FLOOR);
     }
 }
 
+/// Samples with NaN and infinities replaced by silence, so one bad sample cannot poison whole frames
+fn finite_samples(samples: &[f32]) -> Vec<f32> {
+    samples.iter().map(|&s| if s.is_finite() { s } else { 0.0 }).collect()
+}
+
 /// Audio processor for FFT-based noise reduction
 pub struct AudioProcessor {
+    frame_size: usize,
+    hop_size: usize,
     fft: std::sync::Arc<dyn Fft<f32>>,
     ifft: std::sync::Arc<dyn Fft<f32>>,
     fft_scratch: Vec<Complex<f32>>,
@@ -61,16 +369,49 @@ struct NoiseReductionParams {
     config: NoiseReductionConfig,
 }
 
+/// Optional per-frame stages that carry state across STFT frames
+struct FrameStages {
+    noise_tracker: Option<noise_tracking::NoiseTracker>,
+    dereverb: Option<dereverb::Dereverb>,
+    band_gate: Option<gate::BandGate>,
+    /// One echo canceller per channel, empty without a far-end signal
+    echo_cancellers: Vec<aec::EchoCanceller>,
+}
+
 impl AudioProcessor {
     /// Create a new audio processor with specified FFT size
+    ///
+    /// Frames are `frame_size` samples long and half a frame apart. Longer
+    /// frames resolve frequency more finely at the cost of latency and time
+    /// smearing; [`FRAME_SIZE`] is the default every front-end uses.
+    ///
+    /// # Panics
+    ///
+    /// If `frame_size` is odd or smaller than [`MIN_FRAME_SIZE`].
     pub fn new(frame_size: usize) -> Self {
+        assert!(
+            frame_size >= MIN_FRAME_SIZE && frame_size.is_multiple_of(2),
+            "frame size must be even and at least {}, got {}",
+            MIN_FRAME_SIZE,
+            frame_size
+        );
         let mut planner = FftPlanner::<f32>::new();
         let fft = planner.plan_fft_forward(frame_size);
         let ifft = planner.plan_fft_inverse(frame_size);
         let fft_scratch = vec![Complex::new(0.0, 0.0); fft.get_inplace_scratch_len()];
         let ifft_scratch = vec![Complex::new(0.0, 0.0); ifft.get_inplace_scratch_len()];
 
-        AudioProcessor { fft, ifft, fft_scratch, ifft_scratch }
+        AudioProcessor { frame_size, hop_size: frame_size / 2, fft, ifft, fft_scratch, ifft_scratch }
+    }
+
+    /// Length of every STFT frame in samples
+    pub fn frame_size(&self) -> usize {
+        self.frame_size
+    }
+
+    /// Samples between the starts of consecutive frames, half the frame size
+    pub fn hop_size(&self) -> usize {
+        self.hop_size
     }
 
     fn apply_hann_window(&self, frame: &mut [f32]) {
@@ -92,6 +433,23 @@ impl AudioProcessor {
         spectrum.iter().map(|c| c.re / spectrum.len() as f32).collect()
     }
 
+    /// Magnitudes of bins 0..=frame_size/2 for every Hann-windowed frame of `samples`
+    ///
+    /// Frames are [`frame_size`](Self::frame_size) long and [`hop_size`](Self::hop_size)
+    /// apart, the framing the noise reduction itself uses. Empty when `samples` is
+    /// shorter than one frame.
+    pub fn stft_magnitudes(&mut self, samples: &[f32]) -> Vec<Vec<f32>> {
+        let (frame_size, hop_size) = (self.frame_size, self.hop_size);
+        let frames = (samples.len() + hop_size).saturating_sub(frame_size) / hop_size;
+        (0..frames)
+            .map(|index| {
+                let mut frame = Vec::from(&samples[index * hop_size..index * hop_size + frame_size]);
+                self.apply_hann_window(&mut frame);
+                self.fft_forward(&frame)[..=frame_size / 2].iter().map(|bin| bin.norm()).collect()
+            })
+            .collect()
+    }
+
     /// Process audio samples with noise reduction
     ///
     /// # Arguments
@@ -101,66 +459,320 @@ impl AudioProcessor {
     ///
     /// # Returns
     ///
-    /// Processed audio samples
+    /// Processed audio samples, as many as the input. NaN and infinite input
+    /// samples are processed as silence, and `noise_frames`, `sample_rate`,
+    /// `spectral_floor`, `over_subtraction` and `makeup_gain` are forced into
+    /// the ranges [`NoiseReductionConfig::validate`] accepts. Invalid filters
+    /// are skipped and invalid stage settings replaced by the stage defaults.
+    /// Inputs shorter than one frame are returned unprocessed.
     pub fn process(&mut self, samples: &[f32], config: &NoiseReductionConfig) -> Vec<f32> {
-        if samples.len() < FRAME_SIZE {
-            return samples.to_vec();
+        let samples = finite_samples(samples);
+        if samples.len() < self.frame_size {
+            return samples;
         }
 
-        let noise_spectrum = self.estimate_noise_spectrum(samples, config.noise_frames);
+        let config = config.sanitized();
+        let mut output = self.reduce(&[samples], None, LinkSource::Max, &config);
+        Self::apply_output_stages(&mut output, &config);
+        output.remove(0)
+    }
+
+    /// Process a mono microphone signal with acoustic echo cancellation
+    ///
+    /// `far_end` is the signal played back on the loudspeaker (the remote side
+    /// of a call), time-aligned with `samples`; it is zero-padded or truncated
+    /// to their length. The echo is cancelled inside the STFT loop before noise
+    /// reduction, configured by `config.echo_cancellation`; see the [`aec`] module.
+    pub fn process_with_far_end(&mut self, samples: &[f32], far_end: &[f32], config: &NoiseReductionConfig) -> Vec<f32> {
+        let samples = finite_samples(samples);
+        if samples.len() < self.frame_size {
+            return samples;
+        }
+
+        let config = config.sanitized();
+        let mut output = self.reduce(&[samples], Some(&finite_samples(far_end)), LinkSource::Max, &config);
+        Self::apply_output_stages(&mut output, &config);
+        output.remove(0)
+    }
+
+    /// Process a multichannel signal given as one buffer per channel
+    ///
+    /// Channels are combined according to `config.channel_mode`; see the
+    /// [`multichannel`] module. Output gain, loudness normalization and the
+    /// limiter always act on all channels together. A single channel gives the
+    /// same result as [`process`](Self::process). With `config.beamforming`
+    /// set, the channels are first beamformed and one channel is returned.
+    pub fn process_channels(&mut self, channels: &[Vec<f32>], config: &NoiseReductionConfig) -> Vec<Vec<f32>> {
+        self.process_channels_inner(channels, None, config)
+    }
+
+    /// Multichannel version of [`process_with_far_end`](Self::process_with_far_end)
+    ///
+    /// Every channel (or the beamformed, mid and side signals) gets its own
+    /// echo canceller driven by the same mono far-end signal.
+    pub fn process_channels_with_far_end(
+        &mut self,
+        channels: &[Vec<f32>],
+        far_end: &[f32],
+        config: &NoiseReductionConfig,
+    ) -> Vec<Vec<f32>> {
+        self.process_channels_inner(channels, Some(far_end), config)
+    }
+
+    fn process_channels_inner(
+        &mut self,
+        channels: &[Vec<f32>],
+        far_end: Option<&[f32]>,
+        config: &NoiseReductionConfig,
+    ) -> Vec<Vec<f32>> {
+        let len = channels.iter().map(Vec::len).min().unwrap_or(0);
+        if len < self.frame_size {
+            return channels.iter().map(|channel| finite_samples(channel)).collect();
+        }
+        let channels: Vec<Vec<f32>> = channels.iter().map(|channel| finite_samples(&channel[..len])).collect();
+        let far_end = far_end.map(finite_samples);
+        let far_end = far_end.as_deref();
+        let config = &config.sanitized();
+
+        let mut output = match (&config.beamforming, config.channel_mode) {
+            (Some(beamforming), _) if channels.len() > 1 => {
+                let mono = beamform::beamform(&channels, config.sample_rate, beamforming);
+                self.reduce(&[mono], far_end, LinkSource::Max, config)
+            }
+            (_, ChannelMode::Linked(source)) => self.reduce(&channels, far_end, source, config),
+            (_, ChannelMode::MidSide) if channels.len() == 2 => {
+                let (mid, side) = multichannel::to_mid_side(&channels[0], &channels[1]);
+                let mid = self.reduce(&[mid], far_end, LinkSource::Max, config).remove(0);
+                let side = self.reduce(&[side], far_end, LinkSource::Max, config).remove(0);
+                let (left, right) = multichannel::from_mid_side(&mid, &side);
+                vec![left, right]
+            }
+            (_, ChannelMode::Independent | ChannelMode::MidSide) => channels
+                .iter()
+                .map(|channel| self.reduce(std::slice::from_ref(channel), far_end, LinkSource::Max, config).remove(0))
+                .collect(),
+        };
+        Self::apply_output_stages(&mut output, config);
+        output
+    }
+
+    /// Noise reduction up to the broadband gate, with one shared gain mask for all `channels`
+    ///
+    /// With `far_end` given, its echo is cancelled from every channel first.
+    fn reduce(
+        &mut self,
+        channels: &[Vec<f32>],
+        far_end: Option<&[f32]>,
+        source: LinkSource,
+        config: &NoiseReductionConfig,
+    ) -> Vec<Vec<f32>> {
+        let (frame_size, hop_size) = (self.frame_size, self.hop_size);
+        let mut inputs = Self::prepare_inputs(channels, config);
+        let input_len = inputs[0].len();
+        let len = padded_len(input_len, frame_size, hop_size);
+        for input in &mut inputs {
+            input.resize(len, 0.0);
+        }
+        let far_end: Option<Vec<f32>> = far_end.map(|far_end| {
+            far_end.iter().copied().chain(std::iter::repeat(0.0)).take(len).collect()
+        });
+
+        let noise_spectrum = match &config.noise_profile {
+            Some(profile) => profile.full_spectrum(frame_size),
+            None => self.estimate_noise_spectrum(&inputs, source, config.noise_frames),
+        };
         let params = NoiseReductionParams {
             noise_spectrum,
-            config: *config,
+            config: config.clone(),
         };
 
-        let output_samples_len = samples.len() + FRAME_SIZE;
-        let mut output_samples = vec![0.0f32; output_samples_len];
-        let mut window_sum = vec![0.0f32; output_samples_len];
-
-        let hann_window: Vec<f32> = (0..FRAME_SIZE)
-            .map(|i| 0.5 * (1.0 - (2.0 * PI * i as f32 / (FRAME_SIZE - 1) as f32).cos()))
-            .collect();
+        let mut outputs = vec![vec![0.0f32; len + frame_size]; inputs.len()];
+        let mut window_sum = vec![0.0f32; len + frame_size];
+
+        let hann_window = hann_window(frame_size);
+
+        let mut stages = FrameStages {
+            noise_tracker: config.noise_tracking.map(|tracking| {
+                noise_tracking::NoiseTracker::new(&tracking, &params.noise_spectrum, config.sample_rate, hop_size)
+            }),
+            dereverb: config.dereverb.map(|dereverb| {
+                let t60 = dereverb
+                    .t60
+                    .or_else(|| dereverb::estimate_t60(&Self::channel_average(&inputs), config.sample_rate))
+                    .unwrap_or(dereverb::FALLBACK_T60);
+                dereverb::Dereverb::new(&dereverb, t60, config.sample_rate, frame_size, hop_size)
+            }),
+            band_gate: config
+                .gate
+                .filter(|gate| gate.mode == GateMode::PerBand)
+                .map(|gate| gate::BandGate::new(&gate, config.sample_rate, &hann_window, hop_size)),
+            echo_cancellers: match far_end {
+                Some(_) => (0..inputs.len())
+                    .map(|_| aec::EchoCanceller::new(&config.echo_cancellation, config.sample_rate, frame_size, hop_size))
+                    .collect(),
+                None => Vec::new(),
+            },
+        };
+        let gate_masks = match &config.algorithm {
+            Algorithm::SpectralGate(gating) => Some(self.spectral_gate_masks(&inputs, source, gating, config)),
+            Algorithm::SpectralSubtraction => None,
+        };
+        let mut frame_keys = Vec::new();
 
         let mut pos = 0;
-        while pos + FRAME_SIZE <= samples.len() {
-            let mut frame: Vec<f32> = samples[pos..pos + FRAME_SIZE].to_vec();
-            let processed = self.spectral_subtraction(&mut frame, &params);
+        while pos + frame_size <= len {
+            let (mut spectra, mut combined) = self.frame_spectra(&inputs, pos, source);
+            let residual_echo = match &far_end {
+                Some(far_end) => {
+                    let mut far_frame = Vec::from(&far_end[pos..pos + frame_size]);
+                    let far_peak = far_frame.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+                    self.apply_hann_window(&mut far_frame);
+                    let far_spectrum = self.fft_forward(&far_frame);
+
+                    let mut residual = vec![0.0f32; frame_size];
+                    for ((canceller, spectrum), input) in stages.echo_cancellers.iter_mut().zip(&mut spectra).zip(&inputs) {
+                        let mic_peak = input[pos..pos + frame_size].iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+                        let echo = canceller.process_frame(spectrum, &far_spectrum, mic_peak, far_peak);
+                        for (r, e) in residual.iter_mut().zip(echo) {
+                            *r = r.max(e);
+                        }
+                    }
+                    combined = multichannel::combine(&spectra, source);
+                    Some(residual)
+                }
+                None => None,
+            };
+            let reference: Option<Vec<f32>> = (spectra.len() > 1).then(|| combined.iter().map(|bin| bin.norm()).collect());
+            let gate_mask = gate_masks.as_ref().map(|masks| masks[frame_keys.len()].as_slice());
+            let (processed, key) = self.process_frame(combined, &params, &mut stages, gate_mask, residual_echo.as_deref());
+            frame_keys.push(key);
+
+            match reference {
+                Some(reference) => {
+                    // Same real gain on every channel keeps inter-channel phase and level differences
+                    for (bin, (enhanced, &magnitude)) in processed.iter().zip(&reference).enumerate() {
+                        let gain = if magnitude > 0.0 { enhanced.norm() / magnitude } else { 1.0 };
+                        for spectrum in &mut spectra {
+                            spectrum[bin] *= gain;
+                        }
+                    }
+                }
+                None => spectra[0] = processed,
+            }
 
-            for (i, sample) in processed.iter().enumerate() {
-                output_samples[pos + i] += sample;
-                window_sum[pos + i] += hann_window[i];
+            for (output, spectrum) in outputs.iter_mut().zip(&mut spectra) {
+                for (i, sample) in self.fft_inverse(spectrum).iter().enumerate() {
+                    output[pos + i] += sample;
+                }
+            }
+            for (i, w) in hann_window.iter().enumerate() {
+                window_sum[pos + i] += w;
             }
 
-            pos += HOP_SIZE;
+            pos += hop_size;
         }
 
-        // Normalize by window sum and apply makeup gain
-        for (output, ws) in output_samples.iter_mut().zip(window_sum.iter()).take(samples.len()) {
-            if *ws > 0.0 {
-                *output = *output / *ws * config.makeup_gain;
+        for output in &mut outputs {
+            output.truncate(len);
+            normalize_by_window_sum(output, &window_sum);
+
+            FilterBank::from_specs(&config.post_filters, config.sample_rate).process(output);
+        }
+
+        if let Some(gate) = config.gate.filter(|gate| gate.mode == GateMode::Broadband) {
+            let level = match outputs.len() {
+                1 => outputs[0].clone(),
+                count => (0..len)
+                    .map(|i| (outputs.iter().map(|output| output[i] * output[i]).sum::<f32>() / count as f32).sqrt())
+                    .collect(),
+            };
+            let gains = gate::broadband_gains(&level, &frame_keys, &gate, config.sample_rate, frame_size, hop_size);
+            for output in &mut outputs {
+                for (sample, gain) in output.iter_mut().zip(&gains) {
+                    *sample *= gain;
+                }
             }
         }
 
-        output_samples[..samples.len()].to_vec()
+        for output in &mut outputs {
+            output.truncate(input_len);
+        }
+        outputs
     }
 
-    fn estimate_noise_spectrum(&mut self, samples: &[f32], noise_frames: usize) -> Vec<f32> {
-        let mut accumulated_spectrum = vec![0.0f32; FRAME_SIZE];
+    /// Apply loudness normalization or makeup gain, then the limiter, to all channels together
+    fn apply_output_stages(channels: &mut [Vec<f32>], config: &NoiseReductionConfig) {
+        let gain = match &config.loudness {
+            Some(target) => {
+                let views: Vec<&[f32]> = channels.iter().map(Vec::as_slice).collect();
+                loudness::normalization_gain_channels(&views, config.sample_rate, target)
+            }
+            None => config.makeup_gain,
+        };
+        for sample in channels.iter_mut().flatten() {
+            *sample *= gain;
+        }
+
+        if let Some(limiter) = &config.limiter {
+            limiter::limit_channels(channels, config.sample_rate, limiter);
+        }
+    }
+
+    /// DC removal and pre-filters, applied before noise estimation
+    fn prepare_inputs(channels: &[Vec<f32>], config: &NoiseReductionConfig) -> Vec<Vec<f32>> {
+        channels
+            .iter()
+            .map(|channel| {
+                let mut input = channel.clone();
+                if config.remove_dc {
+                    filter::remove_dc_offset(&mut input);
+                }
+                FilterBank::from_specs(&config.pre_filters, config.sample_rate).process(&mut input);
+                input
+            })
+            .collect()
+    }
+
+    fn channel_average(channels: &[Vec<f32>]) -> Vec<f32> {
+        let count = channels.len() as f32;
+        (0..channels[0].len())
+            .map(|i| channels.iter().map(|channel| channel[i]).sum::<f32>() / count)
+            .collect()
+    }
+
+    /// Windowed spectra of every channel at `pos`, plus the combined spectrum the gains are computed from
+    fn frame_spectra(
+        &mut self,
+        channels: &[Vec<f32>],
+        pos: usize,
+        source: LinkSource,
+    ) -> (Vec<Vec<Complex<f32>>>, Vec<Complex<f32>>) {
+        let spectra: Vec<Vec<Complex<f32>>> = channels
+            .iter()
+            .map(|channel| {
+                let mut frame = Vec::from(&channel[pos..pos + self.frame_size]);
+                self.apply_hann_window(&mut frame);
+                self.fft_forward(&frame)
+            })
+            .collect();
+        let combined = multichannel::combine(&spectra, source);
+        (spectra, combined)
+    }
+
+    fn estimate_noise_spectrum(&mut self, channels: &[Vec<f32>], source: LinkSource, noise_frames: usize) -> Vec<f32> {
+        let mut accumulated_spectrum = vec![0.0f32; self.frame_size];
         let mut frames_processed = 0usize;
 
         let mut pos = 0;
-        while pos + FRAME_SIZE <= samples.len() && frames_processed < noise_frames {
-            let mut frame = Vec::from(&samples[pos..pos + FRAME_SIZE]);
-            self.apply_hann_window(&mut frame);
-
-            let spectrum = self.fft_forward(&frame);
+        while pos + self.frame_size <= channels[0].len() && frames_processed < noise_frames {
+            let (_, spectrum) = self.frame_spectra(channels, pos, source);
 
             for (i, bin) in spectrum.iter().enumerate() {
                 accumulated_spectrum[i] += bin.norm();
             }
 
             frames_processed += 1;
-            pos += HOP_SIZE;
+            pos += self.hop_size;
         }
 
         for magnitude in &mut accumulated_spectrum {
@@ -170,26 +782,112 @@ impl AudioProcessor {
         accumulated_spectrum
     }
 
-    fn spectral_subtraction(&mut self, frame: &mut [f32], params: &NoiseReductionParams) -> Vec<f32> {
-        self.apply_hann_window(frame);
+    fn spectral_gate_masks(
+        &mut self,
+        channels: &[Vec<f32>],
+        source: LinkSource,
+        gating: &SpectralGateConfig,
+        config: &NoiseReductionConfig,
+    ) -> Vec<Vec<f32>> {
+        let mut magnitudes = Vec::new();
+        let mut pos = 0;
+        while pos + self.frame_size <= channels[0].len() {
+            let (_, spectrum) = self.frame_spectra(channels, pos, source);
+            magnitudes.push(spectrum[..=self.frame_size / 2].iter().map(|bin| bin.norm()).collect());
+            pos += self.hop_size;
+        }
+
+        let (frame_size, hop_size) = (self.frame_size, self.hop_size);
+        spectral_gate::compute_masks(&mut magnitudes, gating, config.noise_frames, config.sample_rate, frame_size, hop_size);
+        magnitudes
+    }
+
+    /// Suppress noise (and residual echo, if given) in one windowed frame spectrum and return it with the frame's gate key
+    fn process_frame(
+        &mut self,
+        mut spectrum: Vec<Complex<f32>>,
+        params: &NoiseReductionParams,
+        stages: &mut FrameStages,
+        gate_mask: Option<&[f32]>,
+        residual_echo: Option<&[f32]>,
+    ) -> (Vec<Complex<f32>>, gate::FrameKey) {
+        let magnitudes: Vec<f32> = spectrum.iter().map(|bin| bin.norm()).collect();
+        let mut noise_spectrum = match &mut stages.noise_tracker {
+            Some(tracker) => tracker.update(&magnitudes).to_vec(),
+            None => params.noise_spectrum.clone(),
+        };
+        // Echo the linear canceller missed is suppressed like noise
+        if let Some(residual_echo) = residual_echo {
+            for (noise, echo) in noise_spectrum.iter_mut().zip(residual_echo) {
+                *noise += echo;
+            }
+        }
+        let key = gate::FrameKey::measure(&magnitudes, &noise_spectrum, params.config.sample_rate);
 
-        let mut spectrum = self.fft_forward(frame);
+        match gate_mask {
+            Some(mask) => {
+                for (i, bin) in spectrum.iter_mut().enumerate() {
+                    *bin *= mask[i.min(self.frame_size - i)];
+                }
+            }
+            None => {
+                let noisy = params.config.harmonic_regeneration.map(|_| spectrum.clone());
+                Self::spectral_subtraction(&mut spectrum, &magnitudes, &noise_spectrum, &params.config);
+
+                if let (Some(harmonic), Some(noisy)) = (&params.config.harmonic_regeneration, noisy) {
+                    let enhanced = spectrum;
+                    let rectified = harmonic::rectify(&self.fft_inverse(&mut enhanced.clone()));
+                    let regenerated = self.fft_forward(&rectified);
+                    spectrum = noisy;
+                    harmonic::apply(
+                        &mut spectrum,
+                        &enhanced,
+                        &regenerated,
+                        &noise_spectrum,
+                        harmonic,
+                        params.config.spectral_floor,
+                    );
+                }
+            }
+        }
 
-        for (i, bin) in spectrum.iter_mut().enumerate() {
-            let magnitude = bin.norm();
-            let noise_magnitude = params.noise_spectrum[i];
+        if let Some(dereverb) = &mut stages.dereverb {
+            dereverb.process_frame(&mut spectrum);
+        }
 
-            let gain = if magnitude > 0.0 {
-                let raw_gain = (magnitude - params.config.over_subtraction * noise_magnitude) / magnitude;
-                raw_gain.max(params.config.spectral_floor).min(1.0)
-            } else {
-                params.config.spectral_floor
-            };
+        if let Some(band_gate) = &mut stages.band_gate {
+            band_gate.process_frame(&mut spectrum, &magnitudes, &noise_spectrum, &key);
+        }
+
+        (spectrum, key)
+    }
+
+    /// Spectral subtraction gain of one bin
+    ///
+    /// `magnitude` is the bin's magnitude in the current frame and
+    /// `noise_magnitude` the noise estimate for it. The gain is the fraction
+    /// of the magnitude left after subtracting `over_subtraction` times the
+    /// noise, limited to `spectral_floor..=1`. Public for the benchmarks only.
+    #[doc(hidden)]
+    pub fn subtraction_gain(magnitude: f32, noise_magnitude: f32, config: &NoiseReductionConfig) -> f32 {
+        if magnitude > 0.0 {
+            let raw_gain = (magnitude - config.over_subtraction * noise_magnitude) / magnitude;
+            raw_gain.max(config.spectral_floor).min(1.0)
+        } else {
+            config.spectral_floor
+        }
+    }
 
+    fn spectral_subtraction(
+        spectrum: &mut [Complex<f32>],
+        magnitudes: &[f32],
+        noise_spectrum: &[f32],
+        config: &NoiseReductionConfig,
+    ) {
+        for ((bin, &magnitude), &noise_magnitude) in spectrum.iter_mut().zip(magnitudes).zip(noise_spectrum) {
+            let gain = Self::subtraction_gain(magnitude, noise_magnitude, config);
             let phase = bin.arg();
             *bin = Complex::from_polar(magnitude * gain, phase);
         }
-
-        self.fft_inverse(&mut spectrum)
     }
 }
@@ -0,0 +1,129 @@
+//! # Look-ahead True-Peak Limiter
+//!
+//! Brickwall limiter for the end of the output path. Gain reduction is driven
+//! by the oversampled true-peak envelope and starts `lookahead_ms` before each
+//! peak, so overshoots from makeup gain are pulled under the ceiling with a
+//! smooth ramp instead of being hard-clipped.
+
+use crate::loudness;
+use std::collections::VecDeque;
+
+/// Configuration for the output limiter
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default, deny_unknown_fields))]
+pub struct LimiterConfig {
+    /// Maximum output true peak in dBTP (default: -1.0)
+    pub ceiling: f32,
+    /// Time for gain reduction to recover, in milliseconds (default: 50.0)
+    pub release_ms: f32,
+    /// How far ahead peaks are detected, in milliseconds (default: 5.0)
+    pub lookahead_ms: f32,
+}
+
+impl Default for LimiterConfig {
+    fn default() -> Self {
+        Self {
+            ceiling: -1.0,
+            release_ms: 50.0,
+            lookahead_ms: 5.0,
+        }
+    }
+}
+
+/// Limit `samples` in place so their true peak stays at or below the ceiling
+///
+/// Processing is offline over the whole buffer, so look-ahead introduces no
+/// output delay.
+pub fn limit(samples: &mut [f32], sample_rate: u32, config: &LimiterConfig) {
+    let envelope = loudness::true_peak_envelope(samples, sample_rate);
+    apply_gain(samples, &gain_curve(&envelope, sample_rate, config), config);
+}
+
+/// Limit all channels with one shared gain so the stereo image does not shift
+pub fn limit_channels(channels: &mut [Vec<f32>], sample_rate: u32, config: &LimiterConfig) {
+    let len = channels.iter().map(Vec::len).max().unwrap_or(0);
+    let mut envelope = vec![0.0f32; len];
+    for channel in channels.iter() {
+        for (peak, value) in envelope.iter_mut().zip(loudness::true_peak_envelope(channel, sample_rate)) {
+            *peak = peak.max(value);
+        }
+    }
+
+    let gains = gain_curve(&envelope, sample_rate, config);
+    for channel in channels.iter_mut() {
+        apply_gain(channel, &gains, config);
+    }
+}
+
+/// Ceiling as a linear amplitude; a non-finite ceiling falls back to the default
+fn ceiling_amplitude(config: &LimiterConfig) -> f32 {
+    let ceiling = if config.ceiling.is_finite() { config.ceiling } else { LimiterConfig::default().ceiling };
+    10f32.powf(ceiling / 20.0)
+}
+
+fn apply_gain(samples: &mut [f32], gains: &[f32], config: &LimiterConfig) {
+    let ceiling = ceiling_amplitude(config);
+    for (sample, gain) in samples.iter_mut().zip(gains) {
+        *sample = (*sample * gain).clamp(-ceiling, ceiling);
+    }
+}
+
+/// Per-sample gain that keeps a true-peak envelope under the ceiling
+fn gain_curve(envelope: &[f32], sample_rate: u32, config: &LimiterConfig) -> Vec<f32> {
+    if envelope.is_empty() {
+        return Vec::new();
+    }
+
+    let ceiling = ceiling_amplitude(config);
+    let lookahead = ((config.lookahead_ms.max(0.0) / 1000.0 * sample_rate as f32) as usize).max(1);
+    let release_samples = config.release_ms.max(0.0) / 1000.0 * sample_rate as f32;
+    let release = if release_samples > 0.0 { (-1.0 / release_samples).exp() } else { 0.0 };
+
+    let required: Vec<f32> = envelope
+        .iter()
+        .map(|&peak| if peak > ceiling { ceiling / peak } else { 1.0 })
+        .collect();
+
+    let held = forward_minimum(&required, lookahead);
+
+    // Average the held gain over the look-ahead window so the reduction ramps in
+    // and is fully applied by the time the peak arrives
+    let mut window_sum = 0.0f32;
+    let mut gain = 1.0f32;
+    let mut gains = Vec::with_capacity(envelope.len());
+    for i in 0..envelope.len() {
+        window_sum += held[i];
+        if i > lookahead {
+            window_sum -= held[i - lookahead - 1];
+        }
+        let target = window_sum / (i.min(lookahead) + 1) as f32;
+
+        gain = if target < gain {
+            target
+        } else {
+            target + (gain - target) * release
+        };
+
+        gains.push(gain);
+    }
+    gains
+}
+
+/// Minimum of `values[n..=n + window]` for every `n`
+fn forward_minimum(values: &[f32], window: usize) -> Vec<f32> {
+    let mut result = vec![1.0f32; values.len()];
+    let mut candidates: VecDeque<usize> = VecDeque::new();
+
+    for i in (0..values.len()).rev() {
+        while candidates.back().is_some_and(|&j| values[j] >= values[i]) {
+            candidates.pop_back();
+        }
+        candidates.push_back(i);
+        while candidates.front().is_some_and(|&j| j > i + window) {
+            candidates.pop_front();
+        }
+        result[i] = values[candidates[0]];
+    }
+
+    result
+}
@@ -0,0 +1,310 @@
+//! # Loudness Measurement and Normalization
+//!
+//! Implements ITU-R BS.1770-4 K-weighted loudness and the EBU R128 / Tech 3342
+//! measures built on top of it: gated integrated loudness, loudness range and
+//! true-peak level. Used as an alternative to the fixed `makeup_gain`
+//! multiplier so output lands at a predictable loudness without clipping.
+
+use crate::filter::Biquad;
+use std::f64::consts::PI;
+
+/// Momentary block length in seconds (BS.1770 gating block)
+const BLOCK_SECONDS: f64 = 0.4;
+/// Short-term window length in seconds (EBU Tech 3342)
+const SHORT_TERM_SECONDS: f64 = 3.0;
+/// Hop between successive blocks/windows in seconds
+const STEP_SECONDS: f64 = 0.1;
+/// Absolute gating threshold in LUFS
+const ABSOLUTE_GATE: f64 = -70.0;
+/// Relative gate for integrated loudness, in LU below the ungated mean
+const INTEGRATED_RELATIVE_GATE: f64 = -10.0;
+/// Relative gate for loudness range, in LU below the ungated mean
+const RANGE_RELATIVE_GATE: f64 = -20.0;
+/// Taps per polyphase branch of the true-peak interpolator
+const TRUE_PEAK_TAPS: usize = 12;
+
+/// Loudness target for the output stage
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default, deny_unknown_fields))]
+pub struct LoudnessTarget {
+    /// Target integrated loudness in LUFS (default: -23.0, EBU R128)
+    pub integrated_lufs: f32,
+    /// Maximum allowed true peak in dBTP (default: -1.0)
+    pub true_peak_ceiling: f32,
+}
+
+impl Default for LoudnessTarget {
+    fn default() -> Self {
+        Self {
+            integrated_lufs: -23.0,
+            true_peak_ceiling: -1.0,
+        }
+    }
+}
+
+/// Loudness statistics of a mono signal
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LoudnessStats {
+    /// Gated integrated loudness in LUFS, `-inf` for silence or very short input
+    pub integrated_lufs: f32,
+    /// Loudness range in LU
+    pub loudness_range: f32,
+    /// Maximum inter-sample peak in dBTP
+    pub true_peak_dbtp: f32,
+}
+
+/// Build the two K-weighting stages (high shelf + RLB high-pass) for any sample rate
+///
+/// Coefficients are derived from the analog prototypes so they match the
+/// tabulated 48 kHz values in BS.1770 and stay correct at other rates.
+fn k_weighting(sample_rate: u32) -> [Biquad; 2] {
+    let fs = sample_rate as f64;
+
+    let f0 = 1_681.974_450_955_533;
+    let gain_db = 3.999_843_853_973_347;
+    let q = 0.707_175_236_955_419_6;
+    let k = (PI * f0 / fs).tan();
+    let vh = 10f64.powf(gain_db / 20.0);
+    let vb = vh.powf(0.499_666_774_154_541_6);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad::from_coefficients(
+        (vh + vb * k / q + k * k) / a0,
+        2.0 * (k * k - vh) / a0,
+        (vh - vb * k / q + k * k) / a0,
+        2.0 * (k * k - 1.0) / a0,
+        (1.0 - k / q + k * k) / a0,
+    );
+
+    let f0 = 38.135_470_876_024_44;
+    let q = 0.500_327_037_323_877_3;
+    let k = (PI * f0 / fs).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let highpass = Biquad::from_coefficients(
+        1.0,
+        -2.0,
+        1.0,
+        2.0 * (k * k - 1.0) / a0,
+        (1.0 - k / q + k * k) / a0,
+    );
+
+    [shelf, highpass]
+}
+
+/// Prefix sums of the squared K-weighted signal, so any window's mean square is O(1)
+fn weighted_energy(samples: &[f32], sample_rate: u32) -> Vec<f64> {
+    let [mut shelf, mut highpass] = k_weighting(sample_rate);
+    let mut cumulative = Vec::with_capacity(samples.len() + 1);
+    let mut sum = 0.0f64;
+    cumulative.push(sum);
+    for &sample in samples {
+        let y = highpass.tick(shelf.tick(sample as f64));
+        sum += y * y;
+        cumulative.push(sum);
+    }
+    cumulative
+}
+
+/// Mean square of each window of `window` seconds, hopping by `STEP_SECONDS`
+fn window_powers(cumulative: &[f64], sample_rate: u32, window: f64) -> Vec<f64> {
+    let window_len = (window * sample_rate as f64).round() as usize;
+    let step = ((STEP_SECONDS * sample_rate as f64).round() as usize).max(1);
+    let total = cumulative.len() - 1;
+    if window_len == 0 || total < window_len {
+        return Vec::new();
+    }
+
+    (0..=(total - window_len) / step)
+        .map(|i| {
+            let start = i * step;
+            (cumulative[start + window_len] - cumulative[start]) / window_len as f64
+        })
+        .collect()
+}
+
+fn power_to_lufs(power: f64) -> f64 {
+    -0.691 + 10.0 * power.log10()
+}
+
+fn lufs_to_power(lufs: f64) -> f64 {
+    10f64.powf((lufs + 0.691) / 10.0)
+}
+
+/// Apply the absolute and relative gates and return the surviving powers
+fn gate(powers: &[f64], relative_gate: f64) -> Vec<f64> {
+    let absolute_power = lufs_to_power(ABSOLUTE_GATE);
+    let above_absolute: Vec<f64> = powers.iter().copied().filter(|&p| p > absolute_power).collect();
+    if above_absolute.is_empty() {
+        return above_absolute;
+    }
+
+    let mean = above_absolute.iter().sum::<f64>() / above_absolute.len() as f64;
+    let relative_power = lufs_to_power(power_to_lufs(mean) + relative_gate);
+    above_absolute.into_iter().filter(|&p| p > relative_power).collect()
+}
+
+/// Gated integrated loudness in LUFS (BS.1770-4)
+///
+/// Returns negative infinity when the input is shorter than one 400 ms block
+/// or entirely below the absolute gate.
+pub fn integrated_loudness(samples: &[f32], sample_rate: u32) -> f32 {
+    let cumulative = weighted_energy(samples, sample_rate);
+    integrated_from_energy(&cumulative, sample_rate)
+}
+
+fn integrated_from_energy(cumulative: &[f64], sample_rate: u32) -> f32 {
+    let gated = gate(&window_powers(cumulative, sample_rate, BLOCK_SECONDS), INTEGRATED_RELATIVE_GATE);
+    if gated.is_empty() {
+        return f32::NEG_INFINITY;
+    }
+    power_to_lufs(gated.iter().sum::<f64>() / gated.len() as f64) as f32
+}
+
+/// Loudness range in LU (EBU Tech 3342)
+///
+/// Spread between the 10th and 95th percentile of gated 3 s short-term loudness.
+pub fn loudness_range(samples: &[f32], sample_rate: u32) -> f32 {
+    let cumulative = weighted_energy(samples, sample_rate);
+    range_from_energy(&cumulative, sample_rate)
+}
+
+fn range_from_energy(cumulative: &[f64], sample_rate: u32) -> f32 {
+    let gated = gate(&window_powers(cumulative, sample_rate, SHORT_TERM_SECONDS), RANGE_RELATIVE_GATE);
+    if gated.is_empty() {
+        return 0.0;
+    }
+
+    let mut levels: Vec<f64> = gated.into_iter().map(power_to_lufs).collect();
+    levels.sort_by(|a, b| a.total_cmp(b));
+    let percentile = |p: f64| levels[((levels.len() - 1) as f64 * p).round() as usize];
+    (percentile(0.95) - percentile(0.10)) as f32
+}
+
+/// Maximum inter-sample peak in dBTP
+///
+/// Signals below 96 kHz are oversampled 4x (2x below 192 kHz) with a
+/// windowed-sinc polyphase interpolator, as recommended by BS.1770 Annex 2.
+pub fn true_peak(samples: &[f32], sample_rate: u32) -> f32 {
+    let peak = true_peak_envelope(samples, sample_rate)
+        .into_iter()
+        .fold(0.0f32, f32::max);
+    20.0 * peak.max(f32::MIN_POSITIVE).log10()
+}
+
+fn oversampling_factor(sample_rate: u32) -> usize {
+    if sample_rate < 96_000 {
+        4
+    } else if sample_rate < 192_000 {
+        2
+    } else {
+        1
+    }
+}
+
+/// Polyphase interpolation filter, one row of taps per output phase
+fn interpolation_phases(factor: usize) -> Vec<Vec<f32>> {
+    let len = TRUE_PEAK_TAPS * factor;
+    let center = (len - 1) as f64 / 2.0;
+    let prototype: Vec<f64> = (0..len)
+        .map(|n| {
+            let x = (n as f64 - center) / factor as f64;
+            let sinc = if x.abs() < 1e-12 { 1.0 } else { (PI * x).sin() / (PI * x) };
+            let window = 0.5 * (1.0 - (2.0 * PI * n as f64 / (len - 1) as f64).cos());
+            sinc * window
+        })
+        .collect();
+
+    (0..factor)
+        .map(|phase| {
+            (0..TRUE_PEAK_TAPS)
+                .map(|tap| prototype[tap * factor + phase] as f32)
+                .collect()
+        })
+        .collect()
+}
+
+/// Per-sample true-peak magnitude
+///
+/// Entry `n` holds the largest absolute value of the oversampled signal
+/// around sample `n`, compensated for the interpolator delay.
+pub(crate) fn true_peak_envelope(samples: &[f32], sample_rate: u32) -> Vec<f32> {
+    let mut envelope: Vec<f32> = samples.iter().map(|s| s.abs()).collect();
+    let factor = oversampling_factor(sample_rate);
+    if factor == 1 {
+        return envelope;
+    }
+
+    let phases = interpolation_phases(factor);
+    let delay = TRUE_PEAK_TAPS / 2;
+    let mut history = vec![0.0f32; TRUE_PEAK_TAPS];
+
+    // Feed trailing zeros so the filter delay flushes the last samples
+    let tail = std::iter::repeat_n(0.0f32, TRUE_PEAK_TAPS);
+    for (step, sample) in samples.iter().copied().chain(tail).enumerate() {
+        history.rotate_right(1);
+        history[0] = sample;
+
+        let Some(index) = step.checked_sub(delay) else {
+            continue;
+        };
+        if index >= envelope.len() {
+            break;
+        }
+        for taps in &phases {
+            let value: f32 = taps.iter().zip(&history).map(|(t, h)| t * h).sum();
+            envelope[index] = envelope[index].max(value.abs());
+        }
+    }
+
+    envelope
+}
+
+/// Measure integrated loudness, loudness range and true peak in one pass
+pub fn measure(samples: &[f32], sample_rate: u32) -> LoudnessStats {
+    measure_channels(&[samples], sample_rate)
+}
+
+/// Measure a multichannel signal as one program
+///
+/// Channel energies are summed with unit weights (BS.1770 left, right and
+/// center) and the true peak is the highest over all channels. A channel
+/// shorter than the others counts as silent after its end.
+pub fn measure_channels(channels: &[&[f32]], sample_rate: u32) -> LoudnessStats {
+    let energies: Vec<Vec<f64>> = channels.iter().map(|channel| weighted_energy(channel, sample_rate)).collect();
+    let len = energies.iter().map(Vec::len).max().unwrap_or(1);
+    let mut cumulative = vec![0.0f64; len];
+    for energy in &energies {
+        // Running totals stay at their last value past the end of a shorter channel
+        let last = energy.last().copied().unwrap_or(0.0);
+        for (i, total) in cumulative.iter_mut().enumerate() {
+            *total += energy.get(i).copied().unwrap_or(last);
+        }
+    }
+    let peak_dbtp = channels.iter().map(|channel| true_peak(channel, sample_rate)).fold(f32::NEG_INFINITY, f32::max);
+
+    LoudnessStats {
+        integrated_lufs: integrated_from_energy(&cumulative, sample_rate),
+        loudness_range: range_from_energy(&cumulative, sample_rate),
+        true_peak_dbtp: peak_dbtp,
+    }
+}
+
+/// Linear gain that brings `samples` to the target loudness
+///
+/// The gain is reduced when needed so the resulting true peak does not exceed
+/// the ceiling. Silent input (no gated blocks) returns unity gain.
+pub fn normalization_gain(samples: &[f32], sample_rate: u32, target: &LoudnessTarget) -> f32 {
+    normalization_gain_channels(&[samples], sample_rate, target)
+}
+
+/// Single linear gain that brings all channels together to the target loudness
+pub fn normalization_gain_channels(channels: &[&[f32]], sample_rate: u32, target: &LoudnessTarget) -> f32 {
+    let stats = measure_channels(channels, sample_rate);
+    if !stats.integrated_lufs.is_finite() {
+        return 1.0;
+    }
+
+    let loudness_gain_db = target.integrated_lufs - stats.integrated_lufs;
+    let peak_gain_db = target.true_peak_ceiling - stats.true_peak_dbtp;
+    10f32.powf(loudness_gain_db.min(peak_gain_db) / 20.0)
+}
@@ -0,0 +1,353 @@
+//! # Objective Quality Metrics
+//!
+//! Scores a processed recording against the clean reference it was made
+//! from, so parameter changes can be measured on a corpus instead of judged
+//! by ear:
+//!
+//! - **SNR**: clean energy over the energy of everything that differs from it
+//! - **Segmental SNR**: the same per 20 ms frame, clamped to -10..35 dB and
+//!   averaged over the non-silent frames, so quiet passages count as much as
+//!   loud ones
+//! - **Log-spectral distance**: RMS difference of the log power spectra in dB,
+//!   averaged over the STFT frames within 40 dB of the loudest clean frame,
+//!   with both spectra floored 80 dB below the loudest clean bin
+//! - **STOI** (Taal et al., 2011) and **ESTOI** (Jensen and Taal, 2016):
+//!   short-time objective intelligibility, from the correlation of one-third
+//!   octave band envelopes over 384 ms segments, resampled to 10 kHz. Roughly
+//!   0.0 (unintelligible) to 1.0 (identical envelopes). Silent frames are
+//!   dropped in the STFT domain, a close approximation of the reference
+//!   implementations, which drop them before the STFT.
+//!
+//! [`evaluate`] first aligns the processed signal to the clean one with
+//! [`residual::align`](crate::residual::align), so latency and makeup gain do
+//! not count as distortion.
+
+use crate::{residual, AudioProcessor, FRAME_SIZE};
+use num_complex::Complex;
+use rustfft::FftPlanner;
+use std::f32::consts::PI;
+
+/// SNR reported when the processed signal matches the clean one exactly
+const MAX_SNR_DB: f32 = 100.0;
+/// Segmental SNR frame length in seconds
+const SEGMENT_SECONDS: f32 = 0.02;
+/// Per-frame SNR limits of the segmental SNR
+const SEGMENT_SNR_RANGE: (f32, f32) = (-10.0, 35.0);
+/// Log spectra are floored this many dB below the loudest clean bin
+const LSD_FLOOR_DB: f32 = 80.0;
+
+/// STOI sample rate
+const STOI_RATE: u32 = 10000;
+/// STOI frame length, hop and FFT size at 10 kHz
+const STOI_FRAME: usize = 256;
+const STOI_HOP: usize = 128;
+const STOI_FFT: usize = 512;
+/// One-third octave bands from 150 Hz
+const STOI_BANDS: usize = 15;
+const STOI_LOWEST_HZ: f32 = 150.0;
+/// Frames per correlation segment (384 ms)
+const STOI_SEGMENT: usize = 30;
+/// Frames more than this many dB below the loudest clean frame are dropped (also for the LSD)
+const STOI_DYNAMIC_RANGE_DB: f32 = 40.0;
+/// Lower bound on the signal-to-distortion ratio of the clipped processed envelope
+const STOI_BETA_DB: f32 = -15.0;
+
+/// Quality of a processed signal against its clean reference
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Metrics {
+    /// Signal-to-noise ratio in dB
+    pub snr_db: f32,
+    /// Segmental SNR in dB
+    pub segmental_snr_db: f32,
+    /// Log-spectral distance in dB, 0.0 for identical spectra
+    pub log_spectral_distance_db: f32,
+    /// Short-time objective intelligibility, `None` for less than 384 ms of non-silent audio
+    pub stoi: Option<f32>,
+    /// Extended STOI, `None` for less than 384 ms of non-silent audio
+    pub estoi: Option<f32>,
+    /// Samples the processed signal lagged the clean one by
+    pub delay: isize,
+    /// Gain applied to the processed signal to match the clean level
+    pub gain: f32,
+}
+
+/// Align `processed` to `clean` and compute every metric
+pub fn evaluate(clean: &[f32], processed: &[f32], sample_rate: u32) -> Metrics {
+    let alignment = residual::align(clean, processed);
+    let aligned: Vec<f32> = (0..clean.len())
+        .map(|i| {
+            let j = i.checked_add_signed(alignment.delay);
+            alignment.gain * j.and_then(|j| processed.get(j)).copied().unwrap_or(0.0)
+        })
+        .collect();
+    // Both intelligibility measures share the resampled band envelopes
+    let envelopes = band_envelopes(clean, &aligned, sample_rate);
+
+    Metrics {
+        snr_db: snr(clean, &aligned),
+        segmental_snr_db: segmental_snr(clean, &aligned, sample_rate),
+        log_spectral_distance_db: log_spectral_distance(clean, &aligned),
+        stoi: envelopes.as_ref().map(|(x, y)| stoi_from_envelopes(x, y)),
+        estoi: envelopes.as_ref().map(|(x, y)| estoi_from_envelopes(x, y)),
+        delay: alignment.delay,
+        gain: alignment.gain,
+    }
+}
+
+/// SNR of `processed` in dB, over the length of the shorter signal
+pub fn snr(clean: &[f32], processed: &[f32]) -> f32 {
+    let (signal, noise) = clean.iter().zip(processed).fold((0.0f64, 0.0f64), |(signal, noise), (&c, &p)| {
+        (signal + (c as f64).powi(2), noise + (c as f64 - p as f64).powi(2))
+    });
+    ratio_db(signal, noise)
+}
+
+/// Mean of the per-frame SNRs over 20 ms frames, each clamped to -10..35 dB
+///
+/// Frames where the clean signal is digital silence are left out.
+pub fn segmental_snr(clean: &[f32], processed: &[f32], sample_rate: u32) -> f32 {
+    let frame = ((sample_rate as f32 * SEGMENT_SECONDS) as usize).max(1);
+    let len = clean.len().min(processed.len());
+    let (low, high) = SEGMENT_SNR_RANGE;
+    let values: Vec<f32> = clean[..len]
+        .chunks_exact(frame)
+        .zip(processed[..len].chunks_exact(frame))
+        .filter(|(c, _)| c.iter().any(|&s| s != 0.0))
+        .map(|(c, p)| snr(c, p).clamp(low, high))
+        .collect();
+    if values.is_empty() {
+        return snr(clean, processed).clamp(low, high);
+    }
+    values.iter().sum::<f32>() / values.len() as f32
+}
+
+/// Mean over active STFT frames of the RMS difference between the log power spectra, in dB
+///
+/// Frames more than 40 dB below the loudest clean frame are left out and both
+/// spectra are floored 80 dB below the loudest clean bin, so digital silence in
+/// the reference does not dominate the average.
+pub fn log_spectral_distance(clean: &[f32], processed: &[f32]) -> f32 {
+    let len = clean.len().min(processed.len());
+    let mut processor = AudioProcessor::new(FRAME_SIZE);
+    let power_db = |frames: Vec<Vec<f32>>| -> Vec<Vec<f32>> {
+        frames.into_iter().map(|frame| frame.iter().map(|m| 20.0 * m.max(f32::MIN_POSITIVE).log10()).collect()).collect()
+    };
+    let reference = power_db(processor.stft_magnitudes(&clean[..len]));
+    let estimate = power_db(processor.stft_magnitudes(&processed[..len]));
+
+    let floor = reference.iter().flatten().copied().fold(f32::MIN, f32::max) - LSD_FLOOR_DB;
+    let energy_db = |frame: &[f32]| 10.0 * frame.iter().map(|db| 10f32.powf(db / 10.0)).sum::<f32>().max(f32::MIN_POSITIVE).log10();
+    let energies: Vec<f32> = reference.iter().map(|frame| energy_db(frame)).collect();
+    let loudest = energies.iter().copied().fold(f32::MIN, f32::max);
+
+    let distances: Vec<f32> = reference
+        .iter()
+        .zip(&estimate)
+        .zip(&energies)
+        .filter(|(_, &energy)| energy > loudest - STOI_DYNAMIC_RANGE_DB)
+        .map(|((r, e), _)| {
+            let squared: f32 = r.iter().zip(e).map(|(r, e)| (r.max(floor) - e.max(floor)).powi(2)).sum();
+            (squared / r.len() as f32).sqrt()
+        })
+        .collect();
+    if distances.is_empty() {
+        return 0.0;
+    }
+    distances.iter().sum::<f32>() / distances.len() as f32
+}
+
+/// Short-time objective intelligibility of `processed`
+pub fn stoi(clean: &[f32], processed: &[f32], sample_rate: u32) -> Option<f32> {
+    band_envelopes(clean, processed, sample_rate).map(|(x, y)| stoi_from_envelopes(&x, &y))
+}
+
+/// Extended STOI, which also holds up for fluctuating noise
+pub fn estoi(clean: &[f32], processed: &[f32], sample_rate: u32) -> Option<f32> {
+    band_envelopes(clean, processed, sample_rate).map(|(x, y)| estoi_from_envelopes(&x, &y))
+}
+
+fn stoi_from_envelopes(x: &Envelopes, y: &Envelopes) -> f32 {
+    let clip = 1.0 + 10f32.powf(-STOI_BETA_DB / 20.0);
+
+    let mut total = 0.0;
+    let mut count = 0;
+    for end in STOI_SEGMENT..=x[0].len() {
+        for (x_band, y_band) in x.iter().zip(y) {
+            let x_segment = &x_band[end - STOI_SEGMENT..end];
+            let y_segment = &y_band[end - STOI_SEGMENT..end];
+            let y_norm = norm(y_segment);
+            let scale = if y_norm > 0.0 { norm(x_segment) / y_norm } else { 0.0 };
+            let clipped: Vec<f32> = x_segment.iter().zip(y_segment).map(|(&x, &y)| (scale * y).min(clip * x)).collect();
+            total += correlation(x_segment, &clipped);
+            count += 1;
+        }
+    }
+    total / count as f32
+}
+
+fn estoi_from_envelopes(x: &Envelopes, y: &Envelopes) -> f32 {
+    let mut total = 0.0;
+    let mut count = 0;
+    for end in STOI_SEGMENT..=x[0].len() {
+        let x_segment = normalize_segment(x, end);
+        let y_segment = normalize_segment(y, end);
+        let mut sum = 0.0;
+        for frame in 0..STOI_SEGMENT {
+            sum += (0..STOI_BANDS).map(|band| x_segment[band][frame] * y_segment[band][frame]).sum::<f32>();
+        }
+        total += sum / STOI_SEGMENT as f32;
+        count += 1;
+    }
+    total / count as f32
+}
+
+fn ratio_db(signal: f64, noise: f64) -> f32 {
+    if noise <= 0.0 {
+        return if signal > 0.0 { MAX_SNR_DB } else { 0.0 };
+    }
+    if signal <= 0.0 {
+        return -MAX_SNR_DB;
+    }
+    (10.0 * (signal / noise).log10()).clamp(-MAX_SNR_DB as f64, MAX_SNR_DB as f64) as f32
+}
+
+fn norm(values: &[f32]) -> f32 {
+    values.iter().map(|v| v * v).sum::<f32>().sqrt()
+}
+
+/// Pearson correlation of two equally long vectors, 0.0 if either is constant
+fn correlation(a: &[f32], b: &[f32]) -> f32 {
+    let mean = |v: &[f32]| v.iter().sum::<f32>() / v.len() as f32;
+    let (mean_a, mean_b) = (mean(a), mean(b));
+    let (mut product, mut energy_a, mut energy_b) = (0.0, 0.0, 0.0);
+    for (&a, &b) in a.iter().zip(b) {
+        product += (a - mean_a) * (b - mean_b);
+        energy_a += (a - mean_a).powi(2);
+        energy_b += (b - mean_b).powi(2);
+    }
+    let denominator = (energy_a * energy_b).sqrt();
+    if denominator > 0.0 {
+        product / denominator
+    } else {
+        0.0
+    }
+}
+
+/// Segment of band envelopes ending at frame `end`, each band made zero-mean and unit-norm
+/// over time, then each frame zero-mean and unit-norm over bands (the ESTOI normalization)
+fn normalize_segment(bands: &[Vec<f32>], end: usize) -> Vec<Vec<f32>> {
+    let unit = |values: &mut [f32]| {
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+        values.iter_mut().for_each(|v| *v -= mean);
+        let length = norm(values);
+        if length > 0.0 {
+            values.iter_mut().for_each(|v| *v /= length);
+        }
+    };
+
+    let mut segment: Vec<Vec<f32>> = bands.iter().map(|band| band[end - STOI_SEGMENT..end].to_vec()).collect();
+    segment.iter_mut().for_each(|band| unit(band));
+    for frame in 0..STOI_SEGMENT {
+        let mut column: Vec<f32> = segment.iter().map(|band| band[frame]).collect();
+        unit(&mut column);
+        for (band, value) in segment.iter_mut().zip(column) {
+            band[frame] = value;
+        }
+    }
+    segment
+}
+
+/// Band envelopes indexed by band, then frame
+type Envelopes = Vec<Vec<f32>>;
+
+/// One-third octave band envelopes (band x frame) of both signals at 10 kHz, silent frames dropped
+///
+/// `None` when fewer than one segment of frames remain.
+fn band_envelopes(clean: &[f32], processed: &[f32], sample_rate: u32) -> Option<(Envelopes, Envelopes)> {
+    let len = clean.len().min(processed.len());
+    let clean = resample(&clean[..len], sample_rate, STOI_RATE);
+    let processed = resample(&processed[..len], sample_rate, STOI_RATE);
+    if clean.len() < STOI_FRAME {
+        return None;
+    }
+
+    let window: Vec<f32> = (0..STOI_FRAME).map(|i| 0.5 * (1.0 - (2.0 * PI * (i + 1) as f32 / (STOI_FRAME + 1) as f32).cos())).collect();
+    let starts: Vec<usize> = (0..=clean.len() - STOI_FRAME).step_by(STOI_HOP).collect();
+    let energies: Vec<f32> = starts
+        .iter()
+        .map(|&start| {
+            let energy: f32 = clean[start..start + STOI_FRAME].iter().zip(&window).map(|(s, w)| (s * w).powi(2)).sum();
+            20.0 * (energy.sqrt() + f32::EPSILON).log10()
+        })
+        .collect();
+    let loudest = energies.iter().copied().fold(f32::MIN, f32::max);
+    let kept: Vec<usize> = starts
+        .iter()
+        .zip(&energies)
+        .filter(|(_, &energy)| energy > loudest - STOI_DYNAMIC_RANGE_DB)
+        .map(|(&start, _)| start)
+        .collect();
+    if kept.len() < STOI_SEGMENT {
+        return None;
+    }
+
+    let bin_hz = STOI_RATE as f32 / STOI_FFT as f32;
+    let bands: Vec<std::ops::Range<usize>> = (0..STOI_BANDS)
+        .map(|band| {
+            let low = STOI_LOWEST_HZ * 2f32.powf((2 * band) as f32 / 6.0 - 1.0 / 6.0);
+            let high = STOI_LOWEST_HZ * 2f32.powf((2 * band) as f32 / 6.0 + 1.0 / 6.0);
+            (low / bin_hz).round() as usize..((high / bin_hz).round() as usize).min(STOI_FFT / 2 + 1)
+        })
+        .collect();
+
+    let fft = FftPlanner::<f32>::new().plan_fft_forward(STOI_FFT);
+    let envelopes = |samples: &[f32]| -> Envelopes {
+        let mut result: Envelopes = (0..STOI_BANDS).map(|_| Vec::with_capacity(kept.len())).collect();
+        for &start in &kept {
+            let mut buffer: Vec<Complex<f32>> = samples[start..start + STOI_FRAME]
+                .iter()
+                .zip(&window)
+                .map(|(s, w)| Complex::new(s * w, 0.0))
+                .chain(std::iter::repeat(Complex::new(0.0, 0.0)))
+                .take(STOI_FFT)
+                .collect();
+            fft.process(&mut buffer);
+            for (envelope, bins) in result.iter_mut().zip(&bands) {
+                envelope.push(buffer[bins.clone()].iter().map(|bin| bin.norm_sqr()).sum::<f32>().sqrt());
+            }
+        }
+        result
+    };
+    Some((envelopes(&clean), envelopes(&processed)))
+}
+
+/// Band-limited resampling with a Hann-windowed sinc kernel
+fn resample(samples: &[f32], from: u32, to: u32) -> Vec<f32> {
+    if from == to {
+        return samples.to_vec();
+    }
+    /// Kernel half-width in zero crossings of the sinc
+    const HALF_TAPS: f64 = 32.0;
+    let ratio = from as f64 / to as f64;
+    // Cut off a little below the lower Nyquist frequency
+    let cutoff = 0.95 * (to as f64 / from as f64).min(1.0);
+    let out_len = (samples.len() as f64 / ratio).floor() as usize;
+    let reach = HALF_TAPS / cutoff;
+
+    (0..out_len)
+        .map(|n| {
+            let position = n as f64 * ratio;
+            let first = (position - reach).ceil().max(0.0) as usize;
+            let last = ((position + reach).floor() as usize).min(samples.len() - 1);
+            let mut sum = 0.0f64;
+            for (k, &sample) in samples.iter().enumerate().take(last + 1).skip(first) {
+                let t = (k as f64 - position) * cutoff;
+                let sinc = if t == 0.0 { 1.0 } else { (std::f64::consts::PI * t).sin() / (std::f64::consts::PI * t) };
+                let window = 0.5 * (1.0 + (std::f64::consts::PI * t / HALF_TAPS).cos());
+                sum += sample as f64 * sinc * window * cutoff;
+            }
+            sum as f32
+        })
+        .collect()
+}
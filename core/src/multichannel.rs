@@ -0,0 +1,141 @@
+//! # Multichannel Processing
+//!
+//! Channel handling for [`AudioProcessor::process_channels`](crate::AudioProcessor::process_channels).
+//! Independent per-channel gains make the stereo image wander, because a bin
+//! that is suppressed on the left but not on the right pulls the source
+//! sideways. In linked mode one gain mask is computed per frame from a
+//! combined spectrum and applied to every channel, so inter-channel level and
+//! phase differences are preserved. Mid/side mode processes the sum and
+//! difference signals instead of left and right.
+
+use num_complex::Complex;
+use std::fmt;
+use std::str::FromStr;
+
+/// Spectrum the linked gain mask is computed from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkSource {
+    /// Per-bin maximum magnitude over channels; never suppresses a bin that is loud in any channel
+    Max,
+    /// Per-bin mean magnitude over channels
+    Mean,
+    /// Spectrum of the channel average; out-of-phase content counts for less
+    Mid,
+}
+
+/// How the channels of a multichannel signal are processed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(try_from = "String", into = "String"))]
+pub enum ChannelMode {
+    /// Each channel gets its own noise profile and gains
+    Independent,
+    /// One gain mask from a combined spectrum, applied to all channels
+    Linked(LinkSource),
+    /// Process mid (L+R)/2 and side (L-R)/2 independently; stereo only, other layouts fall back to independent
+    MidSide,
+}
+
+impl Default for ChannelMode {
+    fn default() -> Self {
+        ChannelMode::Linked(LinkSource::Max)
+    }
+}
+
+impl FromStr for ChannelMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "independent" => Ok(ChannelMode::Independent),
+            "linked" | "linked-max" => Ok(ChannelMode::Linked(LinkSource::Max)),
+            "linked-mean" => Ok(ChannelMode::Linked(LinkSource::Mean)),
+            "linked-mid" => Ok(ChannelMode::Linked(LinkSource::Mid)),
+            "mid-side" => Ok(ChannelMode::MidSide),
+            _ => Err(format!(
+                "unknown channel mode '{}' (expected independent, linked-max, linked-mean, linked-mid or mid-side)",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ChannelMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ChannelMode::Independent => "independent",
+            ChannelMode::Linked(LinkSource::Max) => "linked-max",
+            ChannelMode::Linked(LinkSource::Mean) => "linked-mean",
+            ChannelMode::Linked(LinkSource::Mid) => "linked-mid",
+            ChannelMode::MidSide => "mid-side",
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<String> for ChannelMode {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<ChannelMode> for String {
+    fn from(value: ChannelMode) -> Self {
+        value.to_string()
+    }
+}
+
+/// Split interleaved samples into one buffer per channel
+///
+/// A trailing partial frame is dropped.
+pub fn deinterleave(samples: &[f32], channels: usize) -> Vec<Vec<f32>> {
+    let channels = channels.max(1);
+    (0..channels)
+        .map(|channel| samples.chunks_exact(channels).map(|frame| frame[channel]).collect())
+        .collect()
+}
+
+/// Interleave per-channel buffers of equal length
+pub fn interleave(channels: &[Vec<f32>]) -> Vec<f32> {
+    let len = channels.iter().map(Vec::len).min().unwrap_or(0);
+    (0..len).flat_map(|i| channels.iter().map(move |channel| channel[i])).collect()
+}
+
+/// Convert left/right to mid/side, `mid = (l + r) / 2`, `side = (l - r) / 2`
+pub fn to_mid_side(left: &[f32], right: &[f32]) -> (Vec<f32>, Vec<f32>) {
+    left.iter()
+        .zip(right)
+        .map(|(&l, &r)| ((l + r) * 0.5, (l - r) * 0.5))
+        .unzip()
+}
+
+/// Convert mid/side back to left/right
+pub fn from_mid_side(mid: &[f32], side: &[f32]) -> (Vec<f32>, Vec<f32>) {
+    mid.iter().zip(side).map(|(&m, &s)| (m + s, m - s)).unzip()
+}
+
+/// Combine per-channel spectra into the spectrum the shared mask is computed from
+///
+/// Magnitudes follow `source`; phases are taken from the channel average so
+/// stages that resynthesize the frame see a coherent signal. A single channel
+/// is returned unchanged.
+pub(crate) fn combine(spectra: &[Vec<Complex<f32>>], source: LinkSource) -> Vec<Complex<f32>> {
+    if spectra.len() == 1 {
+        return spectra[0].clone();
+    }
+
+    let count = spectra.len() as f32;
+    (0..spectra[0].len())
+        .map(|bin| {
+            let mid = spectra.iter().map(|spectrum| spectrum[bin]).sum::<Complex<f32>>() / count;
+            let magnitude = match source {
+                LinkSource::Max => spectra.iter().map(|spectrum| spectrum[bin].norm()).fold(0.0, f32::max),
+                LinkSource::Mean => spectra.iter().map(|spectrum| spectrum[bin].norm()).sum::<f32>() / count,
+                LinkSource::Mid => return mid,
+            };
+            Complex::from_polar(magnitude, mid.arg())
+        })
+        .collect()
+}
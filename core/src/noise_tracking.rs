@@ -0,0 +1,161 @@
+//! # Noise Tracking
+//!
+//! Non-stationary noise estimate for spectral subtraction. Instead of a single
+//! profile from the first `noise_frames` frames, the noise magnitude of each
+//! bin is re-estimated at every frame from a trailing window of past frames,
+//! so suppression follows traffic, HVAC cycling or crowd noise over a long
+//! recording. Speech rarely occupies a bin for the whole window, so a low
+//! percentile or the minimum of the window sits on the noise.
+//!
+//! - **Percentile**: the given percentile of the raw magnitudes in the window,
+//!   scaled to the noise mean assuming Rayleigh-distributed magnitudes.
+//! - **Minimum**: minimum of recursively smoothed magnitudes (minimum
+//!   statistics), scaled by a fixed bias factor.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+
+/// Ratio of noise mean to the minimum of smoothed magnitudes, calibrated on
+/// white noise with the default window and smoothing
+const MINIMUM_BIAS: f32 = 1.8;
+
+/// How the trailing window is summarized into a noise level
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "kebab-case"))]
+pub enum TrackingMethod {
+    /// Low percentile of the magnitudes in the window
+    Percentile,
+    /// Minimum of the smoothed magnitudes in the window
+    Minimum,
+}
+
+impl FromStr for TrackingMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "percentile" => Ok(TrackingMethod::Percentile),
+            "minimum" => Ok(TrackingMethod::Minimum),
+            _ => Err(format!("unknown tracking method '{}' (expected percentile or minimum)", s)),
+        }
+    }
+}
+
+impl fmt::Display for TrackingMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TrackingMethod::Percentile => "percentile",
+            TrackingMethod::Minimum => "minimum",
+        })
+    }
+}
+
+/// Configuration for the sliding-window noise estimate
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default, deny_unknown_fields))]
+pub struct NoiseTrackingConfig {
+    /// Length of the trailing window in seconds; longer adapts slower but survives longer speech (default: 2.0)
+    pub window_s: f32,
+    /// Window statistic used as the noise level (default: Percentile)
+    pub method: TrackingMethod,
+    /// Percentile of the window taken as the noise level, 1-99, percentile method only (default: 20.0)
+    pub percentile: f32,
+    /// Recursive smoothing of magnitudes before the minimum search, 0.0-1.0, minimum method only (default: 0.7)
+    pub smoothing: f32,
+}
+
+impl Default for NoiseTrackingConfig {
+    fn default() -> Self {
+        Self {
+            window_s: 2.0,
+            method: TrackingMethod::Percentile,
+            percentile: 20.0,
+            smoothing: 0.7,
+        }
+    }
+}
+
+/// Sliding-window noise estimator state carried across STFT frames
+pub(crate) struct NoiseTracker {
+    config: NoiseTrackingConfig,
+    history: VecDeque<Vec<f32>>,
+    window_frames: usize,
+    smoothed: Vec<f32>,
+    scratch: Vec<f32>,
+    estimate: Vec<f32>,
+}
+
+impl NoiseTracker {
+    /// Start from `initial`, a full (two-sided) noise spectrum, which fills the window until real frames replace it
+    pub(crate) fn new(config: &NoiseTrackingConfig, initial: &[f32], sample_rate: u32, hop_size: usize) -> Self {
+        let hop_seconds = hop_size as f32 / sample_rate as f32;
+        let window_frames = ((config.window_s / hop_seconds).round() as usize).max(1);
+        let half = initial.len() / 2 + 1;
+
+        let mut tracker = NoiseTracker {
+            config: *config,
+            history: VecDeque::with_capacity(window_frames + 1),
+            window_frames,
+            smoothed: initial[..half].to_vec(),
+            scratch: Vec::with_capacity(window_frames),
+            estimate: initial.to_vec(),
+        };
+
+        // Seed with values that summarize back to the initial profile
+        let seed: Vec<f32> = initial[..half].iter().map(|&m| m / tracker.correction()).collect();
+        tracker.history.extend(std::iter::repeat_n(seed, window_frames));
+        tracker
+    }
+
+    /// Scale from the window statistic to the mean noise magnitude
+    fn correction(&self) -> f32 {
+        match self.config.method {
+            TrackingMethod::Percentile => {
+                // Rayleigh: mean = σ·sqrt(π/2), quantile q = σ·sqrt(-2·ln(1 - q))
+                let q = (self.config.percentile / 100.0).clamp(0.01, 0.99);
+                (std::f32::consts::PI / 2.0).sqrt() / (-2.0 * (1.0 - q).ln()).sqrt()
+            }
+            TrackingMethod::Minimum => MINIMUM_BIAS,
+        }
+    }
+
+    /// Add a frame's full-spectrum magnitudes and return the updated full-spectrum noise estimate
+    pub(crate) fn update(&mut self, magnitudes: &[f32]) -> &[f32] {
+        let half = self.smoothed.len();
+        let entry = match self.config.method {
+            TrackingMethod::Percentile => magnitudes[..half].to_vec(),
+            TrackingMethod::Minimum => {
+                let alpha = self.config.smoothing.clamp(0.0, 0.99);
+                for (smoothed, &magnitude) in self.smoothed.iter_mut().zip(magnitudes) {
+                    *smoothed = alpha * *smoothed + (1.0 - alpha) * magnitude;
+                }
+                self.smoothed.clone()
+            }
+        };
+        if self.history.len() == self.window_frames {
+            self.history.pop_front();
+        }
+        self.history.push_back(entry);
+
+        let correction = self.correction();
+        let rank = ((self.config.percentile / 100.0).clamp(0.0, 1.0) * (self.history.len() - 1) as f32).round() as usize;
+        let frame_size = self.estimate.len();
+        for bin in 0..half {
+            let level = match self.config.method {
+                TrackingMethod::Percentile => {
+                    self.scratch.clear();
+                    self.scratch.extend(self.history.iter().map(|frame| frame[bin]));
+                    *self.scratch.select_nth_unstable_by(rank, |a, b| a.total_cmp(b)).1
+                }
+                TrackingMethod::Minimum => self.history.iter().map(|frame| frame[bin]).fold(f32::INFINITY, f32::min),
+            };
+
+            self.estimate[bin] = level * correction;
+            if bin > 0 && bin < frame_size - bin {
+                self.estimate[frame_size - bin] = self.estimate[bin];
+            }
+        }
+        &self.estimate
+    }
+}
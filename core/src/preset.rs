@@ -0,0 +1,180 @@
+//! # Presets
+//!
+//! Named sets of spectral subtraction parameters. The four built-in presets
+//! span light cleanup to maximum removal; front-ends add user-defined presets
+//! to a [`PresetRegistry`] and look them up by name.
+//!
+//! User presets are TOML files, read with [`PresetDefinition::parse`] (feature
+//! `toml`):
+//!
+//! ```text
+//! # ~/.config/bg-noise-reduction/presets/podcast.toml
+//! description = "Voice over laptop fan"
+//! over_subtraction = 2.5
+//! spectral_floor = 0.08
+//! ```
+//!
+//! Keys that are left out take the Medium (default) values.
+
+use crate::NoiseReductionConfig;
+use std::fmt;
+use std::str::FromStr;
+
+/// Built-in presets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "kebab-case"))]
+pub enum Preset {
+    /// Minimal distortion for light noise
+    Light,
+    /// Balanced; the default settings
+    Medium,
+    /// Strong noise reduction
+    Heavy,
+    /// Maximum noise removal, audible artifacts likely
+    Extreme,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 4] = [Preset::Light, Preset::Medium, Preset::Heavy, Preset::Extreme];
+
+    /// Parameter values of this preset
+    pub fn definition(self) -> PresetDefinition {
+        let (description, spectral_floor, over_subtraction, makeup_gain) = match self {
+            Preset::Light => ("Minimal distortion, light noise", 0.25, 1.0, 1.2),
+            Preset::Medium => ("Balanced, the default", 0.1, 2.0, 1.5),
+            Preset::Heavy => ("Strong noise reduction", 0.05, 3.0, 1.8),
+            Preset::Extreme => ("Maximum noise removal", 0.02, 4.0, 2.0),
+        };
+        PresetDefinition {
+            name: self.to_string(),
+            description: description.to_string(),
+            noise_frames: 10,
+            spectral_floor,
+            over_subtraction,
+            makeup_gain,
+        }
+    }
+}
+
+impl FromStr for Preset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "light" => Ok(Preset::Light),
+            "medium" => Ok(Preset::Medium),
+            "heavy" => Ok(Preset::Heavy),
+            "extreme" => Ok(Preset::Extreme),
+            _ => Err(format!("unknown preset '{}' (expected light, medium, heavy or extreme)", s)),
+        }
+    }
+}
+
+impl fmt::Display for Preset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Preset::Light => "light",
+            Preset::Medium => "medium",
+            Preset::Heavy => "heavy",
+            Preset::Extreme => "extreme",
+        })
+    }
+}
+
+/// A named preset: the parameter values it sets
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PresetDefinition {
+    /// Name used to select the preset, e.g. "medium"
+    pub name: String,
+    /// One-line description for listings
+    pub description: String,
+    pub noise_frames: usize,
+    pub spectral_floor: f32,
+    pub over_subtraction: f32,
+    pub makeup_gain: f32,
+}
+
+impl PresetDefinition {
+    /// Parse a user preset from TOML; unknown keys are an error
+    #[cfg(feature = "toml")]
+    pub fn parse(name: &str, text: &str) -> Result<Self, String> {
+        let file: PresetFile = toml::from_str(text).map_err(|e| e.to_string().trim_end().to_string())?;
+        let medium = Preset::Medium.definition();
+        Ok(PresetDefinition {
+            name: name.to_string(),
+            description: file.description,
+            noise_frames: file.noise_frames.unwrap_or(medium.noise_frames),
+            spectral_floor: file.spectral_floor.unwrap_or(medium.spectral_floor),
+            over_subtraction: file.over_subtraction.unwrap_or(medium.over_subtraction),
+            makeup_gain: file.makeup_gain.unwrap_or(medium.makeup_gain),
+        })
+    }
+
+    /// Write the preset's values into `config`, leaving all other options alone
+    pub fn apply(&self, config: &mut NoiseReductionConfig) {
+        config.noise_frames = self.noise_frames;
+        config.spectral_floor = self.spectral_floor;
+        config.over_subtraction = self.over_subtraction;
+        config.makeup_gain = self.makeup_gain;
+    }
+}
+
+/// Keys of a user preset file
+#[cfg(feature = "toml")]
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PresetFile {
+    #[serde(default)]
+    description: String,
+    noise_frames: Option<usize>,
+    spectral_floor: Option<f32>,
+    over_subtraction: Option<f32>,
+    makeup_gain: Option<f32>,
+}
+
+/// Presets available by name: the built-ins plus any added by the user
+#[derive(Debug, Clone, PartialEq)]
+pub struct PresetRegistry {
+    presets: Vec<PresetDefinition>,
+}
+
+impl Default for PresetRegistry {
+    fn default() -> Self {
+        Self {
+            presets: Preset::ALL.iter().map(|preset| preset.definition()).collect(),
+        }
+    }
+}
+
+impl PresetRegistry {
+    /// Add a preset, replacing any existing preset of the same name
+    pub fn add(&mut self, preset: PresetDefinition) {
+        match self.presets.iter_mut().find(|existing| existing.name == preset.name) {
+            Some(existing) => *existing = preset,
+            None => self.presets.push(preset),
+        }
+    }
+
+    /// Look up a preset by name
+    pub fn get(&self, name: &str) -> Result<&PresetDefinition, String> {
+        self.presets.iter().find(|preset| preset.name == name).ok_or_else(|| {
+            let names: Vec<&str> = self.presets.iter().map(|preset| preset.name.as_str()).collect();
+            format!("unknown preset '{}' (expected {})", name, names.join(", "))
+        })
+    }
+
+    /// All presets, built-ins first
+    pub fn iter(&self) -> impl Iterator<Item = &PresetDefinition> {
+        self.presets.iter()
+    }
+}
+
+impl NoiseReductionConfig {
+    /// Default configuration with a built-in preset's values
+    pub fn from_preset(preset: Preset) -> Self {
+        let mut config = Self::default();
+        preset.definition().apply(&mut config);
+        config
+    }
+}
@@ -0,0 +1,88 @@
+//! # Noise Profiles
+//!
+//! By default the noise spectrum is estimated from the first `noise_frames`
+//! frames of the recording, which only works when it starts with noise alone.
+//! A [`NoiseProfile`] is learned instead from a separate noise-only recording
+//! (room tone, the fan on its own) and set as
+//! [`NoiseReductionConfig::noise_profile`](crate::NoiseReductionConfig::noise_profile),
+//! so recordings that start straight into speech are handled as well.
+//!
+//! The profile is the mean magnitude of every STFT frame of the noise
+//! recording, after the same DC removal and pre-filters as processing, so it
+//! can be subtracted as is.
+
+use crate::{AudioProcessor, LinkSource, NoiseReductionConfig, FRAME_SIZE, HOP_SIZE};
+
+/// Mean noise magnitude spectrum learned from a noise-only recording
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(deny_unknown_fields))]
+pub struct NoiseProfile {
+    /// Sample rate of the noise recording in Hz; the profile only fits input at the same rate
+    pub sample_rate: u32,
+    /// Mean magnitude of FFT bins 0 to `FRAME_SIZE / 2` of the Hann-windowed frames
+    pub magnitudes: Vec<f32>,
+}
+
+impl NoiseProfile {
+    /// Center frequency of each bin of `magnitudes` in Hz
+    pub fn frequencies(&self) -> Vec<f32> {
+        (0..self.magnitudes.len())
+            .map(|bin| bin as f32 * self.sample_rate as f32 / FRAME_SIZE as f32)
+            .collect()
+    }
+
+    /// Magnitudes in dB relative to a full-scale sine, for display
+    pub fn magnitudes_db(&self) -> Vec<f32> {
+        // A full-scale sine peaks at a quarter of the frame size after the Hann window
+        let full_scale = FRAME_SIZE as f32 / 4.0;
+        self.magnitudes
+            .iter()
+            .map(|&magnitude| 20.0 * (magnitude / full_scale).max(1e-10).log10())
+            .collect()
+    }
+
+    /// Magnitudes of all `frame_size` bins, mirrored like the spectrum of a real signal
+    ///
+    /// For frames other than `FRAME_SIZE` the profile is interpolated linearly
+    /// in frequency and scaled by the square root of the frame size ratio, the
+    /// way the windowed magnitude of stationary noise grows with the frame.
+    pub(crate) fn full_spectrum(&self, frame_size: usize) -> Vec<f32> {
+        let ratio = FRAME_SIZE as f32 / frame_size as f32;
+        let scale = ratio.recip().sqrt();
+        let last = self.magnitudes.len() - 1;
+        (0..frame_size)
+            .map(|bin| {
+                let position = (bin.min(frame_size - bin) as f32 * ratio).min(last as f32);
+                let (low, fraction) = (position as usize, position.fract());
+                let high = (low + 1).min(last);
+                scale * (self.magnitudes[low] * (1.0 - fraction) + self.magnitudes[high] * fraction)
+            })
+            .collect()
+    }
+}
+
+impl AudioProcessor {
+    /// Learn a noise profile from mono noise-only samples
+    ///
+    /// DC removal and pre-filters are taken from `config`, which should match
+    /// the configuration the profile is later used with. Profiles are always
+    /// learned with `FRAME_SIZE` frames, whatever this processor's frame size.
+    /// Fails if the samples are shorter than one frame.
+    pub fn learn_noise_profile(&mut self, samples: &[f32], config: &NoiseReductionConfig) -> Result<NoiseProfile, String> {
+        if self.frame_size != FRAME_SIZE {
+            return AudioProcessor::new(FRAME_SIZE).learn_noise_profile(samples, config);
+        }
+        if samples.len() < FRAME_SIZE {
+            return Err(format!("noise recording is shorter than one frame ({} samples)", FRAME_SIZE));
+        }
+
+        let input = Self::prepare_inputs(&[samples.to_vec()], config);
+        let frames = (samples.len() - FRAME_SIZE) / HOP_SIZE + 1;
+        let spectrum = self.estimate_noise_spectrum(&input, LinkSource::Max, frames);
+
+        Ok(NoiseProfile {
+            sample_rate: config.sample_rate,
+            magnitudes: spectrum[..=FRAME_SIZE / 2].to_vec(),
+        })
+    }
+}
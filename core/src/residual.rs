@@ -0,0 +1,65 @@
+//! # Removed-Noise Residual
+//!
+//! The difference between the input and the denoised output: what the
+//! processing took out. Listening to it is the quickest way to hear speech
+//! damage, since any speech in the residual is speech missing from the output.
+//!
+//! The output is first aligned to the input and scaled back to the input
+//! level, so makeup gain or loudness normalization does not leak the whole
+//! signal into the difference. The delay is the peak of the GCC-PHAT
+//! cross-correlation [`beamform`](crate::beamform) uses for steering; the gain
+//! is the least-squares factor that best maps the aligned output onto the input.
+
+use crate::beamform;
+
+/// Largest delay searched, in samples either way
+pub const MAX_DELAY: usize = 4096;
+
+/// How a processed signal lines up with its input
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Alignment {
+    /// Samples the output lags the input by (negative if it leads)
+    pub delay: isize,
+    /// Factor applied to the aligned output to bring it to the input level
+    pub gain: f32,
+}
+
+/// Find the delay and gain that best map `output` onto `input`
+///
+/// The delay is searched within [`MAX_DELAY`] samples. The gain is never
+/// negative and is 0.0 for a silent output.
+pub fn align(input: &[f32], output: &[f32]) -> Alignment {
+    let delay = estimate_delay(input, output);
+    let (mut cross, mut energy) = (0.0f64, 0.0f64);
+    for (i, &x) in input.iter().enumerate() {
+        let y = shifted(output, i, delay) as f64;
+        cross += x as f64 * y;
+        energy += y * y;
+    }
+    let gain = if energy > 0.0 { (cross / energy).max(0.0) as f32 } else { 0.0 };
+    Alignment { delay, gain }
+}
+
+/// Input minus the aligned, gain-compensated output, as long as `input`
+pub fn residual(input: &[f32], output: &[f32]) -> Vec<f32> {
+    residual_with(input, output, align(input, output))
+}
+
+/// Input minus `output` aligned and scaled by a known [`Alignment`]
+pub fn residual_with(input: &[f32], output: &[f32], alignment: Alignment) -> Vec<f32> {
+    input
+        .iter()
+        .enumerate()
+        .map(|(i, &x)| x - alignment.gain * shifted(output, i, alignment.delay))
+        .collect()
+}
+
+/// `output[i + delay]`, zero outside the signal
+fn shifted(output: &[f32], i: usize, delay: isize) -> f32 {
+    i.checked_add_signed(delay).and_then(|j| output.get(j)).copied().unwrap_or(0.0)
+}
+
+/// Lag of the GCC-PHAT peak within [`MAX_DELAY`]
+fn estimate_delay(input: &[f32], output: &[f32]) -> isize {
+    beamform::phat_correlation(input, output).peak(MAX_DELAY)
+}
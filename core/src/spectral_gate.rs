@@ -0,0 +1,189 @@
+//! # Spectral Gating
+//!
+//! Mask-based alternative to spectral subtraction that mirrors Python's
+//! `noisereduce`. Bins are gated against a per-bin threshold and the binary
+//! (or sigmoid) mask is smoothed in time and frequency before it is applied.
+//!
+//! - **Stationary**: threshold is `mean + n_std_thresh * std` of the noise
+//!   profile in dB, measured over the first `noise_frames` frames.
+//! - **Non-stationary**: each bin is compared with a forward-backward smoothed
+//!   copy of itself (time constant `time_constant_s`), so the threshold follows
+//!   slowly changing noise.
+
+/// Configuration for spectral gating (defaults match `noisereduce`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default, deny_unknown_fields))]
+pub struct SpectralGateConfig {
+    /// Use a fixed noise profile (true) or a running time-smoothed estimate (false) (default: true)
+    pub stationary: bool,
+    /// Standard deviations above the noise mean a bin must reach to pass, stationary only (default: 1.5)
+    pub n_std_thresh: f32,
+    /// Fraction of the gated signal removed, 0.0-1.0 (default: 1.0)
+    pub prop_decrease: f32,
+    /// Frequency extent of the mask smoothing kernel in Hz (default: 500.0)
+    pub freq_mask_smooth_hz: f32,
+    /// Time extent of the mask smoothing kernel in milliseconds (default: 50.0)
+    pub time_mask_smooth_ms: f32,
+    /// Time constant of the noise estimate in seconds, non-stationary only (default: 2.0)
+    pub time_constant_s: f32,
+    /// How far above the smoothed estimate a bin must be to pass, non-stationary only (default: 2.0)
+    pub thresh_n_mult_nonstationary: f32,
+    /// Slope of the sigmoid mask, non-stationary only (default: 10.0)
+    pub sigmoid_slope_nonstationary: f32,
+}
+
+impl Default for SpectralGateConfig {
+    fn default() -> Self {
+        Self {
+            stationary: true,
+            n_std_thresh: 1.5,
+            prop_decrease: 1.0,
+            freq_mask_smooth_hz: 500.0,
+            time_mask_smooth_ms: 50.0,
+            time_constant_s: 2.0,
+            thresh_n_mult_nonstationary: 2.0,
+            sigmoid_slope_nonstationary: 10.0,
+        }
+    }
+}
+
+impl SpectralGateConfig {
+    /// Non-stationary defaults
+    pub fn non_stationary() -> Self {
+        Self {
+            stationary: false,
+            ..Self::default()
+        }
+    }
+}
+
+/// Turn per-frame half-spectrum magnitudes into per-frame gains, in place
+///
+/// `magnitudes` has one row of `frame_size / 2 + 1` bins per STFT frame.
+pub(crate) fn compute_masks(
+    magnitudes: &mut [Vec<f32>],
+    config: &SpectralGateConfig,
+    noise_frames: usize,
+    sample_rate: u32,
+    frame_size: usize,
+    hop_size: usize,
+) {
+    if magnitudes.is_empty() {
+        return;
+    }
+
+    if config.stationary {
+        stationary_mask(magnitudes, config, noise_frames);
+    } else {
+        let hop_seconds = hop_size as f32 / sample_rate as f32;
+        non_stationary_mask(magnitudes, config, hop_seconds);
+    }
+
+    let freq_radius = (config.freq_mask_smooth_hz / (sample_rate as f32 / (frame_size / 2) as f32)) as usize;
+    let time_radius = (config.time_mask_smooth_ms / (hop_size as f32 / sample_rate as f32 * 1000.0)) as usize;
+    smooth_mask(magnitudes, freq_radius, time_radius);
+
+    let prop = config.prop_decrease.clamp(0.0, 1.0);
+    for gain in magnitudes.iter_mut().flatten() {
+        *gain = *gain * prop + (1.0 - prop);
+    }
+}
+
+fn to_db(magnitude: f32) -> f32 {
+    20.0 * magnitude.max(1e-10).log10()
+}
+
+fn stationary_mask(magnitudes: &mut [Vec<f32>], config: &SpectralGateConfig, noise_frames: usize) {
+    // Like noisereduce without a noise clip, fall back to the whole signal
+    let profile_len = if noise_frames == 0 { magnitudes.len() } else { noise_frames.min(magnitudes.len()) };
+    let bins = magnitudes[0].len();
+
+    let thresholds: Vec<f32> = (0..bins)
+        .map(|bin| {
+            let levels: Vec<f32> = magnitudes[..profile_len].iter().map(|row| to_db(row[bin])).collect();
+            let mean = levels.iter().sum::<f32>() / levels.len() as f32;
+            let variance = levels.iter().map(|l| (l - mean) * (l - mean)).sum::<f32>() / levels.len() as f32;
+            mean + config.n_std_thresh * variance.sqrt()
+        })
+        .collect();
+
+    for row in magnitudes.iter_mut() {
+        for (value, threshold) in row.iter_mut().zip(&thresholds) {
+            *value = if to_db(*value) > *threshold { 1.0 } else { 0.0 };
+        }
+    }
+}
+
+fn non_stationary_mask(magnitudes: &mut [Vec<f32>], config: &SpectralGateConfig, hop_seconds: f32) {
+    let frames = config.time_constant_s / hop_seconds;
+    let b = if frames > 0.0 {
+        ((1.0 + 4.0 * frames * frames).sqrt() - 1.0) / (2.0 * frames * frames)
+    } else {
+        1.0
+    };
+
+    let bins = magnitudes[0].len();
+    let mut track = vec![0.0f32; magnitudes.len()];
+    for bin in 0..bins {
+        // Zero-phase one-pole smoothing (forward then backward), like filtfilt
+        let mut state = magnitudes[0][bin];
+        for (t, row) in magnitudes.iter().enumerate() {
+            state = b * row[bin] + (1.0 - b) * state;
+            track[t] = state;
+        }
+        let mut state = track[track.len() - 1];
+        for value in track.iter_mut().rev() {
+            state = b * *value + (1.0 - b) * state;
+            *value = state;
+        }
+
+        for (row, &smoothed) in magnitudes.iter_mut().zip(&track) {
+            let above = (row[bin] - smoothed) / smoothed.max(1e-10);
+            row[bin] = sigmoid((above - config.thresh_n_mult_nonstationary) * config.sigmoid_slope_nonstationary);
+        }
+    }
+}
+
+fn sigmoid(x: f32) -> f32 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+/// Triangular kernel weights `1 - |j| / (radius + 1)` for `j` in `-radius..=radius`
+fn triangle(radius: usize) -> Vec<f32> {
+    (0..=2 * radius)
+        .map(|j| 1.0 - (j as f32 - radius as f32).abs() / (radius + 1) as f32)
+        .collect()
+}
+
+/// Separable 2-D triangular smoothing with zero padding, normalized by the full kernel sum
+fn smooth_mask(mask: &mut [Vec<f32>], freq_radius: usize, time_radius: usize) {
+    let freq_kernel = triangle(freq_radius);
+    let freq_sum: f32 = freq_kernel.iter().sum();
+    for row in mask.iter_mut() {
+        let source = row.clone();
+        for (bin, value) in row.iter_mut().enumerate() {
+            let mut acc = 0.0;
+            for (k, weight) in freq_kernel.iter().enumerate() {
+                if let Some(&v) = (bin + k).checked_sub(freq_radius).and_then(|i| source.get(i)) {
+                    acc += weight * v;
+                }
+            }
+            *value = acc / freq_sum;
+        }
+    }
+
+    let time_kernel = triangle(time_radius);
+    let time_sum: f32 = time_kernel.iter().sum();
+    let source: Vec<Vec<f32>> = mask.to_vec();
+    for (t, row) in mask.iter_mut().enumerate() {
+        for (bin, value) in row.iter_mut().enumerate() {
+            let mut acc = 0.0;
+            for (k, weight) in time_kernel.iter().enumerate() {
+                if let Some(frame) = (t + k).checked_sub(time_radius).and_then(|i| source.get(i)) {
+                    acc += weight * frame[bin];
+                }
+            }
+            *value = acc / time_sum;
+        }
+    }
+}
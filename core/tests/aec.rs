@@ -0,0 +1,104 @@
+// Acoustic echo cancellation tests
+// Run with: cargo test -p bg-noise-reduction-core --test aec
+
+mod common;
+
+use bg_noise_reduction_core::{AecConfig, AudioProcessor, NoiseReductionConfig, FRAME_SIZE};
+use common::{noise, power, tone, SAMPLE_RATE};
+
+const LEN: usize = SAMPLE_RATE as usize * 6;
+
+/// Far-end playback as picked up by the microphone: delayed, attenuated, with a small reflection
+fn echo_of(far_end: &[f32]) -> Vec<f32> {
+    let path = [(40, 0.25), (41, -0.1), (300, 0.05)];
+    (0..far_end.len())
+        .map(|i| path.iter().filter(|(delay, _)| i >= *delay).map(|(delay, gain)| gain * far_end[i - delay]).sum())
+        .collect()
+}
+
+/// Near-end talker, present only in the last two seconds
+fn near_end() -> Vec<f32> {
+    tone(LEN, 440.0, 0.2).iter().enumerate().map(|(i, &s)| if i >= LEN - 2 * SAMPLE_RATE as usize { s } else { 0.0 }).collect()
+}
+
+/// Spectral subtraction and output gain disabled so only the echo canceller acts
+fn echo_only(residual_suppression: f32) -> NoiseReductionConfig {
+    NoiseReductionConfig {
+        over_subtraction: 0.0,
+        makeup_gain: 1.0,
+        sample_rate: SAMPLE_RATE,
+        echo_cancellation: AecConfig { residual_suppression, ..AecConfig::default() },
+        ..NoiseReductionConfig::default()
+    }
+}
+
+/// Echo return loss enhancement in dB over `range`, with the near-end signal removed from the output
+fn erle(output: &[f32], echo: &[f32], near: &[f32], range: std::ops::Range<usize>) -> f32 {
+    let residual: Vec<f32> = output[range.clone()].iter().zip(&near[range.clone()]).map(|(o, n)| o - n).collect();
+    10.0 * (power(&echo[range]) / power(&residual)).log10()
+}
+
+#[test]
+fn test_cancels_echo_in_single_talk() {
+    let far_end = noise(3, LEN, 0.1);
+    let echo = echo_of(&far_end);
+    let silence = vec![0.0; LEN];
+
+    let mut processor = AudioProcessor::new(FRAME_SIZE);
+    let output = processor.process_with_far_end(&echo, &far_end, &echo_only(0.0));
+    assert_eq!(output.len(), LEN);
+
+    let last_second = LEN - SAMPLE_RATE as usize - FRAME_SIZE..LEN - FRAME_SIZE;
+    let db = erle(&output, &echo, &silence, last_second);
+    assert!(db > 15.0, "ERLE only {:.1} dB", db);
+}
+
+#[test]
+fn test_double_talk_keeps_near_end() {
+    let far_end = noise(3, LEN, 0.1);
+    let echo = echo_of(&far_end);
+    let near = near_end();
+    let mic: Vec<f32> = echo.iter().zip(&near).map(|(e, n)| e + n).collect();
+
+    let mut processor = AudioProcessor::new(FRAME_SIZE);
+    let output = processor.process_with_far_end(&mic, &far_end, &echo_only(0.0));
+
+    // The canceller must not adapt to the near-end talker and cancel it along with the echo
+    let double_talk = LEN - SAMPLE_RATE as usize - FRAME_SIZE..LEN - FRAME_SIZE;
+    let db = erle(&output, &echo, &near, double_talk.clone());
+    assert!(db > 10.0, "ERLE during double talk only {:.1} dB", db);
+    let ratio = (power(&output[double_talk.clone()]) / power(&near[double_talk])).sqrt();
+    assert!((0.8..1.25).contains(&ratio), "near-end level changed by {:.2}", ratio);
+}
+
+#[test]
+fn test_residual_suppression_removes_more_echo() {
+    // Far end starts after a second, so the noise profile holds no echo
+    let second = SAMPLE_RATE as usize;
+    let far_end: Vec<f32> = noise(3, LEN, 0.1).iter().enumerate().map(|(i, &s)| if i < second { 0.0 } else { s }).collect();
+    let echo = echo_of(&far_end);
+    let silence = vec![0.0; LEN];
+    // Early frames, before the filter has converged
+    let early = second + FRAME_SIZE..2 * second;
+
+    let mut processor = AudioProcessor::new(FRAME_SIZE);
+    let linear = processor.process_with_far_end(&echo, &far_end, &NoiseReductionConfig { over_subtraction: 1.0, ..echo_only(0.0) });
+    let suppressed = processor.process_with_far_end(&echo, &far_end, &NoiseReductionConfig { over_subtraction: 1.0, ..echo_only(1.0) });
+
+    let linear_db = erle(&linear, &echo, &silence, early.clone());
+    let suppressed_db = erle(&suppressed, &echo, &silence, early);
+    assert!(suppressed_db > linear_db + 3.0, "residual suppression {:.1} dB vs linear {:.1} dB", suppressed_db, linear_db);
+}
+
+#[test]
+fn test_silent_far_end_matches_process() {
+    let mic: Vec<f32> = noise(5, LEN, 0.03).iter().zip(near_end()).map(|(n, s)| n + s).collect();
+    let config = NoiseReductionConfig { sample_rate: SAMPLE_RATE, ..NoiseReductionConfig::default() };
+
+    let mut processor = AudioProcessor::new(FRAME_SIZE);
+    let plain = processor.process(&mic, &config);
+    let cancelled = processor.process_with_far_end(&mic, &[], &config);
+    for (a, b) in plain.iter().zip(&cancelled) {
+        assert!((a - b).abs() <= 1e-4 * a.abs().max(1.0), "{} vs {}", a, b);
+    }
+}
@@ -0,0 +1,115 @@
+// Signal analysis and auto settings tests
+// Run with: cargo test -p bg-noise-reduction-core --test analysis
+
+mod common;
+
+use bg_noise_reduction_core::analysis::{self, AutoSettings};
+use common::{hum, noise, tone, SAMPLE_RATE};
+
+const LEN: usize = SAMPLE_RATE as usize * 4;
+
+/// White noise at `noise_rms` with a tone switched on after the first second, half of the time
+fn tone_bursts(noise_rms: f32, tone_rms: f32) -> Vec<f32> {
+    let tone = tone(LEN, 300.0, tone_rms * 2f32.sqrt());
+    noise(1, LEN, noise_rms)
+        .iter()
+        .zip(tone)
+        .enumerate()
+        .map(|(i, (n, t))| if i >= SAMPLE_RATE as usize && (i / 4000) % 2 == 0 { n + t } else { *n })
+        .collect()
+}
+
+#[test]
+fn test_measures_noise_floor_and_snr() {
+    // Noise at -40 dBFS, tone at -20 dBFS: 20 dB SNR
+    let samples = tone_bursts(0.01, 0.1);
+    let result = analysis::analyze(&samples, SAMPLE_RATE);
+
+    assert!((result.noise_floor_db + 40.0).abs() < 2.0, "noise floor {:.1} dBFS", result.noise_floor_db);
+    assert!((result.snr_db - 20.0).abs() < 2.0, "SNR {:.1} dB", result.snr_db);
+    assert!((result.leading_noise_s - 1.0).abs() < 0.05, "leading noise {:.2} s", result.leading_noise_s);
+}
+
+#[test]
+fn test_noise_flatness() {
+    let white = analysis::analyze(&noise(2, LEN, 0.5), SAMPLE_RATE);
+    assert!(white.noise_flatness > 0.7, "white noise flatness {:.2}", white.noise_flatness);
+
+    let tonal = analysis::analyze(&hum(LEN, 50.0, 5, 0.08), SAMPLE_RATE);
+    assert!(tonal.noise_flatness < 0.2, "hum flatness {:.2}", tonal.noise_flatness);
+}
+
+#[test]
+fn test_lower_snr_gets_stronger_settings() {
+    let clean = AutoSettings::for_samples(&tone_bursts(0.001, 0.1), SAMPLE_RATE);
+    let noisy = AutoSettings::for_samples(&tone_bursts(0.05, 0.1), SAMPLE_RATE);
+
+    assert!(noisy.over_subtraction > clean.over_subtraction + 1.0);
+    assert!(noisy.spectral_floor < clean.spectral_floor);
+    assert!((1.0..=4.5).contains(&noisy.over_subtraction) && (1.0..=4.5).contains(&clean.over_subtraction));
+    assert!((0.01..=0.3).contains(&noisy.spectral_floor) && (0.01..=0.3).contains(&clean.spectral_floor));
+}
+
+#[test]
+fn test_noise_frames_follow_leading_noise_and_gain_keeps_headroom() {
+    let settings = AutoSettings::for_samples(&tone_bursts(0.01, 0.1), SAMPLE_RATE);
+    // One second of noise at 16 kHz holds (16000 - 2048) / 1024 + 1 = 14 frames
+    assert_eq!(settings.noise_frames, 14);
+
+    let loud = tone_bursts(0.01, 0.6);
+    let peak = loud.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+    let settings = AutoSettings::for_samples(&loud, SAMPLE_RATE);
+    assert!(settings.makeup_gain < 1.2, "makeup gain {:.2} not limited", settings.makeup_gain);
+    assert!(settings.makeup_gain * peak <= 0.99 + 1e-6, "makeup gain {:.2} clips a {:.2} peak", settings.makeup_gain, peak);
+}
+
+#[test]
+fn test_report_levels_dc_and_clipping() {
+    // -20 dBFS RMS noise with a 0.05 DC offset on one channel, clipped samples on the other
+    let left: Vec<f32> = noise(3, LEN, 0.1).iter().map(|n| n + 0.05).collect();
+    let mut right = noise(4, LEN, 0.1);
+    right[100..110].iter_mut().for_each(|s| *s = 1.0);
+    let report = analysis::report(&[left, right], SAMPLE_RATE);
+
+    assert_eq!(report.channels, 2);
+    assert!((report.duration_s - 4.0).abs() < 1e-6);
+    assert!((report.dc_offset[0] - 0.05).abs() < 0.005 && report.dc_offset[1].abs() < 0.005, "DC {:?}", report.dc_offset);
+    assert_eq!(report.clipped_samples, 10);
+    assert!(report.peak_db.abs() < 1e-3);
+    assert!((report.rms_db + 19.9).abs() < 0.5, "RMS {:.1} dBFS", report.rms_db);
+    assert!(report.hum.is_none());
+}
+
+#[test]
+fn test_band_noise_floors_follow_noise_spectrum() {
+    // Noise through two one-pole low-passes at about 130 Hz: the low bands sit well above the high ones
+    let mut rumble = noise(5, LEN, 0.5);
+    for _ in 0..2 {
+        let mut state = 0.0;
+        rumble.iter_mut().for_each(|s| {
+            state += 0.05 * (*s - state);
+            *s = state;
+        });
+    }
+    let bands = analysis::band_noise_floors(&rumble, SAMPLE_RATE);
+
+    assert_eq!(bands.last().unwrap().high_hz, SAMPLE_RATE as f32 / 2.0);
+    assert!(bands.windows(2).all(|pair| pair[0].high_hz <= pair[1].low_hz + 1e-3));
+    let level = |hz: f32| bands.iter().find(|band| band.low_hz <= hz && hz < band.high_hz).unwrap().noise_floor_db;
+    assert!(level(125.0) > level(4000.0) + 20.0, "125 Hz at {:.1} dB, 4 kHz at {:.1} dB", level(125.0), level(4000.0));
+}
+
+#[test]
+fn test_detects_mains_hum() {
+    // Three harmonics falling off as 1/k at -35.7 dBFS over noise at -44 dBFS
+    let with_hum = |mains: f32| -> Vec<f32> {
+        noise(6, LEN, 0.006).iter().zip(hum(LEN, mains, 3, 10f32.powf(-35.7 / 20.0))).map(|(n, h)| n + h).collect()
+    };
+
+    let found = analysis::detect_hum(&with_hum(60.0), SAMPLE_RATE).expect("60 Hz hum");
+    assert_eq!(found.fundamental_hz, 60.0);
+    assert_eq!(found.harmonics, vec![1, 2, 3]);
+    assert!((found.level_db + 35.7).abs() < 1.5, "hum at {:.1} dBFS", found.level_db);
+    assert_eq!(analysis::detect_hum(&with_hum(50.0), SAMPLE_RATE).unwrap().fundamental_hz, 50.0);
+    assert!(analysis::detect_hum(&noise(7, LEN, 0.5), SAMPLE_RATE).is_none());
+}
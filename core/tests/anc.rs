@@ -0,0 +1,84 @@
+// Adaptive noise canceller tests
+// Run with: cargo test -p bg-noise-reduction-core --test anc
+
+mod common;
+
+use bg_noise_reduction_core::anc::{self, AncConfig, AncMethod};
+use common::{noise, rms, tone, SAMPLE_RATE};
+
+const LEN: usize = SAMPLE_RATE as usize * 4;
+
+/// Reference noise as it reaches the primary microphone: delayed and coloured by a short FIR path
+fn leak(reference: &[f32]) -> Vec<f32> {
+    let path = [(3, 0.6), (4, -0.3), (9, 0.15)];
+    (0..reference.len())
+        .map(|i| path.iter().filter(|(delay, _)| i >= *delay).map(|(delay, gain)| gain * reference[i - delay]).sum())
+        .collect()
+}
+
+/// Residual noise in dB relative to the noise picked up by the primary microphone, over the last second
+fn cancellation_db(method: AncMethod, filter_length: usize) -> f32 {
+    let reference = noise(7, LEN, 0.6);
+    let speech = tone(LEN, 440.0, 0.2);
+    let primary: Vec<f32> = leak(&reference).iter().zip(&speech).map(|(n, s)| n + s).collect();
+
+    let config = AncConfig { method, filter_length, ..AncConfig::default() };
+    let output = anc::cancel(&primary, &reference, &config);
+    assert_eq!(output.len(), LEN);
+
+    let tail = LEN - SAMPLE_RATE as usize;
+    let residual: Vec<f32> = output[tail..].iter().zip(&speech[tail..]).map(|(o, s)| o - s).collect();
+    20.0 * (rms(&residual) / rms(&leak(&reference)[tail..])).log10()
+}
+
+#[test]
+fn test_nlms_cancels_correlated_noise() {
+    let db = cancellation_db(AncMethod::Nlms, 32);
+    assert!(db < -15.0, "NLMS left {:.1} dB of the noise", db);
+}
+
+#[test]
+fn test_rls_cancels_correlated_noise() {
+    let db = cancellation_db(AncMethod::Rls, 16);
+    assert!(db < -25.0, "RLS left {:.1} dB of the noise", db);
+}
+
+#[test]
+fn test_frequency_domain_cancels_correlated_noise() {
+    let db = cancellation_db(AncMethod::FrequencyDomain, 64);
+    assert!(db < -18.0, "frequency-domain filter left {:.1} dB of the noise", db);
+}
+
+#[test]
+fn test_uncorrelated_reference_keeps_primary() {
+    // A reference unrelated to the primary gives the filter nothing to cancel
+    let speech = tone(LEN, 440.0, 0.2);
+    let reference = noise(11, LEN, 0.006);
+
+    for method in [AncMethod::Nlms, AncMethod::FrequencyDomain] {
+        let config = AncConfig { method, filter_length: 32, ..AncConfig::default() };
+        let output = anc::cancel(&speech, &reference, &config);
+        let ratio = rms(&output) / rms(&speech);
+        assert!((0.8..1.2).contains(&ratio), "{} changed the level by {:.2}", method, ratio);
+    }
+}
+
+#[test]
+fn test_output_has_primary_length() {
+    // A short reference cancels while it lasts and leaves the rest of the primary as it is
+    let reference = noise(7, LEN / 2, 0.6);
+    let speech = tone(LEN, 440.0, 0.2);
+    let mut leaked = leak(&reference);
+    leaked.resize(LEN, 0.0);
+    let primary: Vec<f32> = leaked.iter().zip(&speech).map(|(n, s)| n + s).collect();
+
+    for method in [AncMethod::Nlms, AncMethod::Rls, AncMethod::FrequencyDomain] {
+        let config = AncConfig { method, filter_length: 16, ..AncConfig::default() };
+        let output = anc::cancel(&primary, &reference, &config);
+        assert_eq!(output.len(), LEN, "{}", method);
+        let tail = LEN - SAMPLE_RATE as usize;
+        let residual: Vec<f32> = output[tail..].iter().zip(&speech[tail..]).map(|(o, s)| o - s).collect();
+        assert!(rms(&residual) < 0.01 * rms(&speech), "{} changed the primary after the reference ended", method);
+        assert_eq!(anc::cancel(&primary[..LEN / 4], &reference, &config).len(), LEN / 4, "{}", method);
+    }
+}
@@ -0,0 +1,126 @@
+// Beamforming front-end tests
+// Run with: cargo test -p bg-noise-reduction-core --test beamform
+
+mod common;
+
+use bg_noise_reduction_core::beamform::{self, BeamformConfig, BeamformMethod, Steering};
+use bg_noise_reduction_core::{AudioProcessor, NoiseReductionConfig};
+use common::{noise, power, SAMPLE_RATE};
+
+const LEN: usize = SAMPLE_RATE as usize * 3;
+
+/// `source` as received by each microphone, delayed by a whole number of samples
+fn arrive(source: &[f32], delays: &[usize]) -> Vec<Vec<f32>> {
+    delays
+        .iter()
+        .map(|&delay| (0..source.len()).map(|i| if i >= delay { source[i - delay] } else { 0.0 }).collect())
+        .collect()
+}
+
+fn mix(a: &[Vec<f32>], b: &[Vec<f32>]) -> Vec<Vec<f32>> {
+    a.iter().zip(b).map(|(x, y)| x.iter().zip(y).map(|(p, q)| p + q).collect()).collect()
+}
+
+// Talker from 1 s on, arriving 0/1/2/3 samples later at successive microphones
+fn talker() -> Vec<Vec<f32>> {
+    let source: Vec<f32> =
+        noise(7, LEN, 0.2).iter().enumerate().map(|(i, &s)| if i >= SAMPLE_RATE as usize { s } else { 0.0 }).collect();
+    arrive(&source, &[0, 1, 2, 3])
+}
+
+#[test]
+fn test_gcc_phat_finds_delays() {
+    let channels = arrive(&noise(3, LEN, 0.5), &[0, 2, 5]);
+    for (channel, expected) in channels.iter().zip([0.0, 2.0, 5.0]) {
+        let delay = beamform::gcc_phat(&channels[0], channel, SAMPLE_RATE, 1.0) * SAMPLE_RATE as f32;
+        assert!((delay - expected).abs() < 0.25, "expected {} got {}", expected, delay);
+    }
+}
+
+#[test]
+fn test_geometry_delays_for_endfire_array() {
+    // 4 cm spacing along x, looking along +x: farther microphones hear it first
+    let positions = [[0.0, 0.0, 0.0], [0.04, 0.0, 0.0], [0.08, 0.0, 0.0]];
+    let delays = beamform::geometry_delays(&positions, 0.0, 0.0);
+    assert!((delays[1] + 0.04 / 343.0).abs() < 1e-7);
+    assert!((delays[2] + 0.08 / 343.0).abs() < 1e-7);
+
+    // Broadside: no delay
+    let broadside = beamform::geometry_delays(&positions, 90.0, 0.0);
+    assert!(broadside.iter().all(|d| d.abs() < 1e-7));
+}
+
+#[test]
+fn test_delay_and_sum_reduces_uncorrelated_noise() {
+    let sensor_noise: Vec<Vec<f32>> = (0..4).map(|m| noise(100 + m, LEN, 0.03)).collect();
+    let channels = mix(&talker(), &sensor_noise);
+    let output = beamform::beamform(&channels, SAMPLE_RATE, &BeamformConfig::default());
+
+    // Before the talker starts only sensor noise is left; four microphones give up to 6 dB
+    let quiet = 2048..SAMPLE_RATE as usize - 2048;
+    let reduction = 10.0 * (power(&sensor_noise[0][quiet.clone()]) / power(&output[quiet])).log10();
+    assert!(reduction > 5.0, "noise reduction {} dB", reduction);
+
+    // The aligned talker adds up coherently
+    let speech = SAMPLE_RATE as usize + 2048..LEN - 2048;
+    let target_gain = power(&output[speech.clone()]) / power(&channels[0][speech]);
+    assert!((0.85..1.1).contains(&target_gain), "target gain {}", target_gain);
+}
+
+#[test]
+fn test_mvdr_nulls_directional_interferer() {
+    // Fan on the other side of the array, present from the start
+    let fan = noise(55, LEN, 0.1);
+    let interferer = arrive(&fan, &[6, 4, 2, 0]);
+    let sensor_noise: Vec<Vec<f32>> = (0..4).map(|m| noise(200 + m, LEN, 0.003)).collect();
+    let target = talker();
+    let channels = mix(&mix(&target, &interferer), &sensor_noise);
+
+    let steering = Steering::Geometry {
+        positions: (0..4).map(|m| [-(m as f32) / SAMPLE_RATE as f32 * 343.0, 0.0, 0.0]).collect(),
+        azimuth_deg: 0.0,
+        elevation_deg: 0.0,
+    };
+    let run = |method| {
+        let config = BeamformConfig { method, steering: steering.clone(), ..BeamformConfig::default() };
+        beamform::beamform(&channels, SAMPLE_RATE, &config)
+    };
+    let das = run(BeamformMethod::DelayAndSum);
+    let mvdr = run(BeamformMethod::Mvdr);
+
+    let quiet = 2048..SAMPLE_RATE as usize - 2048;
+    assert!(power(&mvdr[quiet.clone()]) < power(&das[quiet.clone()]) * 0.1);
+
+    // Distortionless towards the talker, who dominates once the fan is nulled
+    let speech = SAMPLE_RATE as usize + 2048..LEN - 2048;
+    let target_gain = power(&mvdr[speech.clone()]) / power(&target[0][speech]);
+    assert!((0.8..1.2).contains(&target_gain), "target gain {}", target_gain);
+}
+
+#[test]
+fn test_beamforming_feeds_noise_reduction() {
+    let sensor_noise: Vec<Vec<f32>> = (0..4).map(|m| noise(300 + m, LEN, 0.03)).collect();
+    let channels = mix(&talker(), &sensor_noise);
+    let config = NoiseReductionConfig {
+        beamforming: Some(BeamformConfig::default()),
+        sample_rate: SAMPLE_RATE,
+        ..NoiseReductionConfig::default()
+    };
+
+    let output = AudioProcessor::new(2048).process_channels(&channels, &config);
+    assert_eq!(output.len(), 1);
+    assert_eq!(output[0].len(), LEN);
+}
+
+#[test]
+fn test_delay_and_sum_keeps_the_tail() {
+    // A length that is not a whole number of hops; the samples after the last full frame used to come back silent
+    let channels = arrive(&noise(9, LEN + 777, 0.2), &[0, 1, 2, 3]);
+    let output = beamform::beamform(&channels, SAMPLE_RATE, &BeamformConfig::default());
+    assert_eq!(output.len(), LEN + 777);
+
+    let tail = LEN + 777 - 256..LEN + 777;
+    let gain = power(&output[tail.clone()]) / power(&channels[0][tail]);
+    assert!((0.8..1.1).contains(&gain), "tail gain {}", gain);
+    assert!(output.iter().all(|s| s.abs() < 2.0), "overlap-add edge spike");
+}
@@ -0,0 +1,47 @@
+//! Fixtures shared by the integration tests
+//!
+//! Thin wrappers over `testsignal` at the levels the tests mix them at, so
+//! every test draws its material from the same seeded generators. Each test
+//! crate includes this with `mod common;` and uses only part of it.
+
+#![allow(dead_code)]
+
+use bg_noise_reduction_core::testsignal::{self, NoiseColor};
+
+pub use bg_noise_reduction_core::testsignal::rms;
+
+pub const SAMPLE_RATE: u32 = 16000;
+
+/// White noise at `level` RMS, reproducible from `seed`
+pub fn noise(seed: u64, len: usize, level: f32) -> Vec<f32> {
+    scaled(testsignal::noise(NoiseColor::White, len, seed), level)
+}
+
+/// Sine at `frequency_hz` with peak `amplitude`, at SAMPLE_RATE
+pub fn tone(len: usize, frequency_hz: f32, amplitude: f32) -> Vec<f32> {
+    tone_at(SAMPLE_RATE, len, frequency_hz, amplitude)
+}
+
+/// Sine at `frequency_hz` with peak `amplitude`, for tests that need another sample rate
+pub fn tone_at(sample_rate: u32, len: usize, frequency_hz: f32, amplitude: f32) -> Vec<f32> {
+    scaled(testsignal::tone(len, sample_rate, frequency_hz), amplitude)
+}
+
+/// Mains hum at `fundamental_hz` with `harmonics` harmonics falling off as 1/k, at `level` RMS
+pub fn hum(len: usize, fundamental_hz: f32, harmonics: usize, level: f32) -> Vec<f32> {
+    scaled(testsignal::hum(len, SAMPLE_RATE, fundamental_hz, harmonics), level)
+}
+
+/// Speech-like syllables and pauses at `level` RMS, reproducible from `seed`
+pub fn speech(seed: u64, len: usize, level: f32) -> Vec<f32> {
+    scaled(testsignal::speech_like(len, SAMPLE_RATE, seed), level)
+}
+
+fn scaled(samples: Vec<f32>, gain: f32) -> Vec<f32> {
+    samples.into_iter().map(|s| gain * s).collect()
+}
+
+/// Mean square of a signal
+pub fn power(samples: &[f32]) -> f32 {
+    rms(samples).powi(2)
+}
@@ -0,0 +1,99 @@
+// Configuration serialization and validation tests
+// Run with: cargo test -p bg-noise-reduction-core --features serde --test config
+
+use bg_noise_reduction_core::{
+    AecConfig, Algorithm, ChannelMode, FilterSpec, GateConfig, GateKey, LimiterConfig, LoudnessTarget, NoiseReductionConfig,
+    SpectralGateConfig,
+};
+
+fn configured() -> NoiseReductionConfig {
+    NoiseReductionConfig {
+        algorithm: Algorithm::SpectralGate(SpectralGateConfig::non_stationary()),
+        over_subtraction: 3.0,
+        channel_mode: ChannelMode::MidSide,
+        pre_filters: vec![FilterSpec::highpass(80.0)],
+        post_filters: vec!["peak:3000:2:1.4".parse().unwrap()],
+        gate: Some(GateConfig { ratio: f32::INFINITY, key: GateKey::Snr, ..GateConfig::default() }),
+        ..NoiseReductionConfig::default()
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_json_round_trip() {
+    let config = configured();
+    let json = serde_json::to_string(&config).unwrap();
+    assert!(json.contains(r#""ratio":"inf""#), "{}", json);
+    assert!(json.contains(r#""channel_mode":"mid-side""#), "{}", json);
+
+    let back: NoiseReductionConfig = serde_json::from_str(&json).unwrap();
+    assert_eq!(format!("{:?}", back), format!("{:?}", config));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_missing_fields_take_defaults() {
+    let config: NoiseReductionConfig = serde_json::from_str(r#"{"makeup_gain": 2.0, "gate": {"key": "vad"}}"#).unwrap();
+    assert_eq!(config.makeup_gain, 2.0);
+    assert_eq!(config.noise_frames, NoiseReductionConfig::default().noise_frames);
+    assert_eq!(config.gate, Some(GateConfig { key: GateKey::Vad, ..GateConfig::default() }));
+    assert_eq!(config.algorithm, Algorithm::SpectralSubtraction);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_rejects_unknown_fields_and_values() {
+    assert!(serde_json::from_str::<NoiseReductionConfig>(r#"{"makeup_gian": 2.0}"#).is_err());
+    assert!(serde_json::from_str::<NoiseReductionConfig>(r#"{"channel_mode": "surround"}"#).is_err());
+    assert!(serde_json::from_str::<NoiseReductionConfig>(r#"{"pre_filters": ["notch:50"]}"#).is_err());
+}
+
+#[test]
+fn test_validate() {
+    assert!(NoiseReductionConfig::default().validate().is_ok());
+    assert!(configured().validate().is_ok());
+
+    let invalid = [
+        NoiseReductionConfig { noise_frames: 0, ..NoiseReductionConfig::default() },
+        NoiseReductionConfig { spectral_floor: 1.5, ..NoiseReductionConfig::default() },
+        NoiseReductionConfig { over_subtraction: f32::NAN, ..NoiseReductionConfig::default() },
+        NoiseReductionConfig { gate: Some(GateConfig { ratio: 0.5, ..GateConfig::default() }), ..NoiseReductionConfig::default() },
+    ];
+    for config in invalid {
+        assert!(config.validate().is_err(), "{:?} passed validation", config);
+    }
+}
+
+#[test]
+fn test_validate_stage_settings() {
+    let defaults = NoiseReductionConfig::default;
+    let loudness = |integrated_lufs, true_peak_ceiling| NoiseReductionConfig {
+        loudness: Some(LoudnessTarget { integrated_lufs, true_peak_ceiling }),
+        ..defaults()
+    };
+    let limiter = |limiter| NoiseReductionConfig { limiter: Some(limiter), ..defaults() };
+    let gate = |gate| NoiseReductionConfig { gate: Some(gate), ..defaults() };
+    let gating = |gating| NoiseReductionConfig { algorithm: Algorithm::SpectralGate(gating), ..defaults() };
+    let echo = |echo_cancellation| NoiseReductionConfig { echo_cancellation, ..defaults() };
+
+    assert!(loudness(-16.0, -1.5).validate().is_ok());
+    let invalid = [
+        loudness(100.0, -1.0),
+        loudness(f32::NAN, -1.0),
+        loudness(-23.0, 50.0),
+        limiter(LimiterConfig { release_ms: f32::NAN, ..LimiterConfig::default() }),
+        limiter(LimiterConfig { lookahead_ms: f32::NAN, ..LimiterConfig::default() }),
+        limiter(LimiterConfig { release_ms: -1.0, ..LimiterConfig::default() }),
+        gate(GateConfig { threshold_db: f32::NAN, ..GateConfig::default() }),
+        gate(GateConfig { attack_ms: -5.0, ..GateConfig::default() }),
+        gate(GateConfig { release_ms: f32::NAN, ..GateConfig::default() }),
+        gating(SpectralGateConfig { n_std_thresh: f32::NAN, ..SpectralGateConfig::default() }),
+        gating(SpectralGateConfig { time_constant_s: 0.0, ..SpectralGateConfig::non_stationary() }),
+        gating(SpectralGateConfig { sigmoid_slope_nonstationary: f32::INFINITY, ..SpectralGateConfig::non_stationary() }),
+        echo(AecConfig { double_talk_threshold: f32::NAN, ..AecConfig::default() }),
+        echo(AecConfig { residual_suppression: -1.0, ..AecConfig::default() }),
+    ];
+    for config in invalid {
+        assert!(config.validate().is_err(), "{:?} passed validation", config);
+    }
+}
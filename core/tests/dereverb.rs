@@ -0,0 +1,72 @@
+// Dereverberation tests on synthetic exponentially decaying tails
+// Run with: cargo test -p bg-noise-reduction-core --test dereverb
+
+mod common;
+
+use bg_noise_reduction_core::dereverb::{self, DereverbConfig};
+use bg_noise_reduction_core::{AudioProcessor, NoiseReductionConfig};
+use common::{noise, tone, SAMPLE_RATE};
+
+const T60: f32 = 0.6;
+
+// 300 ms noise bursts every second, each followed by an exponential decay
+// with the given T60, like a dry excitation in a reverberant room
+fn reverberant_bursts() -> Vec<f32> {
+    let decay_rate = 3.0 * std::f32::consts::LN_10 / T60;
+    noise(987654321, SAMPLE_RATE as usize * 5, 0.2)
+        .iter()
+        .enumerate()
+        .map(|(i, n)| {
+            let t = (i as f32 / SAMPLE_RATE as f32) % 1.0;
+            let envelope = if t < 0.3 { 1.0 } else { (-decay_rate * (t - 0.3)).exp() };
+            n * envelope
+        })
+        .collect()
+}
+
+fn energy(samples: &[f32], from: f32, to: f32) -> f32 {
+    (1..4)
+        .map(|second| {
+            let start = ((second as f32 + from) * SAMPLE_RATE as f32) as usize;
+            let end = ((second as f32 + to) * SAMPLE_RATE as f32) as usize;
+            samples[start..end].iter().map(|s| s * s).sum::<f32>()
+        })
+        .sum()
+}
+
+#[test]
+fn test_estimate_t60_from_free_decays() {
+    let t60 = dereverb::estimate_t60(&reverberant_bursts(), SAMPLE_RATE).expect("no decays found");
+    assert!((t60 - T60).abs() < 0.2, "estimated {} s", t60);
+}
+
+#[test]
+fn test_estimate_t60_without_decays() {
+    let steady = tone(SAMPLE_RATE as usize, 250.0, 0.3);
+    assert_eq!(dereverb::estimate_t60(&steady, SAMPLE_RATE), None);
+}
+
+#[test]
+fn test_dereverb_suppresses_tail() {
+    let input = reverberant_bursts();
+    let base = NoiseReductionConfig {
+        spectral_floor: 1.0,
+        makeup_gain: 1.0,
+        sample_rate: SAMPLE_RATE,
+        ..NoiseReductionConfig::default()
+    };
+    let dereverbed = NoiseReductionConfig {
+        dereverb: Some(DereverbConfig { t60: Some(T60), ..DereverbConfig::default() }),
+        ..base.clone()
+    };
+
+    let mut processor = AudioProcessor::new(2048);
+    let plain = processor.process(&input, &base);
+    let processed = processor.process(&input, &dereverbed);
+
+    // Late tail loses at least 6 dB, the direct part at most 3 dB
+    let tail_ratio = energy(&processed, 0.45, 0.9) / energy(&plain, 0.45, 0.9);
+    let direct_ratio = energy(&processed, 0.05, 0.25) / energy(&plain, 0.05, 0.25);
+    assert!(tail_ratio < 0.25, "tail ratio {}", tail_ratio);
+    assert!(direct_ratio > 0.5, "direct ratio {}", direct_ratio);
+}
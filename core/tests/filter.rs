@@ -0,0 +1,69 @@
+// Biquad filter and filter bank tests
+// Run with: cargo test -p bg-noise-reduction-core --test filter
+
+mod common;
+
+use bg_noise_reduction_core::filter::{self, FilterBank, FilterKind, FilterSpec};
+use bg_noise_reduction_core::NoiseReductionConfig;
+use common::{rms, tone_at};
+
+const SAMPLE_RATE: u32 = 48000;
+
+// Steady-state gain in dB of `bank` at `frequency`
+fn response_db(mut bank: FilterBank, frequency: f32) -> f32 {
+    let mut samples = tone_at(SAMPLE_RATE, SAMPLE_RATE as usize, frequency, 1.0);
+    bank.process(&mut samples);
+    20.0 * (rms(&samples[samples.len() / 2..]) * 2f32.sqrt()).log10()
+}
+
+#[test]
+fn test_parse_filter_specs() {
+    let spec: FilterSpec = "hp:80".parse().unwrap();
+    assert_eq!(spec, FilterSpec::highpass(80.0));
+
+    let spec: FilterSpec = "peak:3000:2.5:1.4".parse().unwrap();
+    assert_eq!(spec.kind, FilterKind::Peaking);
+    assert_eq!((spec.frequency, spec.gain_db, spec.q), (3000.0, 2.5, 1.4));
+    assert_eq!(spec.to_string().parse::<FilterSpec>().unwrap(), spec);
+
+    assert!("lowshelf:200".parse::<FilterSpec>().is_err());
+    assert!("notch:50".parse::<FilterSpec>().is_err());
+    assert!("hp:80:0.7:1".parse::<FilterSpec>().is_err());
+}
+
+#[test]
+fn test_reject_non_finite_filters() {
+    for spec in ["hp:NaN", "hp:inf", "lp:8000:nan", "peak:3000:inf:1", "lowshelf:200:-3:-1", "hp:0", "highshelf:4000:1e30"] {
+        assert!(spec.parse::<FilterSpec>().is_err(), "{} parsed", spec);
+    }
+
+    // --highpass builds the spec directly, so validation catches it
+    let config = NoiseReductionConfig { pre_filters: vec![FilterSpec::highpass(f32::NAN)], ..NoiseReductionConfig::default() };
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn test_highpass_removes_rumble() {
+    let bank = || FilterBank::new(SAMPLE_RATE).highpass(80.0, filter::DEFAULT_Q);
+    assert!(response_db(bank(), 20.0) < -20.0);
+    assert!((response_db(bank(), 80.0) - -3.0).abs() < 0.5);
+    assert!(response_db(bank(), 1000.0).abs() < 0.1);
+}
+
+#[test]
+fn test_peaking_and_shelf_gains() {
+    let peak = FilterBank::new(SAMPLE_RATE).peaking(1000.0, 6.0, 1.0);
+    assert!((response_db(peak, 1000.0) - 6.0).abs() < 0.2);
+
+    let shelf = FilterBank::new(SAMPLE_RATE).high_shelf(4000.0, -6.0, filter::DEFAULT_Q);
+    assert!((response_db(shelf.clone(), 16000.0) - -6.0).abs() < 0.5);
+    assert!(response_db(shelf, 200.0).abs() < 0.2);
+}
+
+#[test]
+fn test_remove_dc_offset() {
+    let mut samples: Vec<f32> = tone_at(SAMPLE_RATE, 1000, 750.0, 0.1).iter().map(|s| 0.25 + s).collect();
+    filter::remove_dc_offset(&mut samples);
+    let mean = samples.iter().sum::<f32>() / samples.len() as f32;
+    assert!(mean.abs() < 1e-4);
+}
@@ -0,0 +1,72 @@
+// Noise gate / downward expander tests
+// Run with: cargo test -p bg-noise-reduction-core --test gate
+
+mod common;
+
+use bg_noise_reduction_core::{AudioProcessor, GateConfig, GateKey, GateMode, NoiseReductionConfig};
+use common::{noise, rms, tone, SAMPLE_RATE};
+
+// One second of hiss, one second of tone + hiss, one second of hiss
+fn tone_between_pauses() -> Vec<f32> {
+    let len = SAMPLE_RATE as usize * 3;
+    let on = SAMPLE_RATE as usize..2 * SAMPLE_RATE as usize;
+    noise(12345, len, 0.012)
+        .iter()
+        .zip(tone(len, 440.0, 0.3))
+        .enumerate()
+        .map(|(i, (n, t))| if on.contains(&i) { n + t } else { *n })
+        .collect()
+}
+
+fn process(gate: Option<GateConfig>) -> Vec<f32> {
+    let config = NoiseReductionConfig {
+        makeup_gain: 1.0,
+        gate,
+        sample_rate: SAMPLE_RATE,
+        ..NoiseReductionConfig::default()
+    };
+    AudioProcessor::new(2048).process(&tone_between_pauses(), &config)
+}
+
+fn assert_gated(gate: GateConfig) {
+    let plain = process(None);
+    let gated = process(Some(gate));
+    let second = SAMPLE_RATE as usize;
+
+    // The pause after the tone loses at least 20 dB more residual
+    let pause = 2 * second + second / 2..3 * second - 4096;
+    assert!(rms(&gated[pause.clone()]) < rms(&plain[pause]) * 0.1, "{:?}", gate);
+
+    // The tone itself passes through within 1 dB
+    let tone = second + second / 4..2 * second - second / 4;
+    let ratio = rms(&gated[tone.clone()]) / rms(&plain[tone]);
+    assert!(ratio > 0.89 && ratio <= 1.0001, "{:?}: tone ratio {}", gate, ratio);
+}
+
+#[test]
+fn test_broadband_level_gate() {
+    assert_gated(GateConfig {
+        threshold_db: -30.0,
+        ratio: f32::INFINITY,
+        ..GateConfig::default()
+    });
+}
+
+#[test]
+fn test_per_band_snr_gate() {
+    assert_gated(GateConfig {
+        threshold_db: 6.0,
+        ratio: f32::INFINITY,
+        mode: GateMode::PerBand,
+        key: GateKey::Snr,
+        ..GateConfig::default()
+    });
+}
+
+#[test]
+fn test_broadband_vad_gate() {
+    assert_gated(GateConfig {
+        key: GateKey::Vad,
+        ..GateConfig::default()
+    });
+}
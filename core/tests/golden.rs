@@ -0,0 +1,241 @@
+// Golden-output regression tests
+// Run with: cargo test -p bg-noise-reduction-core --test golden
+//
+// Every algorithm mode and preset is run over the committed synthetic inputs in
+// tests/golden/inputs, and the output is compared with the stored golden in
+// tests/golden/expected. The level envelope and octave band levels must match
+// within the tolerances below; the quality metrics against the clean reference
+// act as thresholds and may only get better, or worse by less than their
+// tolerance. After an intentional change to the processing, regenerate the
+// goldens with
+//
+//     cargo test -p bg-noise-reduction-core --test golden -- --ignored
+//
+// and review the diff of tests/golden/expected before committing it.
+//
+// The inputs were made with the generate command:
+//
+//     generate speech --seed 1 --duration 1.5 --lead-in 1 --noise pink --snr 5 --clean speech_clean.wav speech_pink.wav
+//     generate speech --seed 1 --duration 1.5 --lead-in 1 --noise hum --snr 10 speech_hum.wav
+//     generate chirp --duration 1.5 --lead-in 1 --chirp-start 100 --chirp-end 4000 --noise brown --snr 10 --clean chirp_clean.wav chirp_brown.wav
+
+use bg_noise_reduction_core::metrics::{self, Metrics};
+use bg_noise_reduction_core::{AudioProcessor, NoiseReductionConfig, Preset, FRAME_SIZE};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// (noisy input, clean reference)
+const INPUTS: [(&str, &str); 3] = [("speech_pink", "speech_clean"), ("speech_hum", "speech_clean"), ("chirp_brown", "chirp_clean")];
+const MODES: [&str; 6] = ["light", "medium", "heavy", "extreme", "spectral-gate", "spectral-gate-nonstationary"];
+
+/// Samples per point of the level envelope
+const BLOCK: usize = 1024;
+/// Levels below this are compared as this, so near-silence does not flake
+const LEVEL_FLOOR_DB: f64 = -80.0;
+const ENVELOPE_TOLERANCE_DB: f64 = 0.5;
+const BAND_TOLERANCE_DB: f64 = 0.25;
+const SNR_TOLERANCE_DB: f64 = 0.25;
+const LSD_TOLERANCE_DB: f64 = 0.5;
+const STOI_TOLERANCE: f64 = 0.01;
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden")
+}
+
+/// Read a 16-bit PCM mono WAV file, as written by the generate command
+fn read_wav(path: &Path) -> (Vec<f32>, u32) {
+    let bytes = std::fs::read(path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+    assert_eq!(&bytes[..4], b"RIFF", "{} is not a WAV file", path.display());
+    let (mut offset, mut sample_rate, mut samples) = (12, 0, Vec::new());
+    while offset + 8 <= bytes.len() {
+        let id = &bytes[offset..offset + 4];
+        let size = u32::from_le_bytes(bytes[offset + 4..offset + 8].try_into().unwrap()) as usize;
+        let body = &bytes[offset + 8..(offset + 8 + size).min(bytes.len())];
+        match id {
+            b"fmt " => {
+                assert_eq!(u16::from_le_bytes([body[2], body[3]]), 1, "{} is not mono", path.display());
+                assert_eq!(u16::from_le_bytes([body[14], body[15]]), 16, "{} is not 16-bit", path.display());
+                sample_rate = u32::from_le_bytes(body[4..8].try_into().unwrap());
+            }
+            b"data" => {
+                samples = body.chunks_exact(2).map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / i16::MAX as f32).collect();
+            }
+            _ => {}
+        }
+        offset += 8 + size + size % 2;
+    }
+    (samples, sample_rate)
+}
+
+fn config(mode: &str, sample_rate: u32) -> NoiseReductionConfig {
+    let mut config = match mode.parse::<Preset>() {
+        Ok(preset) => NoiseReductionConfig::from_preset(preset),
+        Err(_) => NoiseReductionConfig { algorithm: mode.parse().unwrap(), ..NoiseReductionConfig::default() },
+    };
+    config.sample_rate = sample_rate;
+    config
+}
+
+fn level_db(samples: &[f32]) -> f64 {
+    let power = samples.iter().map(|&s| (s as f64).powi(2)).sum::<f64>() / samples.len().max(1) as f64;
+    (10.0 * power.max(1e-20).log10()).max(LEVEL_FLOOR_DB)
+}
+
+/// Level of each octave band, centred on 125 Hz and up, below Nyquist, from the averaged STFT power
+fn band_levels_db(samples: &[f32], sample_rate: u32) -> Vec<f64> {
+    let frames = AudioProcessor::new(FRAME_SIZE).stft_magnitudes(samples);
+    let bin_hz = sample_rate as f64 / FRAME_SIZE as f64;
+    let mut bands = Vec::new();
+    let mut low = 88.0;
+    while low * 2.0 <= sample_rate as f64 / 2.0 {
+        let bins = (low / bin_hz).round() as usize..(2.0 * low / bin_hz).round() as usize;
+        let power: f64 = frames.iter().map(|frame| frame[bins.clone()].iter().map(|&m| (m as f64).powi(2)).sum::<f64>()).sum();
+        bands.push((10.0 * (power / frames.len().max(1) as f64).max(1e-20).log10()).max(LEVEL_FLOOR_DB));
+        low *= 2.0;
+    }
+    bands
+}
+
+fn round(value: f64) -> f64 {
+    (value * 1000.0).round() / 1000.0
+}
+
+/// One mode run over one input
+struct Case {
+    name: String,
+    output: Vec<f32>,
+    input_len: usize,
+    sample_rate: u32,
+    metrics: Metrics,
+}
+
+impl Case {
+    fn path(&self) -> PathBuf {
+        golden_dir().join("expected").join(format!("{}.json", self.name))
+    }
+
+    /// Golden data for this output, rounded so the files diff cleanly
+    fn summary(&self) -> Value {
+        let optional = |value: Option<f32>| value.map(|v| round(v as f64));
+        json!({
+            "samples": self.output.len(),
+            "envelope_db": self.output.chunks(BLOCK).map(|block| round(level_db(block))).collect::<Vec<_>>(),
+            "bands_db": band_levels_db(&self.output, self.sample_rate).into_iter().map(round).collect::<Vec<_>>(),
+            "metrics": {
+                "snr_db": round(self.metrics.snr_db as f64),
+                "segmental_snr_db": round(self.metrics.segmental_snr_db as f64),
+                "log_spectral_distance_db": round(self.metrics.log_spectral_distance_db as f64),
+                "stoi": optional(self.metrics.stoi),
+                "estoi": optional(self.metrics.estoi),
+            },
+        })
+    }
+}
+
+/// All cases, processed once and shared between the tests
+fn cases() -> &'static [Case] {
+    static CASES: OnceLock<Vec<Case>> = OnceLock::new();
+    CASES.get_or_init(|| {
+        let mut cases = Vec::new();
+        for (input, clean) in INPUTS {
+            let (noisy, sample_rate) = read_wav(&golden_dir().join("inputs").join(format!("{}.wav", input)));
+            let (reference, _) = read_wav(&golden_dir().join("inputs").join(format!("{}.wav", clean)));
+            for mode in MODES {
+                let output = AudioProcessor::new(FRAME_SIZE).process(&noisy, &config(mode, sample_rate));
+                let metrics = metrics::evaluate(&reference, &output, sample_rate);
+                cases.push(Case { name: format!("{}.{}", input, mode), output, input_len: noisy.len(), sample_rate, metrics });
+            }
+        }
+        cases
+    })
+}
+
+/// Differences between a golden array and the actual one beyond `tolerance`
+fn compare_arrays(field: &str, expected: &Value, actual: &Value, tolerance: f64, failures: &mut Vec<String>) {
+    let (Some(expected), Some(actual)) = (expected.as_array(), actual.as_array()) else {
+        failures.push(format!("{}: missing", field));
+        return;
+    };
+    if expected.len() != actual.len() {
+        failures.push(format!("{}: {} values, golden has {}", field, actual.len(), expected.len()));
+        return;
+    }
+    for (i, (e, a)) in expected.iter().zip(actual).enumerate() {
+        let (e, a) = (e.as_f64().unwrap_or(f64::NAN), a.as_f64().unwrap_or(f64::NAN));
+        // False for NaN on either side, so missing values fail too
+        let within = (e - a).abs() <= tolerance;
+        if !within {
+            failures.push(format!("{}[{}]: {:.3}, golden {:.3} (tolerance {})", field, i, a, e, tolerance));
+        }
+    }
+}
+
+fn compare_case(expected: &Value, actual: &Value) -> Vec<String> {
+    let mut failures = Vec::new();
+    if expected["samples"] != actual["samples"] {
+        failures.push(format!("samples: {}, golden {}", actual["samples"], expected["samples"]));
+    }
+    compare_arrays("envelope_db", &expected["envelope_db"], &actual["envelope_db"], ENVELOPE_TOLERANCE_DB, &mut failures);
+    compare_arrays("bands_db", &expected["bands_db"], &actual["bands_db"], BAND_TOLERANCE_DB, &mut failures);
+    // (metric, tolerance, whether higher is better)
+    for (metric, tolerance, higher_is_better) in [
+        ("snr_db", SNR_TOLERANCE_DB, true),
+        ("segmental_snr_db", SNR_TOLERANCE_DB, true),
+        ("log_spectral_distance_db", LSD_TOLERANCE_DB, false),
+        ("stoi", STOI_TOLERANCE, true),
+        ("estoi", STOI_TOLERANCE, true),
+    ] {
+        let (e, a) = (&expected["metrics"][metric], &actual["metrics"][metric]);
+        let passes = match (e.as_f64(), a.as_f64()) {
+            (Some(e), Some(a)) if higher_is_better => a >= e - tolerance,
+            (Some(e), Some(a)) => a <= e + tolerance,
+            _ => e == a,
+        };
+        if !passes {
+            failures.push(format!("{}: {}, golden {} (tolerance {})", metric, a, e, tolerance));
+        }
+    }
+    failures
+}
+
+#[test]
+fn test_outputs_match_golden() {
+    let mut failures = Vec::new();
+    for case in cases() {
+        let text = match std::fs::read_to_string(case.path()) {
+            Ok(text) => text,
+            Err(_) => {
+                failures.push(format!("{}: no golden at {}", case.name, case.path().display()));
+                continue;
+            }
+        };
+        let expected: Value = serde_json::from_str(&text).unwrap_or_else(|e| panic!("{}: {}", case.path().display(), e));
+        failures.extend(compare_case(&expected, &case.summary()).into_iter().map(|failure| format!("{}: {}", case.name, failure)));
+    }
+    assert!(
+        failures.is_empty(),
+        "outputs differ from the goldens:\n  {}\nIf the change is intended, regenerate them with\n  cargo test -p bg-noise-reduction-core --test golden -- --ignored",
+        failures.join("\n  ")
+    );
+}
+
+#[test]
+fn test_outputs_are_sane() {
+    for case in cases() {
+        assert_eq!(case.output.len(), case.input_len, "{}", case.name);
+        assert!(case.output.iter().all(|s| s.is_finite()), "{}: non-finite output", case.name);
+    }
+}
+
+/// Rewrite every golden from the current outputs
+#[test]
+#[ignore = "regenerates the goldens; run with -- --ignored after an intended change"]
+fn regenerate_golden_outputs() {
+    std::fs::create_dir_all(golden_dir().join("expected")).unwrap();
+    for case in cases() {
+        let text = serde_json::to_string_pretty(&case.summary()).unwrap() + "\n";
+        std::fs::write(case.path(), text).unwrap();
+        println!("wrote {}", case.path().display());
+    }
+}
@@ -0,0 +1,58 @@
+// Output limiter tests
+// Run with: cargo test -p bg-noise-reduction-core --test limiter
+
+mod common;
+
+use bg_noise_reduction_core::limiter::{self, LimiterConfig};
+use bg_noise_reduction_core::loudness;
+use common::tone_at;
+
+#[test]
+fn test_limiter_holds_true_peak_under_ceiling() {
+    let mut samples = tone_at(44100, 44100, 997.0, 1.8);
+    let config = LimiterConfig::default();
+    limiter::limit(&mut samples, 44100, &config);
+
+    let peak = loudness::true_peak(&samples, 44100);
+    assert!(peak <= config.ceiling + 0.1, "got {} dBTP", peak);
+}
+
+#[test]
+fn test_limiter_leaves_quiet_signal_untouched() {
+    let original = tone_at(48000, 24000, 440.0, 0.25);
+    let mut samples = original.clone();
+    limiter::limit(&mut samples, 48000, &LimiterConfig::default());
+
+    assert_eq!(samples, original);
+}
+
+#[test]
+fn test_limiter_ramps_before_transient() {
+    // Quiet tone with a loud burst in the middle: gain must already be reduced
+    // at the first burst sample and recover afterwards
+    let sample_rate = 48000;
+    let mut samples = tone_at(sample_rate, sample_rate as usize, 220.0, 0.2);
+    let burst_start = 24000;
+    for sample in &mut samples[burst_start..burst_start + 480] {
+        *sample *= 8.0;
+    }
+    let original = samples.clone();
+    limiter::limit(&mut samples, sample_rate, &LimiterConfig::default());
+
+    let ceiling = 10f32.powf(-1.0 / 20.0);
+    assert!(samples.iter().all(|s| s.abs() <= ceiling));
+    let before = burst_start - 10;
+    assert!(samples[before].abs() < original[before].abs() || original[before] == 0.0);
+    let recovered = samples.len() - 1;
+    assert!((samples[recovered] - original[recovered]).abs() < 0.01);
+}
+
+#[test]
+fn test_limiter_falls_back_to_default_ceiling() {
+    // A NaN ceiling used to panic in the final clamp
+    let mut samples = tone_at(44100, 22050, 997.0, 1.8);
+    limiter::limit(&mut samples, 44100, &LimiterConfig { ceiling: f32::NAN, ..LimiterConfig::default() });
+
+    let peak = loudness::true_peak(&samples, 44100);
+    assert!(peak <= LimiterConfig::default().ceiling + 0.1, "got {} dBTP", peak);
+}
@@ -0,0 +1,80 @@
+// Loudness measurement tests against the BS.1770 reference behaviour
+// Run with: cargo test -p bg-noise-reduction-core --test loudness
+
+mod common;
+
+use bg_noise_reduction_core::loudness;
+use bg_noise_reduction_core::{AudioProcessor, LoudnessTarget, NoiseReductionConfig};
+use common::tone_at;
+
+#[test]
+fn test_integrated_loudness_of_reference_sine() {
+    // A 1 kHz sine at -20 dBFS reads -23 LUFS (0 dBFS sine reads -3.01 LUFS)
+    let samples = tone_at(48000, 5 * 48000, 1000.0, 0.1);
+    let lufs = loudness::integrated_loudness(&samples, 48000);
+    assert!((lufs - -23.0).abs() < 0.1, "got {} LUFS", lufs);
+
+    let lufs = loudness::integrated_loudness(&tone_at(44100, 5 * 44100, 1000.0, 0.1), 44100);
+    assert!((lufs - -23.0).abs() < 0.1, "got {} LUFS at 44.1 kHz", lufs);
+}
+
+#[test]
+fn test_silence_has_no_integrated_loudness() {
+    let samples = vec![0.0f32; 48000];
+    assert_eq!(loudness::integrated_loudness(&samples, 48000), f32::NEG_INFINITY);
+    assert_eq!(loudness::normalization_gain(&samples, 48000, &LoudnessTarget::default()), 1.0);
+}
+
+#[test]
+fn test_true_peak_finds_inter_sample_peak() {
+    // At fs/4 with a 45 degree phase every sample lands 3 dB below the true peak.
+    // Two fs/4 tones one sample (90 degrees) apart add up to one at 45 degrees.
+    let tone = tone_at(48000, 48001, 12000.0, 0.5 / 2f32.sqrt());
+    let samples: Vec<f32> = tone.windows(2).map(|pair| pair[0] + pair[1]).collect();
+    let sample_peak = 20.0 * samples.iter().fold(0.0f32, |p, s| p.max(s.abs())).log10();
+    let true_peak = loudness::true_peak(&samples, 48000);
+    assert!(sample_peak < -8.5);
+    assert!((true_peak - -6.02).abs() < 0.5, "got {} dBTP", true_peak);
+}
+
+#[test]
+fn test_loudness_range_of_level_steps() {
+    // 10 s at -30 dBFS followed by 10 s at -20 dBFS spans roughly 10 LU
+    let mut samples = tone_at(48000, 10 * 48000, 1000.0, 10f32.powf(-30.0 / 20.0));
+    samples.extend(tone_at(48000, 10 * 48000, 1000.0, 0.1));
+    let range = loudness::loudness_range(&samples, 48000);
+    assert!((range - 10.0).abs() < 1.0, "got {} LU", range);
+}
+
+#[test]
+fn test_process_normalizes_to_target() {
+    let samples = tone_at(48000, 5 * 48000, 1000.0, 0.05);
+    let config = NoiseReductionConfig {
+        spectral_floor: 1.0,
+        sample_rate: 48000,
+        loudness: Some(LoudnessTarget { integrated_lufs: -16.0, true_peak_ceiling: -1.0 }),
+        ..NoiseReductionConfig::default()
+    };
+    let output = AudioProcessor::new(2048).process(&samples, &config);
+
+    let stats = loudness::measure(&output, 48000);
+    assert!((stats.integrated_lufs - -16.0).abs() < 0.2, "got {} LUFS", stats.integrated_lufs);
+    assert!(stats.true_peak_dbtp <= -0.9);
+}
+
+#[test]
+fn test_channels_of_unequal_length() {
+    // A shorter channel measures as if padded with silence, in either order
+    let long = tone_at(48000, 8 * 48000, 1000.0, 0.1);
+    let short = tone_at(48000, 3 * 48000, 500.0, 0.2);
+    let mut padded = short.clone();
+    padded.resize(long.len(), 0.0);
+    let expected = loudness::measure_channels(&[&long, &padded], 48000);
+
+    for channels in [[&long[..], &short[..]], [&short[..], &long[..]]] {
+        let stats = loudness::measure_channels(&channels, 48000);
+        assert!((stats.integrated_lufs - expected.integrated_lufs).abs() < 1e-3, "got {} LUFS", stats.integrated_lufs);
+        assert!((stats.loudness_range - expected.loudness_range).abs() < 1e-3, "got {} LU", stats.loudness_range);
+        assert_eq!(stats.true_peak_dbtp, expected.true_peak_dbtp);
+    }
+}
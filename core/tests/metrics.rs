@@ -0,0 +1,63 @@
+// Objective quality metrics tests
+// Run with: cargo test -p bg-noise-reduction-core --test metrics
+
+mod common;
+
+use bg_noise_reduction_core::{metrics, testsignal};
+use common::{noise, speech, SAMPLE_RATE};
+
+const LEN: usize = SAMPLE_RATE as usize * 3;
+
+/// `clean` plus white noise at `snr_db`
+fn mix(clean: &[f32], snr_db: f32, seed: u64) -> Vec<f32> {
+    testsignal::mix_at_snr(clean, &noise(seed, clean.len(), 1.0), snr_db)
+}
+
+#[test]
+fn test_identical_signals() {
+    let clean = speech(1, LEN, 0.05);
+    let scores = metrics::evaluate(&clean, &clean, SAMPLE_RATE);
+    assert!(scores.snr_db >= 99.0, "SNR {}", scores.snr_db);
+    assert_eq!(scores.segmental_snr_db, 35.0);
+    assert!(scores.log_spectral_distance_db < 1e-3);
+    assert!(scores.stoi.unwrap() > 0.999, "STOI {:?}", scores.stoi);
+    assert!(scores.estoi.unwrap() > 0.999, "ESTOI {:?}", scores.estoi);
+    assert_eq!((scores.delay, scores.gain), (0, 1.0));
+}
+
+#[test]
+fn test_snr_of_known_mixture() {
+    let clean = speech(1, LEN, 0.05);
+    let noisy = mix(&clean, 10.0, 1);
+    assert!((metrics::snr(&clean, &noisy) - 10.0).abs() < 0.01);
+    // Local SNR swings with the syllables but averages out near the global one
+    let segmental = metrics::segmental_snr(&clean, &noisy, SAMPLE_RATE);
+    assert!((segmental - 10.0).abs() < 3.0, "segmental SNR {:.2}", segmental);
+    assert!(metrics::log_spectral_distance(&clean, &noisy) > 1.0);
+}
+
+#[test]
+fn test_intelligibility_falls_with_noise() {
+    let clean = speech(1, LEN, 0.05);
+    let mut last = (1.0f32, 1.0f32);
+    for snr in [20.0, 5.0, -10.0] {
+        let noisy = mix(&clean, snr, 2);
+        let stoi = metrics::stoi(&clean, &noisy, SAMPLE_RATE).unwrap();
+        let estoi = metrics::estoi(&clean, &noisy, SAMPLE_RATE).unwrap();
+        assert!(stoi < last.0 && estoi < last.1, "STOI {:.3}, ESTOI {:.3} at {} dB", stoi, estoi, snr);
+        last = (stoi, estoi);
+    }
+    // Too short to fill one 384 ms segment
+    assert_eq!(metrics::stoi(&clean[..3000], &clean[..3000], SAMPLE_RATE), None);
+}
+
+#[test]
+fn test_evaluate_compensates_delay_and_gain() {
+    let clean = speech(1, LEN, 0.05);
+    let noisy = mix(&clean, 15.0, 3);
+    let processed: Vec<f32> = (0..LEN).map(|i| if i < 64 { 0.0 } else { 0.5 * noisy[i - 64] }).collect();
+    let scores = metrics::evaluate(&clean, &processed, SAMPLE_RATE);
+    assert_eq!(scores.delay, 64);
+    assert!((scores.gain - 2.0).abs() < 0.1, "gain {:.3}", scores.gain);
+    assert!((scores.snr_db - 15.0).abs() < 0.5, "SNR {:.2}", scores.snr_db);
+}
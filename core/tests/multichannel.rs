@@ -0,0 +1,105 @@
+// Multichannel (linked stereo / mid-side) tests
+// Run with: cargo test -p bg-noise-reduction-core --test multichannel
+
+mod common;
+
+use bg_noise_reduction_core::{loudness, multichannel, AudioProcessor, ChannelMode, LinkSource, NoiseReductionConfig};
+use common::{noise, tone, SAMPLE_RATE};
+
+// Source panned left (right at half level) from 1 s on, over uncorrelated hiss in each channel
+fn panned_tone() -> Vec<Vec<f32>> {
+    let len = SAMPLE_RATE as usize * 4;
+    let tone: Vec<f32> =
+        tone(len, 440.0, 0.1).iter().enumerate().map(|(i, &s)| if i >= SAMPLE_RATE as usize { s } else { 0.0 }).collect();
+    let left = noise(1, len, 0.02).iter().zip(&tone).map(|(n, s)| n + s).collect();
+    let right = noise(2, len, 0.02).iter().zip(&tone).map(|(n, s)| n + 0.5 * s).collect();
+    vec![left, right]
+}
+
+fn config(channel_mode: ChannelMode) -> NoiseReductionConfig {
+    NoiseReductionConfig {
+        channel_mode,
+        makeup_gain: 1.0,
+        sample_rate: SAMPLE_RATE,
+        ..NoiseReductionConfig::default()
+    }
+}
+
+// Standard deviation of the right/left level difference over 32 ms blocks, in dB
+fn level_difference_spread(channels: &[Vec<f32>]) -> f32 {
+    let block = 512;
+    let start = SAMPLE_RATE as usize + block;
+    let differences: Vec<f32> = channels[0][start..]
+        .chunks_exact(block)
+        .zip(channels[1][start..].chunks_exact(block))
+        .map(|(left, right)| {
+            let power = |x: &[f32]| x.iter().map(|s| s * s).sum::<f32>();
+            10.0 * (power(right) / power(left)).log10()
+        })
+        .collect();
+    let mean = differences.iter().sum::<f32>() / differences.len() as f32;
+    (differences.iter().map(|d| (d - mean) * (d - mean)).sum::<f32>() / differences.len() as f32).sqrt()
+}
+
+#[test]
+fn test_single_channel_matches_process() {
+    let input = panned_tone().remove(0);
+    let config = config(ChannelMode::default());
+
+    let mono = AudioProcessor::new(2048).process(&input, &config);
+    let channels = AudioProcessor::new(2048).process_channels(&[input], &config);
+    assert_eq!(channels.len(), 1);
+    assert_eq!(mono, channels[0]);
+}
+
+#[test]
+fn test_linked_mode_keeps_stereo_image_steadier() {
+    let input = panned_tone();
+    let independent = AudioProcessor::new(2048).process_channels(&input, &config(ChannelMode::Independent));
+    let linked = AudioProcessor::new(2048).process_channels(&input, &config(ChannelMode::Linked(LinkSource::Max)));
+
+    let independent_spread = level_difference_spread(&independent);
+    let linked_spread = level_difference_spread(&linked);
+    assert!(
+        linked_spread < independent_spread,
+        "linked {} dB vs independent {} dB",
+        linked_spread,
+        independent_spread
+    );
+}
+
+#[test]
+fn test_linked_mode_applies_identical_gains() {
+    // Right is an exact scaled copy of left, so a shared mask must keep it one
+    let left = panned_tone().remove(0);
+    let right: Vec<f32> = left.iter().map(|s| 0.5 * s).collect();
+
+    for source in [LinkSource::Max, LinkSource::Mean, LinkSource::Mid] {
+        let output = AudioProcessor::new(2048).process_channels(&[left.clone(), right.clone()], &config(ChannelMode::Linked(source)));
+        let error = output[0].iter().zip(&output[1]).map(|(l, r)| (0.5 * l - r).abs()).fold(0.0f32, f32::max);
+        assert!(error < 1e-4, "{:?} max error {}", source, error);
+    }
+}
+
+#[test]
+fn test_mid_side_keeps_mono_content_centered() {
+    let mono = panned_tone().remove(0);
+    let output = AudioProcessor::new(2048).process_channels(&[mono.clone(), mono], &config(ChannelMode::MidSide));
+
+    let error = output[0].iter().zip(&output[1]).map(|(l, r)| (l - r).abs()).fold(0.0f32, f32::max);
+    assert!(error < 1e-6, "max error {}", error);
+}
+
+#[test]
+fn test_interleave_round_trip_and_channel_loudness() {
+    let channels = panned_tone();
+    let interleaved = multichannel::interleave(&channels);
+    assert_eq!(interleaved.len(), channels[0].len() * 2);
+    assert_eq!(multichannel::deinterleave(&interleaved, 2), channels);
+
+    // Two identical channels measure 3 LU louder than one
+    let left = &channels[0];
+    let mono = loudness::integrated_loudness(left, SAMPLE_RATE);
+    let stereo = loudness::measure_channels(&[left, left], SAMPLE_RATE).integrated_lufs;
+    assert!((stereo - mono - 3.01).abs() < 0.05, "mono {} stereo {}", mono, stereo);
+}
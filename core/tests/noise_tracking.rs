@@ -0,0 +1,75 @@
+// Sliding-window noise estimate tests
+// Run with: cargo test -p bg-noise-reduction-core --test noise_tracking
+
+mod common;
+
+use bg_noise_reduction_core::{AudioProcessor, NoiseReductionConfig, NoiseTrackingConfig, TrackingMethod};
+use common::{noise, tone, SAMPLE_RATE};
+
+// Hiss that steps up 12 dB at 3 s (HVAC switching on), with tone bursts at 1-2 s and 6-7 s
+fn tone_over_stepped_noise() -> Vec<f32> {
+    let len = SAMPLE_RATE as usize * 10;
+    noise(4242, len, 1.0)
+        .iter()
+        .zip(tone(len, 440.0, 0.3))
+        .enumerate()
+        .map(|(i, (n, s))| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let level = if t < 3.0 { 0.006 } else { 0.024 };
+            let on = (1.0..2.0).contains(&t) || (6.0..7.0).contains(&t);
+            level * n + if on { s } else { 0.0 }
+        })
+        .collect()
+}
+
+fn rms(samples: &[f32], from: f32, to: f32) -> f32 {
+    let range = (from * SAMPLE_RATE as f32) as usize..(to * SAMPLE_RATE as f32) as usize;
+    (samples[range.clone()].iter().map(|s| s * s).sum::<f32>() / range.len() as f32).sqrt()
+}
+
+fn reduce(input: &[f32], noise_tracking: Option<NoiseTrackingConfig>) -> Vec<f32> {
+    let config = NoiseReductionConfig {
+        noise_tracking,
+        makeup_gain: 1.0,
+        sample_rate: SAMPLE_RATE,
+        ..NoiseReductionConfig::default()
+    };
+    AudioProcessor::new(2048).process(input, &config)
+}
+
+#[test]
+fn test_tracking_adapts_to_louder_noise() {
+    let input = tone_over_stepped_noise();
+    let fixed = reduce(&input, None);
+
+    for method in [TrackingMethod::Percentile, TrackingMethod::Minimum] {
+        let tracked = reduce(&input, Some(NoiseTrackingConfig { method, ..NoiseTrackingConfig::default() }));
+
+        // Well after the step, once the window holds only the louder noise
+        let residual = rms(&tracked, 8.0, 9.5);
+        assert!(residual < rms(&fixed, 8.0, 9.5) * 0.5, "{} residual {}", method, residual);
+
+        let tone_ratio = rms(&tracked, 6.2, 6.8) / rms(&input, 6.2, 6.8);
+        assert!(tone_ratio > 0.9, "{} tone ratio {}", method, tone_ratio);
+    }
+}
+
+#[test]
+fn test_tracking_matches_fixed_profile_on_steady_noise() {
+    let input: Vec<f32> = tone_over_stepped_noise()[..SAMPLE_RATE as usize * 3].to_vec();
+    let fixed = reduce(&input, None);
+    let tracked = reduce(&input, Some(NoiseTrackingConfig::default()));
+
+    let ratio = rms(&tracked, 2.2, 2.9) / rms(&fixed, 2.2, 2.9);
+    assert!((0.7..1.4).contains(&ratio), "residual ratio {}", ratio);
+}
+
+#[test]
+fn test_shorter_window_adapts_sooner() {
+    let input = tone_over_stepped_noise();
+    let long = reduce(&input, Some(NoiseTrackingConfig { window_s: 4.0, ..NoiseTrackingConfig::default() }));
+    let short = reduce(&input, Some(NoiseTrackingConfig { window_s: 1.0, ..NoiseTrackingConfig::default() }));
+
+    // 1.5-2.5 s after the step only the short window has caught up
+    assert!(rms(&short, 4.5, 5.5) < rms(&long, 4.5, 5.5) * 0.7);
+}
@@ -0,0 +1,69 @@
+// Preset registry tests
+// Run with: cargo test -p bg-noise-reduction-core --test preset
+
+use bg_noise_reduction_core::{NoiseReductionConfig, Preset, PresetDefinition, PresetRegistry};
+
+#[test]
+fn test_builtin_presets() {
+    let medium = NoiseReductionConfig::from_preset(Preset::Medium);
+    let default = NoiseReductionConfig::default();
+    assert_eq!(medium.spectral_floor, default.spectral_floor);
+    assert_eq!(medium.over_subtraction, default.over_subtraction);
+    assert_eq!(medium.makeup_gain, default.makeup_gain);
+
+    let extreme = NoiseReductionConfig::from_preset(Preset::Extreme);
+    assert_eq!(extreme.over_subtraction, 4.0);
+    assert_eq!(extreme.spectral_floor, 0.02);
+
+    for preset in Preset::ALL {
+        assert_eq!(preset.to_string().parse::<Preset>(), Ok(preset));
+    }
+    assert!("loud".parse::<Preset>().is_err());
+}
+
+#[test]
+fn test_parse_user_preset() {
+    let text = "# Laptop fan\ndescription = \"Voice over fan\"\nover_subtraction = 2.5\nspectral_floor = 0.08 # keep some air\n";
+    let preset = PresetDefinition::parse("podcast", text).unwrap();
+
+    assert_eq!(preset.name, "podcast");
+    assert_eq!(preset.description, "Voice over fan");
+    assert_eq!(preset.over_subtraction, 2.5);
+    assert_eq!(preset.spectral_floor, 0.08);
+    // Unset keys keep the Medium values
+    assert_eq!(preset.makeup_gain, 1.5);
+    assert_eq!(preset.noise_frames, 10);
+
+    assert!(PresetDefinition::parse("bad", "over_subtraction = lots").is_err());
+    assert!(PresetDefinition::parse("bad", "strength = 2").is_err());
+    assert!(PresetDefinition::parse("bad", "over_subtraction").is_err());
+
+    // Quoted strings are TOML strings: a # inside is not a comment, escapes are decoded
+    let preset = PresetDefinition::parse("mix", "description = \"mix #2, \\\"loud\\\"\" # fan\n").unwrap();
+    assert_eq!(preset.description, "mix #2, \"loud\"");
+    assert!(PresetDefinition::parse("bad", "description = \"unterminated").is_err());
+
+    // Front-ends list presets as JSON, which must survive the same characters
+    let json = serde_json::to_string(&preset).unwrap();
+    assert_eq!(serde_json::from_str::<PresetDefinition>(&json).unwrap(), preset);
+}
+
+#[test]
+fn test_registry_lookup_and_override() {
+    let mut registry = PresetRegistry::default();
+    assert_eq!(registry.iter().count(), 4);
+    assert_eq!(registry.get("heavy").unwrap().over_subtraction, 3.0);
+    let error = registry.get("podcast").unwrap_err();
+    assert!(error.contains("light, medium, heavy, extreme"), "{}", error);
+
+    registry.add(PresetDefinition::parse("podcast", "over_subtraction = 2.5").unwrap());
+    registry.add(PresetDefinition::parse("heavy", "over_subtraction = 3.5").unwrap());
+    assert_eq!(registry.iter().count(), 5);
+    assert_eq!(registry.get("podcast").unwrap().over_subtraction, 2.5);
+    assert_eq!(registry.get("heavy").unwrap().over_subtraction, 3.5);
+
+    let mut config = NoiseReductionConfig { remove_dc: false, ..NoiseReductionConfig::default() };
+    registry.get("podcast").unwrap().apply(&mut config);
+    assert_eq!(config.over_subtraction, 2.5);
+    assert!(!config.remove_dc);
+}
@@ -0,0 +1,81 @@
+// Learned noise profile tests
+// Run with: cargo test -p bg-noise-reduction-core --test profile
+
+mod common;
+
+use bg_noise_reduction_core::{AudioProcessor, NoiseReductionConfig, FRAME_SIZE};
+use common::{noise, tone, SAMPLE_RATE};
+
+const LEN: usize = SAMPLE_RATE as usize * 3;
+
+/// Amplitude of the 1 kHz tone in `samples`, away from the edges
+fn tone_amplitude(samples: &[f32]) -> f32 {
+    let range = FRAME_SIZE..samples.len() - FRAME_SIZE;
+    let reference = tone(samples.len(), 1000.0, 0.2);
+    let projection: f32 = range.clone().map(|i| samples[i] * reference[i]).sum();
+    let energy: f32 = range.map(|i| reference[i] * reference[i]).sum();
+    0.2 * projection / energy
+}
+
+fn config() -> NoiseReductionConfig {
+    NoiseReductionConfig { sample_rate: SAMPLE_RATE, makeup_gain: 1.0, ..NoiseReductionConfig::default() }
+}
+
+#[test]
+fn test_learns_flat_profile_from_white_noise() {
+    let mut processor = AudioProcessor::new(FRAME_SIZE);
+    let profile = processor.learn_noise_profile(&noise(1, LEN, 0.03), &config()).unwrap();
+
+    assert_eq!(profile.sample_rate, SAMPLE_RATE);
+    assert_eq!(profile.magnitudes.len(), FRAME_SIZE / 2 + 1);
+    assert_eq!(profile.frequencies()[FRAME_SIZE / 2], SAMPLE_RATE as f32 / 2.0);
+
+    // Away from DC (removed) the white noise spectrum is flat within a few dB
+    let db = profile.magnitudes_db();
+    let band = &db[20..FRAME_SIZE / 2 - 20];
+    let (low, high) = band.iter().fold((f32::MAX, f32::MIN), |(low, high), &v| (low.min(v), high.max(v)));
+    assert!(high - low < 6.0, "profile spans {:.1} dB", high - low);
+}
+
+#[test]
+fn test_profile_keeps_speech_without_noise_intro() {
+    // Tone from the first sample: the default estimate takes it for noise
+    let input: Vec<f32> = tone(LEN, 1000.0, 0.2).iter().zip(noise(2, LEN, 0.03)).map(|(s, n)| s + n).collect();
+    let mut processor = AudioProcessor::new(FRAME_SIZE);
+
+    let estimated = processor.process(&input, &config());
+    let profile = processor.learn_noise_profile(&noise(3, LEN, 0.03), &config()).unwrap();
+    let learned = processor.process(&input, &NoiseReductionConfig { noise_profile: Some(profile), ..config() });
+
+    let (estimated, learned) = (tone_amplitude(&estimated), tone_amplitude(&learned));
+    assert!(learned > 0.18, "tone at {:.3} with the learned profile", learned);
+    assert!(estimated < 0.1, "tone at {:.3} with the estimated profile", estimated);
+}
+
+#[test]
+fn test_rejects_short_or_mismatched_profiles() {
+    let mut processor = AudioProcessor::new(FRAME_SIZE);
+    assert!(processor.learn_noise_profile(&noise(4, FRAME_SIZE - 1, 0.03), &config()).is_err());
+
+    let mut profile = processor.learn_noise_profile(&noise(4, LEN, 0.03), &config()).unwrap();
+    profile.magnitudes.pop();
+    let config = NoiseReductionConfig { noise_profile: Some(profile), ..config() };
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn test_profile_fits_other_frame_sizes() {
+    // On noise alone, the learned profile removes as much as the estimate at the same frame size
+    let input = noise(5, LEN, 0.03);
+    let profile = AudioProcessor::new(FRAME_SIZE).learn_noise_profile(&noise(6, LEN, 0.03), &config()).unwrap();
+    for frame_size in [512, FRAME_SIZE, 8192] {
+        let mut processor = AudioProcessor::new(frame_size);
+        assert_eq!(processor.learn_noise_profile(&input, &config()).unwrap().magnitudes.len(), FRAME_SIZE / 2 + 1);
+
+        let estimated = processor.process(&input, &config());
+        let learned = processor.process(&input, &NoiseReductionConfig { noise_profile: Some(profile.clone()), ..config() });
+        let range = frame_size..LEN - frame_size;
+        let ratio_db = 20.0 * (common::rms(&learned[range.clone()]) / common::rms(&estimated[range])).log10();
+        assert!(ratio_db.abs() < 1.5, "frame size {}: learned profile leaves {:.1} dB more than the estimate", frame_size, ratio_db);
+    }
+}
@@ -0,0 +1,195 @@
+// Property-based tests of AudioProcessor::process
+// Run with: cargo test -p bg-noise-reduction-core --test properties
+
+mod common;
+
+use bg_noise_reduction_core::{
+    Algorithm, AudioProcessor, DereverbConfig, FilterKind, FilterSpec, GateConfig, GateKey, GateMode, LimiterConfig, LoudnessTarget,
+    NoiseReductionConfig, NoiseTrackingConfig, TrackingMethod, FRAME_SIZE,
+};
+use common::tone;
+use proptest::prelude::*;
+
+/// Any float, weighted towards the edges processing has to survive
+fn edgy(normal: std::ops::Range<f32>) -> impl Strategy<Value = f32> {
+    prop_oneof![
+        6 => normal,
+        1 => -1e3f32..0.0,
+        1 => 1e3f32..1e30,
+        1 => Just(f32::NAN),
+        1 => Just(f32::INFINITY),
+        1 => Just(f32::NEG_INFINITY),
+    ]
+}
+
+/// Filters of any kind, including zero and non-finite frequencies, gains and Qs
+fn any_filter() -> impl Strategy<Value = FilterSpec> {
+    let kind = prop_oneof![
+        Just(FilterKind::HighPass),
+        Just(FilterKind::LowPass),
+        Just(FilterKind::LowShelf),
+        Just(FilterKind::HighShelf),
+        Just(FilterKind::Peaking),
+    ];
+    (kind, edgy(20.0..20_000.0), edgy(-24.0..24.0), prop_oneof![4 => edgy(0.1..10.0), 1 => Just(0.0)])
+        .prop_map(|(kind, frequency, gain_db, q)| FilterSpec { kind, frequency, gain_db, q })
+}
+
+fn any_noise_tracking() -> impl Strategy<Value = NoiseTrackingConfig> {
+    (edgy(0.01..5.0), prop_oneof![Just(TrackingMethod::Percentile), Just(TrackingMethod::Minimum)], edgy(1.0..99.0), edgy(0.0..1.0))
+        .prop_map(|(window_s, method, percentile, smoothing)| NoiseTrackingConfig { window_s, method, percentile, smoothing })
+}
+
+fn any_dereverb() -> impl Strategy<Value = DereverbConfig> {
+    (prop::option::of(edgy(0.01..3.0)), edgy(0.0..200.0), edgy(0.0..3.0), edgy(0.0..1.0))
+        .prop_map(|(t60, late_onset_ms, strength, floor)| DereverbConfig { t60, late_onset_ms, strength, floor })
+}
+
+fn any_gate() -> impl Strategy<Value = GateConfig> {
+    let mode = prop_oneof![Just(GateMode::Broadband), Just(GateMode::PerBand)];
+    let key = prop_oneof![Just(GateKey::Level), Just(GateKey::Snr), Just(GateKey::Vad)];
+    (edgy(-80.0..20.0), edgy(1.0..20.0), edgy(-60.0..0.0), edgy(0.0..100.0), edgy(0.0..500.0), edgy(0.0..500.0), mode, key).prop_map(
+        |(threshold_db, ratio, range_db, attack_ms, hold_ms, release_ms, mode, key)| GateConfig {
+            threshold_db,
+            ratio,
+            range_db,
+            attack_ms,
+            hold_ms,
+            release_ms,
+            mode,
+            key,
+        },
+    )
+}
+
+fn any_loudness() -> impl Strategy<Value = LoudnessTarget> {
+    (edgy(-40.0..0.0), edgy(-6.0..0.0)).prop_map(|(integrated_lufs, true_peak_ceiling)| LoudnessTarget { integrated_lufs, true_peak_ceiling })
+}
+
+fn any_limiter() -> impl Strategy<Value = LimiterConfig> {
+    (edgy(-12.0..0.0), edgy(0.0..500.0), edgy(0.0..20.0))
+        .prop_map(|(ceiling, release_ms, lookahead_ms)| LimiterConfig { ceiling, release_ms, lookahead_ms })
+}
+
+/// Configurations including out-of-range and non-finite parameters, with any of the
+/// optional stages and filters
+fn any_config() -> impl Strategy<Value = NoiseReductionConfig> {
+    let core = (
+        0usize..30,
+        edgy(0.0..1.0),
+        edgy(0.0..5.0),
+        edgy(0.0..3.0),
+        prop_oneof![Just("subtraction"), Just("spectral-gate"), Just("spectral-gate-nonstationary")],
+        any::<bool>(),
+    );
+    let stages = (
+        prop::collection::vec(any_filter().boxed(), 0..3),
+        prop::collection::vec(any_filter().boxed(), 0..3),
+        prop::option::of(any_noise_tracking().boxed()),
+        prop::option::of(any_dereverb().boxed()),
+        prop::option::of(any_gate().boxed()),
+        prop::option::of(any_loudness().boxed()),
+        prop::option::of(any_limiter().boxed()),
+    );
+    (core, stages).prop_map(
+        |(
+            (noise_frames, spectral_floor, over_subtraction, makeup_gain, algorithm, remove_dc),
+            (pre_filters, post_filters, noise_tracking, dereverb, gate, loudness, limiter),
+        )| NoiseReductionConfig {
+            noise_frames,
+            spectral_floor,
+            over_subtraction,
+            makeup_gain,
+            algorithm: algorithm.parse::<Algorithm>().unwrap(),
+            remove_dc,
+            sample_rate: 16000,
+            pre_filters,
+            post_filters,
+            noise_tracking,
+            dereverb,
+            gate,
+            loudness,
+            limiter,
+            ..NoiseReductionConfig::default()
+        },
+    )
+}
+
+/// Audio in -1.0..1.0 of any length up to three frames, with some samples replaced by NaN or infinity
+fn any_samples() -> impl Strategy<Value = Vec<f32>> {
+    (prop::collection::vec(-1.0f32..1.0, 0..3 * FRAME_SIZE), prop::collection::vec((any::<prop::sample::Index>(), 0..3usize), 0..4))
+        .prop_map(|(mut samples, bad)| {
+            if !samples.is_empty() {
+                for (index, kind) in bad {
+                    let i = index.index(samples.len());
+                    samples[i] = [f32::NAN, f32::INFINITY, f32::NEG_INFINITY][kind];
+                }
+            }
+            samples
+        })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(48))]
+
+    #[test]
+    fn test_output_has_input_length_and_is_finite(
+        samples in any_samples(),
+        config in any_config(),
+        frame_size in prop_oneof![Just(256usize), Just(FRAME_SIZE), Just(4096)],
+    ) {
+        let output = AudioProcessor::new(frame_size).process(&samples, &config);
+        prop_assert_eq!(output.len(), samples.len());
+        prop_assert!(output.iter().all(|s| s.is_finite()), "non-finite output");
+    }
+
+    #[test]
+    fn test_zero_subtraction_is_near_identity(samples in prop::collection::vec(-1.0f32..1.0, FRAME_SIZE..3 * FRAME_SIZE)) {
+        let config = NoiseReductionConfig { over_subtraction: 0.0, makeup_gain: 1.0, remove_dc: false, ..NoiseReductionConfig::default() };
+        let output = AudioProcessor::new(FRAME_SIZE).process(&samples, &config);
+        // The first quarter frame is covered by the rising half of one window only and fades in
+        for (i, (o, s)) in output.iter().zip(&samples).enumerate().skip(FRAME_SIZE / 4) {
+            prop_assert!((o - s).abs() < 1e-4, "sample {}: {} in, {} out", i, s, o);
+        }
+    }
+
+    #[test]
+    fn test_linear_in_gain(samples in prop::collection::vec(-0.1f32..0.1, FRAME_SIZE..3 * FRAME_SIZE), gain in 0.1f32..10.0) {
+        let config = NoiseReductionConfig::default();
+        let mut processor = AudioProcessor::new(FRAME_SIZE);
+        let output = processor.process(&samples, &config);
+        let scaled: Vec<f32> = samples.iter().map(|s| s * gain).collect();
+        let scaled_output = processor.process(&scaled, &config);
+
+        let peak = output.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        for (a, b) in scaled_output.iter().zip(&output) {
+            prop_assert!((a - gain * b).abs() <= 1e-3 * gain * (peak + 1e-3));
+        }
+    }
+}
+
+#[test]
+fn test_edge_cases() {
+    let mut processor = AudioProcessor::new(FRAME_SIZE);
+    let config = NoiseReductionConfig::default();
+    assert!(processor.process(&[], &config).is_empty());
+
+    let all_nan = processor.process(&vec![f32::NAN; 3 * FRAME_SIZE], &config);
+    assert!(all_nan.iter().all(|&s| s == 0.0));
+
+    // No noise frames behaves as one; out-of-range parameters as their nearest valid value
+    let samples = tone(3 * FRAME_SIZE, 940.0, 0.1);
+    let one_frame = processor.process(&samples, &NoiseReductionConfig { noise_frames: 1, ..config.clone() });
+    assert_eq!(processor.process(&samples, &NoiseReductionConfig { noise_frames: 0, ..config.clone() }), one_frame);
+    let zero_floor = processor.process(&samples, &NoiseReductionConfig { spectral_floor: 0.0, ..config.clone() });
+    assert_eq!(processor.process(&samples, &NoiseReductionConfig { spectral_floor: -5.0, ..config.clone() }), zero_floor);
+    let huge = processor.process(&samples, &NoiseReductionConfig { over_subtraction: 1e30, ..config.clone() });
+    assert!(huge.iter().all(|s| s.is_finite()));
+
+    // Invalid filters are skipped and invalid stage settings run with the stage defaults
+    let bad_filter = FilterSpec { q: 0.0, ..FilterSpec::highpass(80.0) };
+    assert_eq!(processor.process(&samples, &NoiseReductionConfig { post_filters: vec![bad_filter], ..config.clone() }), processor.process(&samples, &config));
+    let limited = NoiseReductionConfig { limiter: Some(LimiterConfig::default()), ..config.clone() };
+    let bad_limiter = NoiseReductionConfig { limiter: Some(LimiterConfig { ceiling: f32::NAN, release_ms: -1.0, ..LimiterConfig::default() }), ..config.clone() };
+    assert_eq!(processor.process(&samples, &bad_limiter), processor.process(&samples, &limited));
+}
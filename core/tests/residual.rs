@@ -0,0 +1,66 @@
+// Removed-noise residual tests
+// Run with: cargo test -p bg-noise-reduction-core --test residual
+
+mod common;
+
+use bg_noise_reduction_core::residual;
+use bg_noise_reduction_core::{AudioProcessor, NoiseReductionConfig, FRAME_SIZE};
+use common::{noise, rms, tone, SAMPLE_RATE};
+
+const LEN: usize = SAMPLE_RATE as usize * 3;
+
+#[test]
+fn test_recovers_delay_and_gain() {
+    let input = noise(1, LEN, 0.03);
+    for delay in [0isize, 37, -250] {
+        let output: Vec<f32> = (0..LEN)
+            .map(|i| i.checked_add_signed(-delay).and_then(|j| input.get(j)).map_or(0.0, |s| 0.5 * s))
+            .collect();
+        let alignment = residual::align(&input, &output);
+        assert_eq!(alignment.delay, delay);
+        assert!((alignment.gain - 2.0).abs() < 0.01, "gain {:.3}", alignment.gain);
+
+        // Apart from the samples shifted out, nothing is left
+        let difference = residual::residual_with(&input, &output, alignment);
+        let inner = &difference[300..LEN - 300];
+        assert!(rms(inner) < 1e-6, "residual RMS {:.2e} at delay {}", rms(inner), delay);
+    }
+}
+
+#[test]
+fn test_residual_holds_the_noise_not_the_tone() {
+    // Noise-only lead-in for the estimate, then tone over noise; makeup gain must not leak the tone
+    let noisy: Vec<f32> = noise(2, LEN, 0.03)
+        .iter()
+        .zip(tone(LEN, 1000.0, 0.2))
+        .enumerate()
+        .map(|(i, (n, t))| if i < SAMPLE_RATE as usize { *n } else { n + t })
+        .collect();
+    let config = NoiseReductionConfig { sample_rate: SAMPLE_RATE, makeup_gain: 1.5, ..NoiseReductionConfig::default() };
+    let output = AudioProcessor::new(FRAME_SIZE).process(&noisy, &config);
+    // No overlap-add blow-up at the edges, and the tail after the last full frame is kept
+    assert!(output.iter().all(|s| s.abs() < 0.5), "peak {:.2}", output.iter().fold(0.0f32, |p, s| p.max(s.abs())));
+    assert!(rms(&output[LEN - 512..]) > 0.1);
+
+    let alignment = residual::align(&noisy, &output);
+    assert_eq!(alignment.delay, 0);
+    let difference = residual::residual_with(&noisy, &output, alignment);
+    let speech = SAMPLE_RATE as usize + FRAME_SIZE..LEN - FRAME_SIZE;
+
+    // Tone amplitude left in the residual, by projection onto the clean tone
+    let reference = tone(LEN, 1000.0, 0.2);
+    let projection: f32 = speech.clone().map(|i| difference[i] * reference[i]).sum();
+    let energy: f32 = speech.clone().map(|i| reference[i] * reference[i]).sum();
+    let leaked = 0.2 * projection / energy;
+    assert!(leaked.abs() < 0.02, "{:.3} of the 0.2 tone in the residual", leaked);
+    assert!(rms(&difference[..SAMPLE_RATE as usize]) > 0.5 * rms(&noisy[..SAMPLE_RATE as usize]));
+}
+
+#[test]
+fn test_silent_or_empty_output() {
+    let input = noise(3, LEN, 0.03);
+    let alignment = residual::align(&input, &vec![0.0; LEN]);
+    assert_eq!(alignment.gain, 0.0);
+    assert_eq!(residual::residual(&input, &vec![0.0; LEN]), input);
+    assert_eq!(residual::residual(&[], &[]), Vec::<f32>::new());
+}
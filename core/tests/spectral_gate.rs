@@ -0,0 +1,72 @@
+// Spectral gating (noisereduce-style) tests
+// Run with: cargo test -p bg-noise-reduction-core --test spectral_gate
+
+mod common;
+
+use bg_noise_reduction_core::{Algorithm, AudioProcessor, NoiseReductionConfig, SpectralGateConfig};
+use common::{noise, tone, SAMPLE_RATE};
+
+// Tone bursts at 1-2 s and 5-6 s over hiss whose level follows `noise_level(t)`
+fn bursts_over_noise(noise_level: impl Fn(f32) -> f32) -> Vec<f32> {
+    let len = SAMPLE_RATE as usize * 8;
+    noise(777, len, 1.0)
+        .iter()
+        .zip(tone(len, 500.0, 0.3))
+        .enumerate()
+        .map(|(i, (n, s))| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let on = (1.0..2.0).contains(&t) || (5.0..6.0).contains(&t);
+            noise_level(t) * n + if on { s } else { 0.0 }
+        })
+        .collect()
+}
+
+fn rms(samples: &[f32], from: f32, to: f32) -> f32 {
+    let range = (from * SAMPLE_RATE as f32) as usize..(to * SAMPLE_RATE as f32) as usize;
+    (samples[range.clone()].iter().map(|s| s * s).sum::<f32>() / range.len() as f32).sqrt()
+}
+
+// A pure tone only lights a few bins, so keep the frequency smoothing narrow
+fn gated(input: &[f32], gating: SpectralGateConfig) -> Vec<f32> {
+    let gating = SpectralGateConfig { freq_mask_smooth_hz: 50.0, ..gating };
+    let config = NoiseReductionConfig {
+        algorithm: Algorithm::SpectralGate(gating),
+        makeup_gain: 1.0,
+        sample_rate: SAMPLE_RATE,
+        ..NoiseReductionConfig::default()
+    };
+    AudioProcessor::new(2048).process(input, &config)
+}
+
+#[test]
+fn test_stationary_gate_removes_steady_noise() {
+    let input = bursts_over_noise(|_| 0.012);
+    let output = gated(&input, SpectralGateConfig::default());
+
+    let pause_ratio = rms(&output, 3.0, 4.5) / rms(&input, 3.0, 4.5);
+    assert!(pause_ratio < 0.3, "pause ratio {}", pause_ratio);
+    let tone_ratio = rms(&output, 1.2, 1.8) / rms(&input, 1.2, 1.8);
+    assert!(tone_ratio > 0.5, "tone ratio {}", tone_ratio);
+}
+
+#[test]
+fn test_prop_decrease_scales_reduction() {
+    let input = bursts_over_noise(|_| 0.012);
+    let half = gated(&input, SpectralGateConfig { prop_decrease: 0.5, ..SpectralGateConfig::default() });
+
+    let ratio = rms(&half, 3.0, 4.5) / rms(&input, 3.0, 4.5);
+    assert!((0.45..0.6).contains(&ratio), "pause ratio {}", ratio);
+}
+
+#[test]
+fn test_non_stationary_gate_tracks_rising_noise() {
+    // Noise is quiet during the profile frames and 4x louder by the end
+    let input = bursts_over_noise(|t| 0.006 * (1.0 + 3.0 * (t / 8.0).min(1.0)));
+    let stationary = gated(&input, SpectralGateConfig::default());
+    let non_stationary = gated(&input, SpectralGateConfig::non_stationary());
+
+    let late_pause = |output: &[f32]| rms(output, 6.5, 7.5);
+    assert!(late_pause(&non_stationary) < late_pause(&stationary) * 0.5);
+    let tone_ratio = rms(&non_stationary, 5.2, 5.8) / rms(&input, 5.2, 5.8);
+    assert!(tone_ratio > 0.5, "tone ratio {}", tone_ratio);
+}
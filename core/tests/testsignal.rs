@@ -0,0 +1,93 @@
+// Synthetic test signal tests
+// Run with: cargo test -p bg-noise-reduction-core --test testsignal
+
+use bg_noise_reduction_core::testsignal::{self, NoiseColor, Sweep};
+use bg_noise_reduction_core::{analysis, metrics, AudioProcessor, FRAME_SIZE};
+
+const SAMPLE_RATE: u32 = 16000;
+const LEN: usize = SAMPLE_RATE as usize * 4;
+
+/// Mean power in dB between two frequencies, summed over the bins
+fn band_db(samples: &[f32], low_hz: f32, high_hz: f32) -> f32 {
+    let frames = AudioProcessor::new(FRAME_SIZE).stft_magnitudes(samples);
+    let bin_hz = SAMPLE_RATE as f32 / FRAME_SIZE as f32;
+    let bins = (low_hz / bin_hz) as usize..(high_hz / bin_hz) as usize;
+    let power: f32 = frames.iter().map(|frame| frame[bins.clone()].iter().map(|m| m * m).sum::<f32>()).sum();
+    10.0 * (power / frames.len() as f32).log10()
+}
+
+#[test]
+fn test_generators_are_seeded_and_normalized() {
+    for color in [NoiseColor::White, NoiseColor::Pink, NoiseColor::Brown] {
+        let a = testsignal::noise(color, LEN, 7);
+        assert_eq!(a, testsignal::noise(color, LEN, 7), "{} not reproducible", color);
+        assert_ne!(a, testsignal::noise(color, LEN, 8), "{} ignores the seed", color);
+        assert!((testsignal::rms(&a) - 1.0).abs() < 1e-3);
+    }
+    let speech = testsignal::speech_like(LEN, SAMPLE_RATE, 3);
+    assert_eq!(speech, testsignal::speech_like(LEN, SAMPLE_RATE, 3));
+    assert_eq!(speech.len(), LEN);
+    assert!((testsignal::rms(&speech) - 1.0).abs() < 1e-3);
+    assert!(speech.iter().all(|s| s.is_finite()));
+}
+
+#[test]
+fn test_noise_color_slopes() {
+    // Energy per octave: white rises 3 dB per octave, pink is flat, brown falls 3 dB
+    for (color, expected) in [(NoiseColor::White, 6.0), (NoiseColor::Pink, 0.0), (NoiseColor::Brown, -6.0)] {
+        let samples = testsignal::noise(color, LEN, 1);
+        let change = band_db(&samples, 2000.0, 4000.0) - band_db(&samples, 500.0, 1000.0);
+        assert!((change - expected).abs() < 1.5, "{}: {:+.1} dB over two octaves", color, change);
+    }
+}
+
+#[test]
+fn test_hum_tone_and_chirp() {
+    let hum = testsignal::hum(LEN, SAMPLE_RATE, 60.0, 6);
+    let found = analysis::report(&[hum], SAMPLE_RATE).hum.expect("hum not detected");
+    assert_eq!(found.fundamental_hz, 60.0);
+
+    // Frequency from zero crossings over 100 ms windows at the start, middle and end
+    let crossings = |s: &[f32]| s.windows(2).filter(|w| w[0] < 0.0 && w[1] >= 0.0).count() as f32 * 10.0;
+    let tenth = SAMPLE_RATE as usize / 10;
+
+    let tone = testsignal::tone(LEN, SAMPLE_RATE, 440.0);
+    assert!((testsignal::rms(&tone) - 0.5f32.sqrt()).abs() < 1e-3);
+    for start in [0, LEN / 2, LEN - tenth] {
+        assert!((crossings(&tone[start..start + tenth]) - 440.0).abs() <= 10.0);
+    }
+    let duration = LEN as f32 / SAMPLE_RATE as f32;
+    for sweep in [Sweep::Linear, Sweep::Exponential] {
+        let chirp = testsignal::chirp(LEN, SAMPLE_RATE, 100.0, 4000.0, sweep);
+        assert!(chirp.iter().all(|s| s.abs() <= 1.0));
+        for start in [0, LEN / 2, LEN - tenth] {
+            let t = (start + tenth / 2) as f32 / SAMPLE_RATE as f32 / duration;
+            let expected = match sweep {
+                Sweep::Linear => 100.0 + 3900.0 * t,
+                Sweep::Exponential => 100.0 * 40f32.powf(t),
+            };
+            let measured = crossings(&chirp[start..start + tenth]);
+            assert!((measured - expected).abs() < 0.05 * expected + 20.0, "{} at {:.2}: {:.0} Hz, expected {:.0}", sweep, t, measured, expected);
+        }
+    }
+}
+
+#[test]
+fn test_speech_like_has_pauses_and_formants() {
+    let speech = testsignal::speech_like(LEN, SAMPLE_RATE, 5);
+    let silent = speech.chunks(320).filter(|frame| frame.iter().all(|&s| s == 0.0)).count();
+    assert!(silent > 10, "only {} silent 20 ms frames", silent);
+    // Formants keep most of the energy below 4 kHz
+    assert!(band_db(&speech, 100.0, 4000.0) - band_db(&speech, 4000.0, 8000.0) > 20.0);
+}
+
+#[test]
+fn test_mix_at_snr() {
+    let speech = testsignal::speech_like(LEN, SAMPLE_RATE, 2);
+    let noise = testsignal::noise(NoiseColor::Pink, LEN / 3, 2);
+    for snr in [-5.0, 0.0, 10.0, 30.0] {
+        let mixture = testsignal::mix_at_snr(&speech, &noise, snr);
+        assert_eq!(mixture.len(), LEN);
+        assert!((metrics::snr(&speech, &mixture) - snr).abs() < 0.05);
+    }
+}
@@ -1,4 +1,6 @@
-use bg_noise_reduction_core::{AudioProcessor, NoiseReductionConfig};
+use bg_noise_reduction_core::{analysis, anc, dereverb, loudness, multichannel, AecConfig, Algorithm, AncConfig, AudioProcessor, AutoSettings, BeamformConfig, Steering, DereverbConfig, FilterSpec, GateConfig, HarmonicConfig, LimiterConfig, LoudnessTarget, NoiseReductionConfig, NoiseTrackingConfig, PresetRegistry, SpectralGateConfig};
+use bg_noise_reduction_core::SignalAnalysis;
+use serde::Serialize;
 use wasm_bindgen::prelude::*;
 
 // When the `wee_alloc` feature is enabled, use `wee_alloc` as the global allocator
@@ -13,6 +15,31 @@ pub struct NoiseReduction {
     config: NoiseReductionConfig,
 }
 
+/// What `apply_auto` measured and chose
+#[derive(Serialize)]
+struct AutoReport {
+    #[serde(flatten)]
+    analysis: SignalAnalysis,
+    settings: AutoSettings,
+}
+
+impl Default for NoiseReduction {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NoiseReduction {
+    /// Apply `change` to a copy of the configuration and keep it only if it validates
+    fn update(&mut self, change: impl FnOnce(&mut NoiseReductionConfig)) -> Result<(), JsValue> {
+        let mut config = self.config.clone();
+        change(&mut config);
+        config.validate().map_err(|e: String| JsValue::from_str(&e))?;
+        self.config = config;
+        Ok(())
+    }
+}
+
 #[wasm_bindgen]
 impl NoiseReduction {
     /// Create a new noise reduction processor with default settings
@@ -48,6 +75,109 @@ impl NoiseReduction {
         self.processor.process(samples, &self.config)
     }
 
+    /// Process interleaved multichannel samples and return them interleaved
+    ///
+    /// Channels are combined according to the channel mode, see `set_channel_mode`.
+    /// With beamforming enabled the result is a single (mono) channel.
+    #[wasm_bindgen]
+    pub fn process_interleaved(&mut self, samples: &[f32], channels: usize) -> Vec<f32> {
+        let buffers = multichannel::deinterleave(samples, channels);
+        multichannel::interleave(&self.processor.process_channels(&buffers, &self.config))
+    }
+
+    /// Process mono microphone samples, cancelling the echo of `far_end` (the call's playback signal)
+    ///
+    /// `far_end` must be time-aligned with `samples` and at the same sample rate.
+    #[wasm_bindgen]
+    pub fn process_with_far_end(&mut self, samples: &[f32], far_end: &[f32]) -> Vec<f32> {
+        self.processor.process_with_far_end(samples, far_end, &self.config)
+    }
+
+    /// Configure the echo canceller used by `process_with_far_end`
+    ///
+    /// Out-of-range or non-finite values are rejected and the previous settings kept.
+    #[wasm_bindgen]
+    pub fn set_echo_cancellation(
+        &mut self,
+        tail_ms: f32,
+        step_size: f32,
+        double_talk_threshold: f32,
+        residual_suppression: f32,
+    ) -> Result<(), JsValue> {
+        self.update(|config| {
+            config.echo_cancellation = AecConfig {
+                tail_ms,
+                step_size,
+                double_talk_threshold,
+                residual_suppression,
+            }
+        })
+    }
+
+    /// Set how channels are combined: "linked-max", "linked-mean", "linked-mid", "mid-side" or "independent"
+    #[wasm_bindgen]
+    pub fn set_channel_mode(&mut self, mode: &str) -> Result<(), JsValue> {
+        self.config.channel_mode = mode.parse().map_err(|e: String| JsValue::from_str(&e))?;
+        Ok(())
+    }
+
+    /// Beamform multichannel input to mono before noise reduction
+    ///
+    /// `method` is "delay-and-sum" or "mvdr". `positions` holds x, y, z in metres for each
+    /// microphone; pass an empty array to estimate delays with GCC-PHAT instead.
+    #[wasm_bindgen]
+    pub fn set_beamforming(&mut self, method: &str, positions: &[f32], azimuth_deg: f32, elevation_deg: f32) -> Result<(), JsValue> {
+        if !positions.len().is_multiple_of(3) {
+            return Err(JsValue::from_str("positions must hold x, y, z for each microphone"));
+        }
+        let steering = if positions.is_empty() {
+            Steering::Estimated
+        } else {
+            Steering::Geometry {
+                positions: positions.chunks_exact(3).map(|p| [p[0], p[1], p[2]]).collect(),
+                azimuth_deg,
+                elevation_deg,
+            }
+        };
+        self.config.beamforming = Some(BeamformConfig {
+            method: method.parse().map_err(|e: String| JsValue::from_str(&e))?,
+            steering,
+            ..BeamformConfig::default()
+        });
+        Ok(())
+    }
+
+    /// Disable beamforming
+    #[wasm_bindgen]
+    pub fn clear_beamforming(&mut self) {
+        self.config.beamforming = None;
+    }
+
+    /// Analyze mono samples and set noise frames, spectral floor, over-subtraction and makeup gain for them
+    ///
+    /// Uses the current sample rate. Returns a JSON string with the measurements
+    /// (`noise_floor_db`, `signal_level_db`, `snr_db`, `noise_flatness`,
+    /// `leading_noise_s`, `peak`) and the chosen `settings`; non-finite levels are `null`.
+    #[wasm_bindgen]
+    pub fn apply_auto(&mut self, samples: &[f32]) -> String {
+        let analysis = analysis::analyze(samples, self.config.sample_rate);
+        let settings = AutoSettings::from_analysis(&analysis, self.config.sample_rate);
+        settings.apply(&mut self.config);
+        serde_json::to_string(&AutoReport { analysis, settings }).expect("analysis serializes to JSON")
+    }
+
+    /// Set noise frames, spectral floor, over-subtraction and makeup gain from a built-in preset
+    ///
+    /// See `preset_table` for the available names.
+    #[wasm_bindgen]
+    pub fn set_preset(&mut self, name: &str) -> Result<(), JsValue> {
+        PresetRegistry::default()
+            .get(name)
+            .map_err(|e| JsValue::from_str(&e))?
+            .apply(&mut self.config);
+        Ok(())
+    }
+
     /// Set all configuration parameters at once (avoids aliasing issues)
     #[wasm_bindgen]
     pub fn set_config(&mut self, noise_frames: usize, spectral_floor: f32, over_subtraction: f32, makeup_gain: f32) {
@@ -81,15 +211,252 @@ impl NoiseReduction {
         self.config.makeup_gain = value;
     }
 
+    /// Set the sample rate of the samples passed to `process`
+    #[wasm_bindgen]
+    pub fn set_sample_rate(&mut self, value: u32) {
+        self.config.sample_rate = value;
+    }
+
+    /// Enable or disable DC offset removal before processing
+    #[wasm_bindgen]
+    pub fn set_remove_dc(&mut self, value: bool) {
+        self.config.remove_dc = value;
+    }
+
+    /// Add a filter applied before processing, e.g. "hp:80"
+    ///
+    /// Spec format is `<type>:<freq>[:<gain_db>][:<q>]` with type hp, lp,
+    /// lowshelf, highshelf or peak.
+    #[wasm_bindgen]
+    pub fn add_pre_filter(&mut self, spec: &str) -> Result<(), JsValue> {
+        let spec: FilterSpec = spec.parse().map_err(|e: String| JsValue::from_str(&e))?;
+        self.config.pre_filters.push(spec);
+        Ok(())
+    }
+
+    /// Add a filter applied to the output, e.g. "highshelf:8000:2"
+    #[wasm_bindgen]
+    pub fn add_post_filter(&mut self, spec: &str) -> Result<(), JsValue> {
+        let spec: FilterSpec = spec.parse().map_err(|e: String| JsValue::from_str(&e))?;
+        self.config.post_filters.push(spec);
+        Ok(())
+    }
+
+    /// Remove all pre- and post-filters
+    #[wasm_bindgen]
+    pub fn clear_filters(&mut self) {
+        self.config.pre_filters.clear();
+        self.config.post_filters.clear();
+    }
+
+    /// Normalize output to a target loudness (LUFS) with a true-peak ceiling (dBTP)
+    /// instead of applying makeup gain
+    ///
+    /// The target must be within -70..=0 LUFS and the ceiling within -60..=0 dBTP.
+    #[wasm_bindgen]
+    pub fn set_loudness_target(&mut self, integrated_lufs: f32, true_peak_ceiling: f32) -> Result<(), JsValue> {
+        self.update(|config| config.loudness = Some(LoudnessTarget { integrated_lufs, true_peak_ceiling }))
+    }
+
+    /// Go back to the fixed makeup gain output stage
+    #[wasm_bindgen]
+    pub fn clear_loudness_target(&mut self) {
+        self.config.loudness = None;
+    }
+
+    /// Re-estimate the noise at every frame from a trailing window of `window_s` seconds
+    ///
+    /// `method` is "percentile" or "minimum"; `percentile` applies to the percentile method.
+    #[wasm_bindgen]
+    pub fn set_noise_tracking(&mut self, window_s: f32, method: &str, percentile: f32) -> Result<(), JsValue> {
+        self.config.noise_tracking = Some(NoiseTrackingConfig {
+            window_s,
+            method: method.parse().map_err(|e: String| JsValue::from_str(&e))?,
+            percentile,
+            ..NoiseTrackingConfig::default()
+        });
+        Ok(())
+    }
+
+    /// Go back to the fixed noise profile from the first frames
+    #[wasm_bindgen]
+    pub fn clear_noise_tracking(&mut self) {
+        self.config.noise_tracking = None;
+    }
+
+    /// Use noisereduce-style spectral gating instead of spectral subtraction
+    ///
+    /// `n_std_thresh` applies to stationary mode and `time_constant_s` to non-stationary mode.
+    #[wasm_bindgen]
+    pub fn set_spectral_gate(
+        &mut self,
+        stationary: bool,
+        n_std_thresh: f32,
+        prop_decrease: f32,
+        freq_mask_smooth_hz: f32,
+        time_mask_smooth_ms: f32,
+        time_constant_s: f32,
+    ) {
+        self.config.algorithm = Algorithm::SpectralGate(SpectralGateConfig {
+            stationary,
+            n_std_thresh,
+            prop_decrease,
+            freq_mask_smooth_hz,
+            time_mask_smooth_ms,
+            time_constant_s,
+            ..SpectralGateConfig::default()
+        });
+    }
+
+    /// Switch back to spectral subtraction
+    #[wasm_bindgen]
+    pub fn set_spectral_subtraction(&mut self) {
+        self.config.algorithm = Algorithm::SpectralSubtraction;
+    }
+
+    /// Enable harmonic regeneration after subtraction
+    ///
+    /// `mix` weights the enhanced versus regenerated spectrum (0.0-1.0).
+    #[wasm_bindgen]
+    pub fn set_harmonic_regeneration(&mut self, mix: f32) {
+        self.config.harmonic_regeneration = Some(HarmonicConfig { mix });
+    }
+
+    /// Disable harmonic regeneration
+    #[wasm_bindgen]
+    pub fn clear_harmonic_regeneration(&mut self) {
+        self.config.harmonic_regeneration = None;
+    }
+
+    /// Enable late-reverberation suppression
+    ///
+    /// Pass a `t60` of zero or less to estimate the reverberation time from the input.
+    #[wasm_bindgen]
+    pub fn set_dereverb(&mut self, t60: f32, strength: f32) {
+        self.config.dereverb = Some(DereverbConfig {
+            t60: (t60 > 0.0).then_some(t60),
+            strength,
+            ..DereverbConfig::default()
+        });
+    }
+
+    /// Disable dereverberation
+    #[wasm_bindgen]
+    pub fn clear_dereverb(&mut self) {
+        self.config.dereverb = None;
+    }
+
+    /// Enable the noise gate / downward expander after spectral subtraction
+    ///
+    /// `mode` is "broadband" or "per-band"; `key` is "level", "snr" or "vad".
+    #[wasm_bindgen]
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_gate(
+        &mut self,
+        threshold_db: f32,
+        ratio: f32,
+        range_db: f32,
+        attack_ms: f32,
+        hold_ms: f32,
+        release_ms: f32,
+        mode: &str,
+        key: &str,
+    ) -> Result<(), JsValue> {
+        let gate = GateConfig {
+            threshold_db,
+            ratio,
+            range_db,
+            attack_ms,
+            hold_ms,
+            release_ms,
+            mode: mode.parse().map_err(|e: String| JsValue::from_str(&e))?,
+            key: key.parse().map_err(|e: String| JsValue::from_str(&e))?,
+        };
+        self.update(|config| config.gate = Some(gate))
+    }
+
+    /// Disable the noise gate
+    #[wasm_bindgen]
+    pub fn clear_gate(&mut self) {
+        self.config.gate = None;
+    }
+
+    /// Enable the look-ahead true-peak limiter as the final output stage
+    ///
+    /// Out-of-range or non-finite values are rejected and the previous settings kept.
+    #[wasm_bindgen]
+    pub fn set_limiter(&mut self, ceiling: f32, release_ms: f32, lookahead_ms: f32) -> Result<(), JsValue> {
+        self.update(|config| config.limiter = Some(LimiterConfig { ceiling, release_ms, lookahead_ms }))
+    }
+
+    /// Enable the limiter with the library's default ceiling, release and look-ahead
+    #[wasm_bindgen]
+    pub fn enable_limiter(&mut self) {
+        self.config.limiter = Some(LimiterConfig::default());
+    }
+
+    /// Disable the output limiter
+    #[wasm_bindgen]
+    pub fn clear_limiter(&mut self) {
+        self.config.limiter = None;
+    }
+
+    /// Replace the whole configuration from JSON in the shape returned by `get_config`
+    ///
+    /// Fields left out take their defaults. The configuration is validated
+    /// before it replaces the current one.
+    #[wasm_bindgen]
+    pub fn set_config_json(&mut self, json: &str) -> Result<(), JsValue> {
+        let config: NoiseReductionConfig = serde_json::from_str(json).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        config.validate().map_err(|e: String| JsValue::from_str(&e))?;
+        self.config = config;
+        Ok(())
+    }
+
     /// Get current configuration as JSON string
     #[wasm_bindgen]
     pub fn get_config(&self) -> String {
-        format!(
-            r#"{{"noise_frames":{},"spectral_floor":{},"over_subtraction":{},"makeup_gain":{}}}"#,
-            self.config.noise_frames,
-            self.config.spectral_floor,
-            self.config.over_subtraction,
-            self.config.makeup_gain
-        )
+        serde_json::to_string(&self.config).expect("configuration serializes to JSON")
     }
 }
+
+/// Measure integrated loudness, loudness range and true peak of mono samples
+///
+/// Returns a JSON string with `integrated_lufs`, `loudness_range` and `true_peak_dbtp`.
+/// Silent input reports `null` loudness and true peak.
+#[wasm_bindgen]
+pub fn measure_loudness(samples: &[f32], sample_rate: u32) -> String {
+    serde_json::to_string(&loudness::measure(samples, sample_rate)).expect("loudness serializes to JSON")
+}
+
+/// Estimate the reverberation time (T60) of mono samples in seconds
+///
+/// Returns `undefined` when the recording has no usable free decays.
+#[wasm_bindgen]
+pub fn estimate_t60(samples: &[f32], sample_rate: u32) -> Option<f32> {
+    dereverb::estimate_t60(samples, sample_rate)
+}
+
+/// Subtract the noise in `primary` that is correlated with a reference microphone
+///
+/// `method` is "nlms", "rls" or "fdaf". Both inputs are mono at the same sample rate;
+/// the result is as long as `primary`. Run it through `NoiseReduction` for the
+/// uncorrelated remainder.
+#[wasm_bindgen]
+pub fn cancel_noise(primary: &[f32], reference: &[f32], method: &str, filter_length: usize, step_size: f32) -> Result<Vec<f32>, JsValue> {
+    let config = AncConfig {
+        method: method.parse().map_err(|e: String| JsValue::from_str(&e))?,
+        filter_length,
+        step_size,
+        ..AncConfig::default()
+    };
+    Ok(anc::cancel(primary, reference, &config))
+}
+
+/// Built-in presets as a JSON array of objects with `name`, `description`,
+/// `noise_frames`, `spectral_floor`, `over_subtraction` and `makeup_gain`
+#[wasm_bindgen]
+pub fn preset_table() -> String {
+    let registry = PresetRegistry::default();
+    serde_json::to_string(&registry.iter().collect::<Vec<_>>()).expect("presets serialize to JSON")
+}
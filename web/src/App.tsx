@@ -13,7 +13,7 @@ interface AudioMetadata {
 }
 
 function App() {
-  const { isReady, isProcessing, initModule, processAudio, applyPreset } = useNoiseReduction();
+  const { isReady, isProcessing, initModule, processAudio, presets, applyPreset } = useNoiseReduction();
   const [file, setFile] = useState<File | null>(null);
   const [arrayBuffer, setArrayBuffer] = useState<ArrayBuffer | null>(null);
   const [originalSamples, setOriginalSamples] = useState<Float32Array | null>(null);
@@ -22,14 +22,16 @@ function App() {
   const [metadata, setMetadata] = useState<AudioMetadata | null>(null);
   const [error, setError] = useState<string | null>(null);
   const [isAnalyzing, setIsAnalyzing] = useState(false);
-  const [selectedPreset, setSelectedPreset] = useState<'light' | 'medium' | 'heavy' | 'extreme' | null>(null);
+  const [selectedPreset, setSelectedPreset] = useState<string | null>(null);
 
   const config = useRef({
     noise_frames: 10,
     spectral_floor: 0.1,
     over_subtraction: 2.0,
     makeup_gain: 1.5,
+    limiter: false,
   });
+  const [limiterEnabled, setLimiterEnabled] = useState(false);
 
   // Original audio state
   const audioContextRef = useRef<AudioContext | null>(null);
@@ -355,7 +357,7 @@ function App() {
     setIsAnalyzing(true);
 
     try {
-      const result = await processAudio(originalSamples, config.current);
+      const result = await processAudio(originalSamples, sampleRate, config.current);
       setProcessedSamples(result);
       setCurrentTime2(0);
       pausedAtRef2.current = 0;
@@ -374,16 +376,15 @@ function App() {
     downloadBlob(wavBlob, `${fileName}_clean.wav`);
   };
 
-  const handlePreset = (preset: 'light' | 'medium' | 'heavy' | 'extreme') => {
-    applyPreset(preset);
+  const handlePreset = (preset: string) => {
     setSelectedPreset(preset);
-    const presets = {
-      light: { noise_frames: 10, spectral_floor: 0.25, over_subtraction: 1.0, makeup_gain: 1.2 },
-      medium: { noise_frames: 10, spectral_floor: 0.1, over_subtraction: 2.0, makeup_gain: 1.5 },
-      heavy: { noise_frames: 10, spectral_floor: 0.05, over_subtraction: 3.0, makeup_gain: 1.8 },
-      extreme: { noise_frames: 10, spectral_floor: 0.02, over_subtraction: 4.0, makeup_gain: 2.0 },
-    };
-    config.current = presets[preset];
+    config.current = applyPreset(preset, { ...config.current, limiter: limiterEnabled });
+  };
+
+  const handleLimiterToggle = () => {
+    const enabled = !limiterEnabled;
+    setLimiterEnabled(enabled);
+    config.current = { ...config.current, limiter: enabled };
   };
 
   if (!isReady) {
@@ -489,32 +490,39 @@ function App() {
               </div>
 
               <div className="grid grid-cols-4 gap-3 mb-6">
-                {[
-                  { key: 'light', label: 'Light', desc: 'Subtle' },
-                  { key: 'medium', label: 'Medium', desc: 'Balanced' },
-                  { key: 'heavy', label: 'Heavy', desc: 'Aggressive' },
-                  { key: 'extreme', label: 'Extreme', desc: 'Maximum' },
-                ].map(({ key, label, desc }) => (
+                {presets.map(({ name: key, description: desc }) => (
                   <button
                     key={key}
-                    onClick={() => handlePreset(key as any)}
+                    onClick={() => handlePreset(key)}
                     className={`p-3 rounded-lg text-center transition-all ${
                       selectedPreset === key
                         ? 'bg-[#dbb807] text-black font-semibold'
                         : 'bg-gray-800 hover:bg-gray-700 text-gray-300'
                     }`}
                   >
-                    <div className="text-sm">{label}</div>
+                    <div className="text-sm capitalize">{key}</div>
                     <div className={`text-xs ${selectedPreset === key ? 'text-black/70' : 'text-gray-500'}`}>{desc}</div>
                   </button>
                 ))}
               </div>
 
-              <div className="grid grid-cols-4 gap-4 text-xs text-gray-400 bg-black/30 p-3 rounded-lg">
+              <label className="flex items-center gap-3 mb-6 text-sm text-gray-300 cursor-pointer">
+                <input
+                  type="checkbox"
+                  checked={limiterEnabled}
+                  onChange={handleLimiterToggle}
+                  className="accent-[#dbb807] w-4 h-4"
+                />
+                Peak limiter
+                <span className="text-xs text-gray-500">Prevents clipping when gain overshoots</span>
+              </label>
+
+              <div className="grid grid-cols-5 gap-4 text-xs text-gray-400 bg-black/30 p-3 rounded-lg">
                 <div>Noise Frames: {config.current.noise_frames}</div>
                 <div>Spectral Floor: {config.current.spectral_floor}</div>
                 <div>Over Sub: {config.current.over_subtraction}</div>
                 <div>Gain: {config.current.makeup_gain}x</div>
+                <div>Limiter: {limiterEnabled ? 'On' : 'Off'}</div>
               </div>
             </div>
           )}
@@ -1,20 +1,33 @@
 import { useState, useCallback } from 'react';
-import init, { NoiseReduction } from '../../pkg/bg_noise_reduction_wasm';
+import init, { NoiseReduction, preset_table } from '../../pkg/bg_noise_reduction_wasm';
 
 export interface NoiseReductionConfig {
   noise_frames: number;
   spectral_floor: number;
   over_subtraction: number;
   makeup_gain: number;
+  limiter: boolean;
+}
+
+export interface Preset {
+  name: string;
+  description: string;
+  noise_frames: number;
+  spectral_floor: number;
+  over_subtraction: number;
+  makeup_gain: number;
 }
 
 export function useNoiseReduction() {
   const [isReady, setIsReady] = useState(false);
   const [isProcessing, setIsProcessing] = useState(false);
+  const [presets, setPresets] = useState<Preset[]>([]);
 
   const initModule = useCallback(async () => {
     try {
       await init();
+      // The preset table lives in the core library, shared with the CLI
+      setPresets(JSON.parse(preset_table()));
       setIsReady(true);
     } catch (error) {
       console.error('Failed to initialize WASM module:', error);
@@ -23,7 +36,7 @@ export function useNoiseReduction() {
   }, []);
 
   const processAudio = useCallback(
-    async (samples: Float32Array, config: NoiseReductionConfig): Promise<Float32Array> => {
+    async (samples: Float32Array, sampleRate: number, config: NoiseReductionConfig): Promise<Float32Array> => {
       setIsProcessing(true);
 
       try {
@@ -32,6 +45,11 @@ export function useNoiseReduction() {
           setTimeout(() => {
             try {
               const processor = new NoiseReduction();
+              processor.set_sample_rate(sampleRate);
+              if (config.limiter) {
+                // The library's limiter defaults, the same as the CLI's --limiter
+                processor.enable_limiter();
+              }
               resolve(processor.process_with_config(
                 samples,
                 config.noise_frames,
@@ -56,16 +74,29 @@ export function useNoiseReduction() {
     []
   );
 
-  const applyPreset = useCallback((_preset: 'light' | 'medium' | 'heavy' | 'extreme') => {
-    // Presets are handled in JS - config is applied in processAudio
-    // This is a no-op function to maintain API compatibility
-  }, []);
+  const applyPreset = useCallback(
+    (name: string, config: NoiseReductionConfig): NoiseReductionConfig => {
+      const preset = presets.find((p) => p.name === name);
+      if (!preset) {
+        throw new Error(`Unknown preset '${name}'`);
+      }
+      return {
+        ...config,
+        noise_frames: preset.noise_frames,
+        spectral_floor: preset.spectral_floor,
+        over_subtraction: preset.over_subtraction,
+        makeup_gain: preset.makeup_gain,
+      };
+    },
+    [presets]
+  );
 
   return {
     isReady,
     isProcessing,
     initModule,
     processAudio,
+    presets,
     applyPreset,
   };
 }